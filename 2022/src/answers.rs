@@ -0,0 +1,144 @@
+//! The answers manifest, checked against this year's solutions
+
+use std::path::Path;
+
+use aoc_common::answers;
+use eyre::Result;
+
+use crate::{parse::ParseError, Input, Params, PUZZLES};
+
+/// The expected answer to one part of a puzzle for one input file, with this year's parameters
+pub type Expected = answers::Expected<Params>;
+
+/// An expected answer and the answer that was actually given
+pub type Check<'a> = answers::Check<'a, Params>;
+
+/// Parses the answers manifest
+pub fn parse_manifest(input: &Input) -> Result<Vec<Expected>, ParseError> {
+    answers::parse_manifest(input)
+}
+
+/// Solves the expected entry's part of its input file, returning the answer without any
+/// trailing newlines
+pub fn solve(expected: &Expected, inputs: &Path) -> Result<String> {
+    answers::solve(&PUZZLES, expected, inputs)
+}
+
+/// Solves every entry, with input files read from the `inputs` directory
+pub fn verify<'a>(entries: &'a [Expected], inputs: &Path) -> Vec<Check<'a>> {
+    answers::verify(&PUZZLES, entries, inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse::ParseError, Input, Part};
+
+    #[test]
+    fn parse_manifest_test() {
+        let input = Input::from(
+            "
+# day 10
+day10 1 13140
+day10_less_simple 2 |
+  ##..
+  ..##
+
+day15_simple 1 26 row=10
+",
+        );
+        assert_eq!(
+            parse_manifest(&input).unwrap(),
+            vec![
+                Expected {
+                    file: "day10".to_string(),
+                    day: 10,
+                    part: Part::One,
+                    answer: "13140".to_string(),
+                    params: Params::default(),
+                },
+                Expected {
+                    file: "day10_less_simple".to_string(),
+                    day: 10,
+                    part: Part::Two,
+                    answer: "##..\n..##".to_string(),
+                    params: Params::default(),
+                },
+                Expected {
+                    file: "day15_simple".to_string(),
+                    day: 15,
+                    part: Part::One,
+                    answer: "26".to_string(),
+                    params: Params {
+                        row: 10,
+                        ..Params::default()
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_manifest_error_test() {
+        let parse = |s| parse_manifest(&Input::from(s)).unwrap_err();
+        assert_eq!(
+            parse("day1 1 1\ninput 1 1"),
+            ParseError::new(2, 1, "file named day<N>", "input")
+        );
+        assert_eq!(parse("day1 3 1"), ParseError::new(1, 6, "part 1 or 2", "3"));
+        assert_eq!(parse("day1 1"), ParseError::new(1, 7, "answer", ""));
+        assert_eq!(
+            parse("day15 1 26 y=10"),
+            ParseError::new(
+                1,
+                12,
+                "parameter as <key>=<value> (unknown parameter y)",
+                "y=10"
+            )
+        );
+        assert_eq!(
+            parse("day9 1 13 knots=0"),
+            ParseError::new(
+                1,
+                11,
+                "parameter as <key>=<value> (invalid value \"0\" for parameter knots)",
+                "knots=0"
+            )
+        );
+        assert_eq!(
+            parse("day15 1 26 row"),
+            ParseError::new(1, 12, "parameter as <key>=<value>", "row")
+        );
+        assert_eq!(
+            parse("day10 2 |\nday10 1 1"),
+            ParseError::new(2, 1, "indented answer line", "")
+        );
+    }
+
+    #[test]
+    fn verify_test() {
+        let entries = parse_manifest(&Input::from(
+            "day1 1 69836\nday1 2 1\nday1_missing 1 1\nday26 1 1",
+        ))
+        .unwrap();
+        let checks = verify(&entries, Path::new("./testdata"));
+        assert_eq!(
+            checks.iter().map(Check::passed).collect::<Vec<_>>(),
+            vec![true, false, false, false]
+        );
+        assert_eq!(checks[1].actual.as_ref().unwrap(), "207968");
+        assert!(checks[2].actual.is_err());
+    }
+
+    #[test]
+    fn manifest_test() {
+        let entries = parse_manifest(&Input::from_path("./answers.txt").unwrap()).unwrap();
+        for expected in entries {
+            assert!(
+                Path::new("./testdata").join(&expected.file).exists(),
+                "{} not found",
+                expected.file
+            );
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use adventofcode_2022::{
+    animate::{self, Controls},
+    animation,
+    asciicast::Recorder,
+    Input, Params, Part, ANIMATED_DAYS,
+};
+use clap::Parser;
+use eyre::{eyre, Result};
+
+/// Plays a day's simulation in the terminal, or records it
+#[derive(Parser)]
+#[command(name = "anim")]
+struct Args {
+    /// Day to animate
+    #[arg(value_parser = parse_day)]
+    day: u8,
+    /// Puzzle input file, or `-` to read from stdin
+    #[arg(default_value = "-")]
+    input: PathBuf,
+    /// Part whose simulation to play
+    #[arg(short, long, default_value = "1")]
+    part: Part,
+    /// Milliseconds between frames
+    #[arg(long, default_value_t = 50)]
+    delay: u64,
+    /// Simulation steps between frames
+    #[arg(long, default_value_t = 1)]
+    steps_per_frame: usize,
+    /// Puzzle parameter, like `knots=5` or `floor=true`
+    #[arg(long = "param", value_name = "KEY=VALUE")]
+    params: Vec<String>,
+    /// Record every frame to an asciicast v2 file instead of playing
+    #[arg(long, value_name = "FILE")]
+    record: Option<PathBuf>,
+    /// Terminal size of the recording [default: this terminal's size, or 80x24]
+    #[arg(long, value_name = "COLUMNSxROWS", value_parser = parse_size)]
+    size: Option<(usize, usize)>,
+}
+
+fn parse_size(s: &str) -> Result<(usize, usize)> {
+    let (width, height) = s
+        .split_once('x')
+        .ok_or_else(|| eyre!("expected a size like 80x24, got {s:?}"))?;
+    Ok((width.parse()?, height.parse()?))
+}
+
+fn parse_day(s: &str) -> Result<u8> {
+    let day = s.parse()?;
+    if ANIMATED_DAYS.contains(&day) {
+        Ok(day)
+    } else {
+        Err(eyre!(
+            "day {day} has no animation, expected one of {ANIMATED_DAYS:?}"
+        ))
+    }
+}
+
+fn read_input(path: &Path) -> Result<Input> {
+    if path == Path::new("-") {
+        Input::from_stdin()
+    } else {
+        Input::from_path(path)
+    }
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let mut params = Params::default();
+    for param in &args.params {
+        let (key, value) = param
+            .split_once('=')
+            .ok_or_else(|| eyre!("expected a parameter like knots=5, got {param:?}"))?;
+        params.set(key, value)?;
+    }
+
+    let input = read_input(&args.input)?;
+    let mut animation = animation(args.day, args.part, &params, &input)?;
+    let delay = Duration::from_millis(args.delay);
+    match args.record {
+        Some(path) => {
+            let size = match args.size {
+                Some(size) => size,
+                None => termion::terminal_size().map_or((80, 24), |(width, height)| {
+                    (width as usize, height as usize)
+                }),
+            };
+            let title = format!("Day {} part {}", args.day, args.part);
+            let file = BufWriter::new(File::create(&path)?);
+            let mut recorder = Recorder::new(file, size, delay, &title)?;
+            let frames = animate::record(animation.as_mut(), &mut recorder, args.steps_per_frame)?;
+            recorder.into_inner().flush()?;
+            eprintln!("recorded {frames} frames to {}", path.display());
+            Ok(())
+        }
+        None => animate::play(
+            animation.as_mut(),
+            Controls::new(delay, args.steps_per_frame),
+        ),
+    }
+}
@@ -0,0 +1,584 @@
+use std::{
+    fs, io,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use adventofcode_2022::{
+    answers, asm, bench,
+    cache::{self, Http, InputCache},
+    debugger::{self, Breakpoint, Debugger},
+    device::{Display, Schedule},
+    generate, report, scaffold, solution,
+    submit::{History, Submitter},
+    trace::{self, Filter, Level},
+    Input, Params, Part, Solution, DAYS, YEAR,
+};
+use clap::{builder::RangedU64ValueParser, ArgAction, Args, Parser, Subcommand};
+use eyre::{eyre, Result, WrapErr};
+
+#[derive(Parser)]
+#[command(name = "aoc", about = "Advent of Code 2022 solutions")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve one day's puzzle
+    Run(RunArgs),
+    /// Solve every day's parts at once and report answers, times and errors
+    RunAll(RunAllArgs),
+    /// Time each day's parts over many runs
+    Bench(BenchArgs),
+    /// Check every answer in the answers manifest
+    Verify(VerifyArgs),
+    /// Draw a day's puzzle as a PNG or PPM image
+    Image(ImageArgs),
+    /// Step through day 10's program in the terminal
+    Debug(DebugArgs),
+    /// Assemble a day 10 program with labels, constants and `draw` into plain instructions
+    Asm(AsmArgs),
+    /// List a day 10 program with the cycle each instruction starts on and the value of X
+    Disasm(DisasmArgs),
+    /// Make a random puzzle input, printing the answers known from how it was made
+    Generate(GenerateArgs),
+    /// Send an answer to the site, unless earlier attempts show it cannot be right
+    Submit(SubmitArgs),
+    /// Add a day's module, input file and answers entry, and register the day
+    NewDay(NewDayArgs),
+    /// Create a crate for another year, next to this one in the workspace, with its first day
+    NewYear(NewYearArgs),
+}
+
+#[derive(Args)]
+struct RunArgs {
+    /// Day to solve
+    day: u8,
+    /// Puzzle input file, or `-` to read from stdin [default: the day's input from the cache
+    /// in $AOC_CACHE_DIR, fetched with the session token in $AOC_SESSION if it is not there]
+    input: Option<PathBuf>,
+    /// Part to solve; both parts are solved if omitted
+    #[arg(short, long)]
+    part: Option<Part>,
+    /// Explain how the answer is reached on stderr; repeat for more detail
+    #[arg(short, long, action = ArgAction::Count)]
+    verbose: u8,
+    /// Days and levels to explain, like `debug` or `day7=info,day13=trace`; overrides -v
+    #[arg(long, value_name = "FILTER")]
+    trace: Option<Filter>,
+    #[command(flatten)]
+    params: ParamArgs,
+}
+
+#[derive(Args)]
+struct RunAllArgs {
+    /// Days to solve; every day is solved if omitted
+    days: Vec<u8>,
+    /// Directory holding each day's input, named `day<N>`; a day without one is read from the
+    /// input cache
+    #[arg(long, default_value = "testdata")]
+    inputs: PathBuf,
+    /// Part to solve; both parts are solved if omitted
+    #[arg(short, long)]
+    part: Option<Part>,
+    /// Print a JSON document instead of a table
+    #[arg(long)]
+    json: bool,
+    #[command(flatten)]
+    params: ParamArgs,
+}
+
+#[derive(Args)]
+struct BenchArgs {
+    /// Days to time; every day is timed if omitted
+    days: Vec<u8>,
+    /// Directory holding each day's input, named `day<N>`; a day without one is read from the
+    /// input cache
+    #[arg(long, default_value = "testdata")]
+    inputs: PathBuf,
+    /// Part to time; both parts are timed if omitted
+    #[arg(short, long)]
+    part: Option<Part>,
+    /// Times to solve each part
+    #[arg(short = 'n', long, default_value_t = 10)]
+    runs: usize,
+    /// Print tab separated nanoseconds instead of a table
+    #[arg(long)]
+    tsv: bool,
+    #[command(flatten)]
+    params: ParamArgs,
+}
+
+#[derive(Args)]
+struct VerifyArgs {
+    /// Input files to check; every entry in the manifest is checked if omitted
+    files: Vec<String>,
+    /// Answers manifest
+    #[arg(long, default_value = "answers.txt")]
+    answers: PathBuf,
+    /// Directory holding the input files named in the manifest
+    #[arg(long, default_value = "testdata")]
+    inputs: PathBuf,
+}
+
+#[derive(Args)]
+struct ImageArgs {
+    /// Day to draw
+    day: u8,
+    /// Puzzle input file, or `-` to read from stdin
+    #[arg(default_value = "-")]
+    input: PathBuf,
+    /// Image file, whose extension picks the format: .png or .ppm
+    #[arg(short, long)]
+    output: PathBuf,
+    /// Part whose puzzle state to draw
+    #[arg(short, long, default_value = "1")]
+    part: Part,
+    /// Width and height in pixels of each cell
+    #[arg(long, default_value_t = 4, value_parser = positive())]
+    scale: usize,
+    #[command(flatten)]
+    params: ParamArgs,
+}
+
+#[derive(Args)]
+struct DebugArgs {
+    /// Program file [default: day 10's input from the cache]
+    input: Option<PathBuf>,
+    /// Place to stop when continuing, like `cycle=20` or `instruction=3`; repeat for more
+    #[arg(short, long = "break", value_name = "BREAKPOINT")]
+    breakpoints: Vec<Breakpoint>,
+    /// Stop when X changes
+    #[arg(short, long)]
+    watch: bool,
+    #[command(flatten)]
+    display: DisplayArgs,
+}
+
+#[derive(Args)]
+struct AsmArgs {
+    /// Assembly source file, or `-` to read from stdin
+    #[arg(default_value = "-")]
+    input: PathBuf,
+    /// Read the input as an image of `#` and `.`, one line per CRT row, and make a program
+    /// that draws it
+    #[arg(long)]
+    image: bool,
+    #[command(flatten)]
+    display: DisplayArgs,
+}
+
+#[derive(Args)]
+struct DisasmArgs {
+    /// Program file, or `-` to read from stdin
+    #[arg(default_value = "-")]
+    input: PathBuf,
+    #[command(flatten)]
+    display: DisplayArgs,
+}
+
+/// Geometry of day 10's CRT, for devices other than the puzzle's
+#[derive(Args)]
+struct DisplayArgs {
+    /// Day 10 CRT width in pixels
+    #[arg(long, default_value_t = Display::DEVICE.width)]
+    crt_width: NonZeroUsize,
+    /// Day 10 CRT height in pixels
+    #[arg(long, default_value_t = Display::DEVICE.height)]
+    crt_height: NonZeroUsize,
+    /// Day 10 sprite width in pixels
+    #[arg(long, default_value_t = Display::DEVICE.sprite_width)]
+    sprite_width: NonZeroUsize,
+}
+
+/// Parser for counts and sizes that must be at least 1
+fn positive() -> RangedU64ValueParser<usize> {
+    RangedU64ValueParser::new().range(1..)
+}
+
+impl From<DisplayArgs> for Display {
+    fn from(args: DisplayArgs) -> Self {
+        Display {
+            width: args.crt_width,
+            height: args.crt_height,
+            sprite_width: args.sprite_width,
+        }
+    }
+}
+
+#[derive(Args)]
+struct GenerateArgs {
+    /// Day to make an input for
+    day: u8,
+    /// Seed of the random choices; the same seed makes the same input
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// How large an input to make, such as the number of moves or sensors
+    #[arg(long, default_value_t = 10)]
+    size: usize,
+    /// File to write the input to instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct SubmitArgs {
+    /// Day to answer
+    day: u8,
+    /// Part to answer
+    part: Part,
+    /// Answer to send [default: the part solved for the day's cached input]
+    answer: Option<String>,
+    /// File of earlier attempts [default: `submissions` in the input cache directory]
+    #[arg(long, value_name = "FILE")]
+    history: Option<PathBuf>,
+    /// Site to send the answer to
+    #[arg(long, value_name = "URL", default_value = cache::BASE_URL)]
+    base_url: String,
+    /// Send the answer even if earlier attempts show it is too high or too low
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Args)]
+struct NewDayArgs {
+    /// Day to add
+    day: u8,
+    /// Crate to add the day to
+    #[arg(long, default_value = ".")]
+    root: PathBuf,
+}
+
+#[derive(Args)]
+struct NewYearArgs {
+    /// Year of the puzzles
+    year: u16,
+    /// Directory to create the crate in [default: ../<YEAR>]
+    #[arg(long)]
+    root: Option<PathBuf>,
+    /// The shared crate that the new crate registers its days with
+    #[arg(long, default_value = "../aoc-common")]
+    shared: PathBuf,
+}
+
+#[derive(Args)]
+struct ParamArgs {
+    /// Day 6 marker length [default: 4 for part 1, 14 for part 2]
+    #[arg(long, value_parser = positive())]
+    marker_len: Option<usize>,
+    /// Day 9 number of knots [default: 2 for part 1, 10 for part 2]
+    #[arg(long, value_parser = positive())]
+    knots: Option<usize>,
+    /// Day 10 part 2 reads the CRT's letters, or prints the screen as drawn when false
+    #[arg(long, default_value_t = true, action = ArgAction::Set)]
+    ocr: bool,
+    #[command(flatten)]
+    display: DisplayArgs,
+    /// Day 10 cycles to sample in part 1, as `START+STRIDE`, `START+STRIDExCOUNT` or a list
+    /// like `20,60,100`
+    #[arg(long, default_value_t = Schedule::DEVICE)]
+    samples: Schedule,
+    /// Day 14 cave floor [default: false for part 1, true for part 2]
+    #[arg(long)]
+    floor: Option<bool>,
+    /// Day 15 row to inspect in part 1
+    #[arg(long, default_value_t = Params::default().row)]
+    row: i64,
+    /// Day 15 coordinate bound in part 2
+    #[arg(long, default_value_t = Params::default().bound)]
+    bound: i64,
+}
+
+impl From<ParamArgs> for Params {
+    fn from(args: ParamArgs) -> Self {
+        Params {
+            marker_len: args.marker_len,
+            knots: args.knots,
+            ocr: args.ocr,
+            display: args.display.into(),
+            schedule: args.samples,
+            floor: args.floor,
+            row: args.row,
+            bound: args.bound,
+        }
+    }
+}
+
+fn read_input(path: &Path) -> Result<Input> {
+    if path == Path::new("-") {
+        Input::from_stdin()
+    } else {
+        Input::from_path(path)
+    }
+}
+
+/// The day's input from `inputs/day<N>`, or from the input cache if there is no such file
+fn day_input(inputs: &Path, day: u8) -> Result<Input> {
+    let path = inputs.join(format!("day{day}"));
+    if path.exists() {
+        Input::from_path(path)
+    } else {
+        InputCache::from_env()?.get(YEAR, day)
+    }
+}
+
+fn parts(part: Option<Part>) -> Vec<Part> {
+    match part {
+        Some(part) => vec![part],
+        None => Part::BOTH.to_vec(),
+    }
+}
+
+fn find_solution(day: u8, params: &Params) -> Result<Box<dyn Solution>> {
+    solution(day, params).ok_or_else(|| {
+        eyre!(
+            "no solution for day {day}, expected one of {}..={}",
+            DAYS.start(),
+            DAYS.end()
+        )
+    })
+}
+
+fn run(args: RunArgs) -> Result<()> {
+    let filter = args
+        .trace
+        .or_else(|| Level::from_verbosity(args.verbose).map(|level| Filter::day(args.day, level)));
+    if let Some(filter) = filter {
+        trace::enable(filter);
+    }
+
+    let solution = find_solution(args.day, &args.params.into())?;
+    let input = match &args.input {
+        Some(path) => read_input(path)?,
+        None => InputCache::from_env()?.get(YEAR, args.day)?,
+    };
+    let parts = parts(args.part);
+
+    for part in parts {
+        let answer = solution.solve(part, &input)?.to_string();
+        if answer.contains('\n') {
+            println!("Day {} part {part}:\n{answer}", args.day);
+        } else {
+            println!("Day {} part {part}: {answer}", args.day);
+        }
+    }
+
+    Ok(())
+}
+
+fn run_all(args: RunAllArgs) -> Result<()> {
+    let days = if args.days.is_empty() {
+        DAYS.collect()
+    } else {
+        args.days
+    };
+
+    let read_input = |day| day_input(&args.inputs, day);
+    let report = report::run_all(&days, &parts(args.part), read_input, &args.params.into());
+    if args.json {
+        report::write_json(io::stdout().lock(), &report)?;
+    } else {
+        report::write_table(io::stdout().lock(), &report)?;
+    }
+    if report.failures() > 0 {
+        return Err(eyre!("some parts could not be solved"));
+    }
+    Ok(())
+}
+
+fn bench(args: BenchArgs) -> Result<()> {
+    let params = args.params.into();
+    let days = if args.days.is_empty() {
+        DAYS.collect()
+    } else {
+        args.days
+    };
+
+    let mut timings = vec![];
+    for day in days {
+        let solution = find_solution(day, &params)?;
+        let input = day_input(&args.inputs, day)?;
+        for part in parts(args.part) {
+            let timing = bench::bench(day, solution.as_ref(), part, &input, args.runs)
+                .wrap_err_with(|| format!("day {day} part {part}"))?;
+            eprintln!("timed day {day} part {part}");
+            timings.push(timing);
+        }
+    }
+
+    if args.tsv {
+        bench::write_tsv(io::stdout().lock(), &timings)?;
+    } else {
+        bench::write_table(io::stdout().lock(), &timings)?;
+    }
+    Ok(())
+}
+
+fn verify(args: VerifyArgs) -> Result<()> {
+    let manifest = Input::from_path(&args.answers)?;
+    let entries = answers::parse_manifest(&manifest)
+        .wrap_err_with(|| format!("could not parse {}", args.answers.display()))?
+        .into_iter()
+        .filter(|e| args.files.is_empty() || args.files.contains(&e.file))
+        .collect::<Vec<_>>();
+
+    let checks = answers::verify(&entries, &args.inputs);
+    for check in checks.iter().filter(|c| !c.passed()) {
+        let label = format!("{} part {}", check.expected.file, check.expected.part);
+        match &check.actual {
+            Ok(actual) if actual.contains('\n') || check.expected.answer.contains('\n') => {
+                println!(
+                    "{label}: expected\n{}\ngot\n{actual}",
+                    check.expected.answer
+                )
+            }
+            Ok(actual) => println!("{label}: expected {}, got {actual}", check.expected.answer),
+            Err(e) => println!("{label}: {e:#}"),
+        }
+    }
+
+    let passed = checks.iter().filter(|c| c.passed()).count();
+    println!("{passed} of {} answers correct", checks.len());
+    if passed < checks.len() {
+        return Err(eyre!("some answers were incorrect"));
+    }
+    Ok(())
+}
+
+fn image(args: ImageArgs) -> Result<()> {
+    let input = read_input(&args.input)?;
+    let image = adventofcode_2022::image(args.day, args.part, &args.params.into(), &input)?;
+    image.scale(args.scale).save(&args.output)?;
+    Ok(())
+}
+
+fn debug(args: DebugArgs) -> Result<()> {
+    // Commands are typed on stdin, so the program cannot come from there
+    let input = match &args.input {
+        Some(path) if path == Path::new("-") => {
+            return Err(eyre!(
+                "the debugger reads commands from stdin, give a program file"
+            ))
+        }
+        Some(path) => Input::from_path(path)?,
+        None => InputCache::from_env()?.get(YEAR, 10)?,
+    };
+    let mut debugger = Debugger::new(&input)?.with_display(args.display.into());
+    for breakpoint in args.breakpoints {
+        debugger.execute(debugger::Command::Break(breakpoint));
+    }
+    if args.watch {
+        debugger.execute(debugger::Command::Watch);
+    }
+    debugger::run_terminal(&mut debugger)
+}
+
+fn assemble(args: AsmArgs) -> Result<()> {
+    let input = read_input(&args.input)?;
+    let display = args.display.into();
+    let program = if args.image {
+        asm::compile_image(&display, &input)?
+    } else {
+        asm::assemble(&display, &input)?
+    };
+    for instruction in program {
+        println!("{instruction}");
+    }
+    Ok(())
+}
+
+fn disassemble(args: DisasmArgs) -> Result<()> {
+    let program = asm::assemble(&args.display.into(), &read_input(&args.input)?)?;
+    print!("{}", asm::disassemble(&program)?);
+    Ok(())
+}
+
+fn generate(args: GenerateArgs) -> Result<()> {
+    let generated = generate::generate(args.day, args.seed, args.size)?;
+    let file = match &args.output {
+        Some(path) => {
+            fs::write(path, &generated.input)?;
+            path.file_name().map_or_else(
+                || path.display().to_string(),
+                |f| f.to_string_lossy().into(),
+            )
+        }
+        None => {
+            print!("{}", generated.input);
+            format!("day{}", args.day)
+        }
+    };
+
+    // Entries for the answers manifest, on stderr so they stay out of the input
+    for entry in generated.manifest_entries(&file) {
+        eprintln!("{entry}");
+    }
+    Ok(())
+}
+
+fn submit(args: SubmitArgs) -> Result<()> {
+    let session =
+        cache::session().ok_or_else(|| eyre!("set AOC_SESSION to the site's session cookie"))?;
+    let answer = match args.answer {
+        Some(answer) => answer,
+        None => {
+            let solution = find_solution(args.day, &Params::default())?;
+            let input = InputCache::from_env()?.get(YEAR, args.day)?;
+            solution.solve(args.part, &input)?.to_string()
+        }
+    };
+    let history_path = match args.history {
+        Some(path) => path,
+        None => cache::cache_dir()?.join("submissions"),
+    };
+
+    let mut history = History::load(history_path)?;
+    let submitter = Submitter::new(Box::new(Http::new()), &session)
+        .with_base_url(&args.base_url)
+        .with_force(args.force);
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let (verdict, warnings) =
+        submitter.submit(&mut history, YEAR, args.day, args.part, &answer, now)?;
+    for warning in warnings {
+        eprintln!("warning: sent with --force, {warning}");
+    }
+    println!("Day {} part {}: {answer} is {verdict}", args.day, args.part);
+    Ok(())
+}
+
+fn new_day(args: NewDayArgs) -> Result<()> {
+    for path in scaffold::new_day(&args.root, args.day)? {
+        println!("wrote {}", path.display());
+    }
+    Ok(())
+}
+
+fn new_year(args: NewYearArgs) -> Result<()> {
+    let root = args
+        .root
+        .unwrap_or_else(|| Path::new("..").join(args.year.to_string()));
+    for path in scaffold::new_year(&root, args.year, &args.shared)? {
+        println!("wrote {}", path.display());
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    match Cli::parse().command {
+        Command::Run(args) => run(args),
+        Command::RunAll(args) => run_all(args),
+        Command::Bench(args) => bench(args),
+        Command::Verify(args) => verify(args),
+        Command::Image(args) => image(args),
+        Command::Debug(args) => debug(args),
+        Command::Asm(args) => assemble(args),
+        Command::Disasm(args) => disassemble(args),
+        Command::Generate(args) => generate(args),
+        Command::Submit(args) => submit(args),
+        Command::NewDay(args) => new_day(args),
+        Command::NewYear(args) => new_year(args),
+    }
+}
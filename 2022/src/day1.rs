@@ -1,12 +1,18 @@
 use eyre::Result;
-use std::{fs::File, io::prelude::*, io::BufReader};
 
-fn day1_part1(reader: BufReader<File>) -> Result<i32> {
+use crate::{parse::ParseError, Answer, Input, Solution};
+
+fn parse_calories(line: &str) -> Result<i32, ParseError> {
+    line.parse::<i32>()
+        .map_err(|_| ParseError::new(1, 1, "calorie count", line))
+}
+
+fn day1_part1(input: &Input) -> Result<i32> {
     let mut max_calories = 0;
     let mut current_elf_calories = 0;
 
-    for line in reader.lines() {
-        match line?.as_str() {
+    for (idx, line) in input.lines().enumerate() {
+        match line {
             "" => {
                 if current_elf_calories > max_calories {
                     max_calories = current_elf_calories;
@@ -15,7 +21,7 @@ fn day1_part1(reader: BufReader<File>) -> Result<i32> {
                 current_elf_calories = 0;
             }
             line => {
-                let calories = line.parse::<i32>()?;
+                let calories = parse_calories(line).map_err(|e| e.offset_lines(idx))?;
                 current_elf_calories += calories;
             }
         }
@@ -24,18 +30,18 @@ fn day1_part1(reader: BufReader<File>) -> Result<i32> {
     Ok(max_calories)
 }
 
-fn day1_part2(reader: BufReader<File>) -> Result<i32> {
+fn day1_part2(input: &Input) -> Result<i32> {
     let mut elf_calories = vec![];
     let mut current_elf_calories = 0;
 
-    for line in reader.lines() {
-        match line?.as_str() {
+    for (idx, line) in input.lines().enumerate() {
+        match line {
             "" => {
                 elf_calories.push(current_elf_calories);
                 current_elf_calories = 0;
             }
             line => {
-                let calories = line.parse::<i32>()?;
+                let calories = parse_calories(line).map_err(|e| e.offset_lines(idx))?;
                 current_elf_calories += calories;
             }
         }
@@ -45,22 +51,48 @@ fn day1_part2(reader: BufReader<File>) -> Result<i32> {
     Ok(elf_calories.iter().take(3).sum())
 }
 
+pub struct Day1;
+
+impl Solution for Day1 {
+    fn part1(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day1_part1(input)?))
+    }
+
+    fn part2(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day1_part2(input)?))
+    }
+
+    fn parse(&self, input: &Input) -> Result<()> {
+        for (idx, line) in input.lines().enumerate().filter(|(_, l)| !l.is_empty()) {
+            parse_calories(line).map_err(|e| e.offset_lines(idx))?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::{fs::File, io::BufReader};
 
     #[test]
     fn day1_part1_test() {
-        let input = File::open("./testdata/day1").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day1_part1(reader).unwrap(), 69836);
+        let input = Input::from_path("./testdata/day1").unwrap();
+        assert_eq!(day1_part1(&input).unwrap(), 69836);
     }
 
     #[test]
     fn day1_part2_test() {
-        let input = File::open("./testdata/day1").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day1_part2(reader).unwrap(), 207968);
+        let input = Input::from_path("./testdata/day1").unwrap();
+        assert_eq!(day1_part2(&input).unwrap(), 207968);
+    }
+
+    #[test]
+    fn day1_parse_error_test() {
+        let input = Input::from("1000\n2000\n\nabc\n");
+        let err = day1_part1(&input).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ParseError>(),
+            Some(&ParseError::new(4, 1, "calorie count", "abc"))
+        );
     }
 }
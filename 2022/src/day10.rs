@@ -1,125 +1,87 @@
-use std::io::{BufRead, BufReader};
-
-fn day10<T>(reader: BufReader<T>) -> i32
-where
-    T: std::io::Read,
-{
-    let mut x = 1;
-    let mut cycle_n = 0;
-    let mut cycles: Vec<(i32, i32)> = vec![];
-    let mut start_cycle = |x: i32| {
-        cycle_n += 1;
-        cycles.push((cycle_n, x))
-    };
-    for line in reader.lines() {
-        match parse_instruction(&line.unwrap()) {
-            Instruction::Noop => {
-                start_cycle(x);
-            }
-            Instruction::Addx(v) => {
-                start_cycle(x);
-                start_cycle(x);
-                x += v;
-            }
-        }
-    }
+use eyre::Result;
+
+use crate::{
+    grid::Grid,
+    image::{Image, Palette},
+    ocr, Answer, Input, Solution,
+};
+
+pub mod asm;
+mod cpu;
+pub mod debugger;
+pub mod device;
+
+use cpu::parse_program;
+use device::{Display, Schedule};
+
+fn day10(input: &Input, schedule: &Schedule) -> Result<i32> {
+    schedule.signal_strength(parse_program(input)?)
+}
 
-    cycles
-        .into_iter()
-        .filter_map(|(cycle, x)| match cycle {
-            20 | 60 | 100 | 140 | 180 | 220 => Some(cycle * x),
-            _ => None,
-        })
-        .sum()
+fn crt(input: &Input, display: &Display) -> Result<String> {
+    Ok(display.screen(parse_program(input)?)?)
 }
 
-fn day10_part2<T>(reader: BufReader<T>) -> String
-where
-    T: std::io::Read,
-{
-    let mut x = 1;
-    let mut output = String::new();
-    let mut cycle_n = 0;
-    let mut crt_row_pos = 0;
-    let mut start_cycle = |x: i32| {
-        let sprite_pos = x - 1..=x + 1;
-        if sprite_pos.contains(&crt_row_pos) {
-            output.push('#');
-        } else {
-            output.push('.');
-        };
-
-        cycle_n += 1;
-
-        if crt_row_pos == 39 {
-            output.push('\n');
-            crt_row_pos = 0;
-        } else {
-            crt_row_pos += 1;
-        }
-    };
-    for line in reader.lines() {
-        match parse_instruction(&line.unwrap()) {
-            Instruction::Noop => {
-                start_cycle(x);
-            }
-            Instruction::Addx(v) => {
-                start_cycle(x);
-                start_cycle(x);
-                x += v;
-            }
-        }
+fn day10_part2(input: &Input, display: &Display, ocr: bool) -> Result<String> {
+    let screen = crt(input, display)?;
+    if ocr {
+        Ok(ocr::read(&screen)?)
+    } else {
+        Ok(screen)
     }
+}
 
-    output
+/// The CRT's screen once the program has run
+pub fn image(input: &Input, display: &Display) -> Result<Image> {
+    let screen = Grid::parse(&crt(input, display)?, "# or .", Some)?;
+    let palette = Palette::new([15, 15, 35]).with('#', [255, 230, 110]);
+    Ok(Image::from_grid(&screen, |c| palette.colour(c)))
 }
 
-#[derive(Debug)]
-enum Instruction {
-    Noop,
-    Addx(i32),
+/// Part 1 samples the signal strength on the `schedule`'s cycles. Part 2 reads the letters on
+/// the `display`, or gives the screen itself when `ocr` is false.
+pub struct Day10 {
+    pub ocr: bool,
+    pub display: Display,
+    pub schedule: Schedule,
 }
 
-fn parse_instruction(input: &str) -> Instruction {
-    let mut split = input.split(' ');
-    let (op, v) = (
-        split.next().unwrap(),
-        split.next().map(|s| s.parse::<i32>().unwrap()),
-    );
-
-    match op {
-        "noop" => Instruction::Noop,
-        "addx" => Instruction::Addx(v.unwrap()),
-        _ => panic!("unexpected instruction"),
+impl Solution for Day10 {
+    fn part1(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day10(input, &self.schedule)?))
+    }
+
+    fn part2(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day10_part2(input, &self.display, self.ocr)?))
+    }
+
+    fn parse(&self, input: &Input) -> Result<()> {
+        parse_program(input)?;
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{fs::File, io::BufReader};
-
     use super::*;
 
     #[test]
     fn day10_test_less_simple() {
-        let input = File::open("./testdata/day10_less_simple").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day10(reader), 13140);
+        let input = Input::from_path("./testdata/day10_less_simple").unwrap();
+        assert_eq!(day10(&input, &Schedule::DEVICE).unwrap(), 13140);
     }
 
     #[test]
     fn day10_test() {
-        let input = File::open("./testdata/day10").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day10(reader), 12540);
+        let input = Input::from_path("./testdata/day10").unwrap();
+        assert_eq!(day10(&input, &Schedule::DEVICE).unwrap(), 12540);
     }
 
     #[test]
-    fn day10_part2_test_less_simple() {
-        let input = File::open("./testdata/day10_less_simple").unwrap();
-        let reader = BufReader::new(input);
+    fn crt_test_less_simple() {
+        let input = Input::from_path("./testdata/day10_less_simple").unwrap();
         assert_eq!(
-            day10_part2(reader),
+            crt(&input, &Display::DEVICE).unwrap(),
             "
 ##..##..##..##..##..##..##..##..##..##..
 ###...###...###...###...###...###...###.
@@ -133,11 +95,10 @@ mod tests {
     }
 
     #[test]
-    fn day10_part2_test() {
-        let input = File::open("./testdata/day10").unwrap();
-        let reader = BufReader::new(input);
+    fn crt_test() {
+        let input = Input::from_path("./testdata/day10").unwrap();
         assert_eq!(
-            day10_part2(reader),
+            crt(&input, &Display::DEVICE).unwrap(),
             "
 ####.####..##..####.####.#....#..#.####.
 #....#....#..#....#.#....#....#..#.#....
@@ -149,4 +110,26 @@ mod tests {
             .trim_start_matches('\n')
         );
     }
+
+    #[test]
+    fn day10_part2_test() {
+        let input = Input::from_path("./testdata/day10").unwrap();
+        assert_eq!(
+            day10_part2(&input, &Display::DEVICE, true).unwrap(),
+            "FECZELHE"
+        );
+
+        let input = Input::from_path("./testdata/day10_less_simple").unwrap();
+        let error = day10_part2(&input, &Display::DEVICE, true)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.starts_with("unknown letters at columns 1, 6,"),
+            "{error}"
+        );
+        assert_eq!(
+            day10_part2(&input, &Display::DEVICE, false).unwrap(),
+            crt(&input, &Display::DEVICE).unwrap()
+        );
+    }
 }
@@ -0,0 +1,415 @@
+//! Writing programs for the CPU by hand: an assembler with labels, comments, constants and a
+//! `draw` pseudo-instruction, and a disassembler that shows what each instruction runs with
+//!
+//! Each line of assembly is one of these, optionally after a `label:` and before a `; comment`:
+//!
+//! - `NAME = VALUE`, a constant
+//! - `draw PATTERN`, instructions that make the CRT draw the pattern of `#` and `.` from where
+//!   the beam is
+//! - an instruction, such as `addx WIDTH - 1`
+//!
+//! A value is a sum of numbers and names, where a label stands for the cycle its instruction
+//! starts on. Names must be defined before they are used.
+
+use std::collections::{BTreeMap, HashMap};
+
+use eyre::{eyre, Result};
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while1},
+    character::complete::{self as cc, alpha1, alphanumeric1, char, one_of, space0, space1},
+    combinator::{consumed, cut, map, opt, recognize},
+    error::context,
+    multi::{many0, many0_count},
+    sequence::{pair, preceded, terminated, tuple},
+};
+
+use super::{
+    cpu::{Cpu, Instruction, Opcode, Overflow, Registers, MNEMONICS},
+    device::Display,
+};
+use crate::parse::{parse_all, IResult, ParseError};
+
+enum Term<'a> {
+    Number(i32),
+    Name(&'a str),
+}
+
+/// A value as it is written, with its text for reporting errors
+struct Expr<'a> {
+    text: &'a str,
+    /// Terms to add, each with its sign
+    terms: Vec<(i32, Term<'a>)>,
+}
+
+enum Statement<'a> {
+    Constant(&'a str, Expr<'a>),
+    Draw(&'a str),
+    Instruction(&'a str, Option<Expr<'a>>),
+}
+
+struct Line<'a> {
+    label: Option<&'a str>,
+    statement: Option<Statement<'a>>,
+}
+
+fn name(i: &str) -> IResult<'_, &str> {
+    recognize(pair(
+        alt((alpha1, tag("_"))),
+        many0_count(alt((alphanumeric1, tag("_")))),
+    ))(i)
+}
+
+fn term(i: &str) -> IResult<'_, Term<'_>> {
+    context(
+        "number or name",
+        alt((map(cc::i32, Term::Number), map(name, Term::Name))),
+    )(i)
+}
+
+fn expr(i: &str) -> IResult<'_, Expr<'_>> {
+    let signed = pair(preceded(space0, one_of("+-")), preceded(space0, cut(term)));
+    map(
+        consumed(pair(term, many0(signed))),
+        |(text, (first, rest))| Expr {
+            text,
+            terms: std::iter::once((1, first))
+                .chain(
+                    rest.into_iter()
+                        .map(|(sign, t)| (if sign == '-' { -1 } else { 1 }, t)),
+                )
+                .collect(),
+        },
+    )(i)
+}
+
+fn statement(i: &str) -> IResult<'_, Statement<'_>> {
+    alt((
+        map(
+            tuple((name, space0, char('='), space0, cut(expr))),
+            |(name, _, _, _, value)| Statement::Constant(name, value),
+        ),
+        map(
+            preceded(
+                pair(tag("draw"), space1),
+                cut(context(
+                    "pattern of # and .",
+                    take_while1(|c| c == '#' || c == '.'),
+                )),
+            ),
+            Statement::Draw,
+        ),
+        map(
+            pair(name, opt(preceded(space1, expr))),
+            |(mnemonic, value)| Statement::Instruction(mnemonic, value),
+        ),
+    ))(i)
+}
+
+fn line(i: &str) -> IResult<'_, Line<'_>> {
+    map(
+        tuple((
+            space0,
+            opt(terminated(name, pair(char(':'), space0))),
+            opt(statement),
+            space0,
+        )),
+        |(_, label, statement, _)| Line { label, statement },
+    )(i)
+}
+
+/// Instructions that make the display draw the pixels, `true` for lit, starting after `cycles`
+/// cycles with `X` at `x`, or `None` if it cannot. `X` only changes at the end of an `addx`,
+/// which draws two pixels with the old value, so not every pattern can be drawn from every
+/// place. The fewest instructions are used.
+pub fn draw(display: &Display, pixels: &[bool], cycles: usize, x: i32) -> Option<Vec<Instruction>> {
+    let drawn = |k: usize, x: i32| {
+        let (_, column) = display.position(cycles + k + 1);
+        display.sprite(x).contains(&(column as i32)) == pixels[k]
+    };
+    let targets = display.sprite_positions();
+
+    // For each pixel, the values `X` can have when the beam reaches it, with how many
+    // instructions it takes and the pixel, value and instruction that came before
+    type Step = (usize, Option<(usize, i32, Instruction)>);
+    let mut reached = vec![BTreeMap::<i32, Step>::new(); pixels.len() + 1];
+    reached[0].insert(x, (0, None));
+    for k in 0..pixels.len() {
+        let states = reached[k]
+            .iter()
+            .map(|(&x, &(count, _))| (x, count))
+            .collect::<Vec<_>>();
+        for (x, count) in states {
+            if !drawn(k, x) {
+                continue;
+            }
+            let mut reach = |k: usize, target: i32, instruction: Instruction| {
+                let step = (
+                    count + 1,
+                    Some((k - instruction.opcode.cycles, x, instruction)),
+                );
+                let best = reached[k].entry(target).or_insert(step);
+                if step.0 < best.0 {
+                    *best = step;
+                }
+            };
+            reach(k + 1, x, Instruction::new("noop", 0));
+            if k + 1 < pixels.len() && drawn(k + 1, x) {
+                for target in targets.clone() {
+                    reach(k + 2, target, Instruction::new("addx", target - x));
+                }
+            }
+        }
+    }
+
+    let &(_, mut step) = reached[pixels.len()]
+        .values()
+        .min_by_key(|(count, _)| *count)?;
+    let mut program = vec![];
+    while let Some((k, x, instruction)) = step {
+        program.push(instruction);
+        step = reached[k][&x].1;
+    }
+    program.reverse();
+    Some(program)
+}
+
+/// Assembles the source into the plain instructions that `parse_instruction` takes, with `draw`
+/// drawing on the display
+pub fn assemble(display: &Display, source: &str) -> Result<Vec<Instruction>, ParseError> {
+    let mut names = HashMap::new();
+    let mut registers = Registers::default();
+    let mut cycles = 0;
+    let mut program = vec![];
+
+    for (idx, text) in source.lines().enumerate() {
+        let code = text.split(';').next().unwrap_or_default();
+        let line = parse_all(code, line).map_err(|e| e.offset_lines(idx))?;
+        let error = |at: &str, expected: &str| ParseError::at(text, at, expected).offset_lines(idx);
+        let define = |names: &mut HashMap<String, i32>, name: &str, value: i32| {
+            if names.insert(name.to_string(), value).is_some() {
+                return Err(error(name, "name that is not defined yet"));
+            }
+            Ok(())
+        };
+        let eval = |names: &HashMap<String, i32>, expr: &Expr| {
+            expr.terms.iter().try_fold(0i32, |sum, (sign, term)| {
+                let value = match term {
+                    Term::Number(n) => *n,
+                    Term::Name(name) => *names
+                        .get(*name)
+                        .ok_or_else(|| error(name, "defined name"))?,
+                };
+                sign.checked_mul(value)
+                    .and_then(|value| sum.checked_add(value))
+                    .ok_or_else(|| error(expr.text, "value that fits in 32 bits"))
+            })
+        };
+
+        if let Some(label) = line.label {
+            define(&mut names, label, cycles as i32 + 1)?;
+        }
+        let instructions = match line.statement {
+            None => vec![],
+            Some(Statement::Constant(name, value)) => {
+                let value = eval(&names, &value)?;
+                define(&mut names, name, value)?;
+                vec![]
+            }
+            Some(Statement::Draw(pattern)) => {
+                let pixels = pattern.chars().map(|c| c == '#').collect::<Vec<_>>();
+                draw(display, &pixels, cycles, registers.x).ok_or_else(|| {
+                    let expected = format!(
+                        "pattern the CRT can draw from cycle {} with X={}",
+                        cycles + 1,
+                        registers.x
+                    );
+                    error(pattern, &expected)
+                })?
+            }
+            Some(Statement::Instruction(mnemonic, value)) => {
+                let opcode = Opcode::named(mnemonic).ok_or_else(|| error(mnemonic, &MNEMONICS))?;
+                let value = match (opcode.takes_value, value) {
+                    (true, Some(value)) => eval(&names, &value)?,
+                    (true, None) => return Err(error(&code[code.len()..], "value")),
+                    (false, Some(value)) => return Err(error(value.text, "end of line")),
+                    (false, None) => 0,
+                };
+                vec![Instruction { opcode, value }]
+            }
+        };
+        for instruction in instructions {
+            registers =
+                (instruction.opcode.execute)(registers, instruction.value).ok_or_else(|| {
+                    let expected = format!("instruction that keeps X={} in 32 bits", registers.x);
+                    error(code.trim(), &expected)
+                })?;
+            cycles += instruction.opcode.cycles;
+            program.push(instruction);
+        }
+    }
+
+    Ok(program)
+}
+
+/// The program with each instruction followed by a comment of the cycle it starts on and the
+/// value of `X` while it runs, in a form the assembler takes back
+pub fn disassemble(program: &[Instruction]) -> Result<String, Overflow> {
+    let mut listing = String::new();
+    let mut pc = None;
+    for state in Cpu::new(program.to_vec()) {
+        let state = state?;
+        if pc == Some(state.pc) {
+            continue;
+        }
+        pc = Some(state.pc);
+        listing.push_str(&format!(
+            "{:<10} ; cycle {:>3}  X={}\n",
+            program[state.pc].to_string(),
+            state.cycle,
+            state.registers.x
+        ));
+    }
+    Ok(listing)
+}
+
+/// A program that makes the display draw the image of `#` and `.`, one line per row, with the
+/// fewest instructions
+pub fn compile_image(display: &Display, image: &str) -> Result<Vec<Instruction>> {
+    let rows = image.lines().count();
+    if rows != display.height.get() {
+        return Err(eyre!(
+            "the image has {rows} rows, expected {}",
+            display.height
+        ));
+    }
+    if let Some((idx, row)) = image
+        .lines()
+        .enumerate()
+        .find(|(_, r)| r.len() != display.width.get())
+    {
+        return Err(eyre!(
+            "row {} is {} pixels wide, expected {}",
+            idx + 1,
+            row.len(),
+            display.width
+        ));
+    }
+    let pixels = image
+        .lines()
+        .flat_map(|row| row.chars().map(|c| c == '#'))
+        .collect::<Vec<_>>();
+    let x = Registers::default().x;
+    draw(display, &pixels, 0, x).ok_or_else(|| {
+        eyre!(
+            "the display cannot draw the image, the sprite starts at columns {:?}",
+            display.sprite(x)
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{day10::parse_program, ocr, Input};
+
+    const DEVICE: Display = Display::DEVICE;
+
+    fn text(program: &[Instruction]) -> Vec<String> {
+        program.iter().map(|i| i.to_string()).collect()
+    }
+
+    #[test]
+    fn assemble_test() {
+        let source = "
+; a test pattern
+STEP = 3
+start:  addx STEP      ; X = 4
+        noop
+later: addx start - later + -1
+        draw ..##
+";
+        let program = assemble(&DEVICE, source).unwrap();
+        assert_eq!(text(&program)[..3], ["addx 3", "noop", "addx -4"]);
+        // The pattern starts on cycle 6, with X at 0
+        assert_eq!(&DEVICE.screen(program).unwrap()[5..9], "..##");
+    }
+
+    #[test]
+    fn assemble_error_test() {
+        let error = |source| assemble(&DEVICE, source).unwrap_err();
+        assert_eq!(
+            error("noop\naddx WIDTH"),
+            ParseError::new(2, 6, "defined name", "WIDTH")
+        );
+        assert_eq!(
+            error("a = 1\na: noop"),
+            ParseError::new(2, 1, "name that is not defined yet", "a")
+        );
+        assert_eq!(
+            error("mulx 3"),
+            ParseError::new(1, 1, "noop or addx", "mulx")
+        );
+        assert_eq!(error("addx ; none"), ParseError::new(1, 6, "value", ""));
+        assert_eq!(error("noop 1"), ParseError::new(1, 6, "end of line", "1"));
+        assert_eq!(
+            error("addx 1 +"),
+            ParseError::new(1, 9, "number or name", "")
+        );
+        assert_eq!(
+            error("A = 2147483647\naddx A + 1"),
+            ParseError::new(2, 6, "value that fits in 32 bits", "A + 1")
+        );
+        assert_eq!(
+            error("addx 2147483646\naddx 1"),
+            ParseError::new(
+                2,
+                1,
+                "instruction that keeps X=2147483647 in 32 bits",
+                "addx 1"
+            )
+        );
+        assert_eq!(
+            error("draw #."),
+            ParseError::new(1, 6, "pattern the CRT can draw from cycle 1 with X=1", "#.")
+        );
+    }
+
+    #[test]
+    fn disassemble_test() {
+        let program = assemble(&DEVICE, "addx 3\nnoop\naddx -5\nnoop").unwrap();
+        let listing = disassemble(&program).unwrap();
+        assert_eq!(
+            listing,
+            "\
+addx 3     ; cycle   1  X=1
+noop       ; cycle   3  X=4
+addx -5    ; cycle   4  X=4
+noop       ; cycle   6  X=-1
+"
+        );
+        assert_eq!(assemble(&DEVICE, &listing).unwrap(), program);
+    }
+
+    #[test]
+    fn compile_image_test() {
+        let input = Input::from_path("./testdata/day10").unwrap();
+        let image = DEVICE.screen(parse_program(&input).unwrap()).unwrap();
+        let program = compile_image(&DEVICE, &image).unwrap();
+        assert_eq!(DEVICE.screen(program.clone()).unwrap(), image);
+        assert_eq!(
+            ocr::read(&DEVICE.screen(program).unwrap()).unwrap(),
+            "FECZELHE"
+        );
+
+        let blank = format!("{}\n", ".".repeat(40)).repeat(6);
+        assert!(compile_image(&DEVICE, &blank).is_err());
+        assert!(compile_image(&DEVICE, "##").is_err());
+
+        // A wider sprite on a smaller screen can draw what the device's cannot
+        let display = Display::new(8, 2, 4).unwrap();
+        let image = "####....\n..####..\n";
+        let program = compile_image(&display, image).unwrap();
+        assert_eq!(display.screen(program).unwrap(), image);
+    }
+}
@@ -0,0 +1,350 @@
+//! The handheld device's CPU: its registers, instruction set and the cycles a program takes
+
+use std::fmt;
+
+use lazy_static::lazy_static;
+use nom::{
+    character::complete::{self as cc, alpha1, char},
+    combinator::{cut, map_opt},
+    error::context,
+    sequence::preceded,
+};
+
+use crate::parse::{parse_all, IResult, ParseError};
+
+/// Values of the CPU's registers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Registers {
+    pub x: i32,
+}
+
+impl Default for Registers {
+    /// Registers as the CPU starts, with `X` at 1
+    fn default() -> Self {
+        Registers { x: 1 }
+    }
+}
+
+/// An instruction the CPU knows: how it is written, how many cycles it takes and what it does to
+/// the registers when the last of them is over
+pub struct Opcode {
+    pub mnemonic: &'static str,
+    /// At least 1
+    pub cycles: usize,
+    /// Whether the instruction takes a value, as in `addx 3`
+    pub takes_value: bool,
+    /// The registers after the instruction, or `None` if one of them overflows
+    pub execute: fn(Registers, i32) -> Option<Registers>,
+}
+
+impl Opcode {
+    pub fn named(mnemonic: &str) -> Option<&'static Opcode> {
+        INSTRUCTION_SET.iter().find(|op| op.mnemonic == mnemonic)
+    }
+}
+
+impl fmt::Debug for Opcode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.mnemonic)
+    }
+}
+
+impl PartialEq for Opcode {
+    fn eq(&self, other: &Self) -> bool {
+        self.mnemonic == other.mnemonic
+    }
+}
+
+impl Eq for Opcode {}
+
+/// Every instruction the CPU knows
+pub static INSTRUCTION_SET: [Opcode; 2] = [
+    Opcode {
+        mnemonic: "noop",
+        cycles: 1,
+        takes_value: false,
+        execute: |r, _| Some(r),
+    },
+    Opcode {
+        mnemonic: "addx",
+        cycles: 2,
+        takes_value: true,
+        execute: |r, v| {
+            Some(Registers {
+                x: r.x.checked_add(v)?,
+            })
+        },
+    },
+];
+
+lazy_static! {
+    /// The mnemonics, as the parser expects them, e.g. "noop or addx"
+    pub(super) static ref MNEMONICS: String = match INSTRUCTION_SET.split_last() {
+        Some((last, [])) => last.mnemonic.to_string(),
+        Some((last, rest)) => format!(
+            "{} or {}",
+            rest.iter().map(|op| op.mnemonic).collect::<Vec<_>>().join(", "),
+            last.mnemonic
+        ),
+        None => "instruction".to_string(),
+    };
+}
+
+/// One line of a program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub opcode: &'static Opcode,
+    /// 0 for instructions that take no value
+    pub value: i32,
+}
+
+impl Instruction {
+    /// The instruction with the mnemonic, which must be in [`INSTRUCTION_SET`]
+    pub fn new(mnemonic: &str, value: i32) -> Instruction {
+        let opcode = Opcode::named(mnemonic).expect("mnemonic must be in the instruction set");
+        Instruction { opcode, value }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.opcode.takes_value {
+            write!(f, "{} {}", self.opcode.mnemonic, self.value)
+        } else {
+            f.write_str(self.opcode.mnemonic)
+        }
+    }
+}
+
+fn instruction(i: &str) -> IResult<'_, Instruction> {
+    let (i, opcode) = context(MNEMONICS.as_str(), map_opt(alpha1, Opcode::named))(i)?;
+    if !opcode.takes_value {
+        return Ok((i, Instruction { opcode, value: 0 }));
+    }
+    let (i, value) = context("value", preceded(char(' '), cut(context("value", cc::i32))))(i)?;
+    Ok((i, Instruction { opcode, value }))
+}
+
+pub fn parse_instruction(input: &str) -> Result<Instruction, ParseError> {
+    parse_all(input, instruction)
+}
+
+/// Parses a program of one instruction per line
+pub fn parse_program(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| parse_instruction(line).map_err(|e| e.offset_lines(idx)))
+        .collect()
+}
+
+/// The CPU during one cycle, before the instruction being executed has taken effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct State {
+    /// 1-based cycle number
+    pub cycle: usize,
+    /// Index of the instruction being executed
+    pub pc: usize,
+    pub registers: Registers,
+}
+
+/// An instruction that left a register too large or too small to hold, which stops the CPU
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow {
+    /// 1-based cycle the instruction finished on
+    pub cycle: usize,
+    pub instruction: Instruction,
+    /// Registers before the instruction took effect
+    pub registers: Registers,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "`{}` overflows X={} on cycle {}",
+            self.instruction, self.registers.x, self.cycle
+        )
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+/// A CPU running a program, iterating over the state during each cycle until the program ends,
+/// or until an instruction overflows and ends it with an error
+#[derive(Debug, Clone)]
+pub struct Cpu {
+    program: Vec<Instruction>,
+    registers: Registers,
+    pc: usize,
+    /// Cycles started so far
+    cycle: usize,
+    /// Cycles of the current instruction that are over
+    elapsed: usize,
+    fault: Option<Overflow>,
+}
+
+impl Cpu {
+    pub fn new(program: Vec<Instruction>) -> Cpu {
+        Cpu {
+            program,
+            registers: Registers::default(),
+            pc: 0,
+            cycle: 0,
+            elapsed: 0,
+            fault: None,
+        }
+    }
+
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
+    /// Registers as they are between cycles
+    pub fn registers(&self) -> Registers {
+        self.registers
+    }
+
+    /// Index of the instruction that the next cycle executes
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    /// Cycles run so far
+    pub fn cycles(&self) -> usize {
+        self.cycle
+    }
+
+    /// Cycles of the current instruction that are over, 0 when the next cycle starts it
+    pub fn elapsed(&self) -> usize {
+        self.elapsed
+    }
+
+    /// The overflow that stopped the program, if one did
+    pub fn fault(&self) -> Option<Overflow> {
+        self.fault
+    }
+
+    pub fn finished(&self) -> bool {
+        self.pc >= self.program.len() || self.fault.is_some()
+    }
+}
+
+impl Iterator for Cpu {
+    type Item = Result<State, Overflow>;
+
+    fn next(&mut self) -> Option<Result<State, Overflow>> {
+        if self.fault.is_some() {
+            return None;
+        }
+        let instruction = *self.program.get(self.pc)?;
+        self.cycle += 1;
+        let state = State {
+            cycle: self.cycle,
+            pc: self.pc,
+            registers: self.registers,
+        };
+
+        if self.elapsed + 1 < instruction.opcode.cycles {
+            self.elapsed += 1;
+            return Some(Ok(state));
+        }
+        match (instruction.opcode.execute)(self.registers, instruction.value) {
+            Some(registers) => {
+                self.registers = registers;
+                self.pc += 1;
+                self.elapsed = 0;
+                Some(Ok(state))
+            }
+            None => {
+                let fault = Overflow {
+                    cycle: self.cycle,
+                    instruction,
+                    registers: self.registers,
+                };
+                self.fault = Some(fault);
+                Some(Err(fault))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn cpu_test() {
+        let program = parse_program("noop\naddx 3\naddx -5").unwrap();
+        let mut cpu = Cpu::new(program);
+        let states = cpu.by_ref().map_ok(|s| (s.cycle, s.pc, s.registers.x));
+        assert_eq!(
+            states.collect::<Result<Vec<_>, _>>().unwrap(),
+            [(1, 0, 1), (2, 1, 1), (3, 1, 1), (4, 2, 4), (5, 2, 4)]
+        );
+        assert_eq!(cpu.registers(), Registers { x: -1 });
+        assert!(cpu.finished());
+        assert_eq!(cpu.cycles(), 5);
+    }
+
+    #[test]
+    fn overflow_test() {
+        let program = parse_program("addx 2147483646\nnoop\naddx 1\nnoop").unwrap();
+        let mut cpu = Cpu::new(program);
+        let fault = cpu.by_ref().find_map(Result::err).unwrap();
+        assert_eq!(fault.cycle, 5);
+        assert_eq!(fault.instruction, Instruction::new("addx", 1));
+        assert_eq!(
+            fault.to_string(),
+            "`addx 1` overflows X=2147483647 on cycle 5"
+        );
+        assert!(cpu.finished());
+        assert_eq!(cpu.next(), None);
+    }
+
+    #[test]
+    fn opcode_test() {
+        // An instruction outside the set runs from its own entry, taking the cycles it declares
+        static SUBX: Opcode = Opcode {
+            mnemonic: "subx",
+            cycles: 3,
+            takes_value: true,
+            execute: |r, v| Some(Registers { x: r.x - v }),
+        };
+        let subx = Instruction {
+            opcode: &SUBX,
+            value: 4,
+        };
+        let mut cpu = Cpu::new(vec![subx, Instruction::new("noop", 0)]);
+        assert_eq!(cpu.by_ref().last().unwrap().unwrap().cycle, 4);
+        assert_eq!(cpu.registers().x, -3);
+        assert_eq!(subx.to_string(), "subx 4");
+    }
+
+    #[test]
+    fn parse_instruction_test() {
+        assert_eq!(parse_instruction("noop"), Ok(Instruction::new("noop", 0)));
+        assert_eq!(
+            parse_instruction("addx -5"),
+            Ok(Instruction::new("addx", -5))
+        );
+        assert_eq!(
+            parse_instruction("mulx 3"),
+            Err(ParseError::new(1, 1, "noop or addx", "mulx"))
+        );
+        assert_eq!(
+            parse_instruction("addx x"),
+            Err(ParseError::new(1, 6, "value", "x"))
+        );
+        assert_eq!(
+            parse_instruction("addx"),
+            Err(ParseError::new(1, 5, "value", ""))
+        );
+        assert_eq!(
+            parse_program("noop\nnoop 1"),
+            Err(ParseError::new(2, 5, "end of input", " 1"))
+        );
+    }
+}
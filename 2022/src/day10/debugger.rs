@@ -0,0 +1,535 @@
+//! Stepping through a program on the CPU, with breakpoints, a watch on `X` and the CRT row being
+//! drawn, in the terminal
+
+use std::{
+    collections::BTreeSet,
+    fmt,
+    io::{self, Write},
+    str::FromStr,
+};
+
+use eyre::{eyre, Result};
+use termion::{
+    clear, cursor, event::Key, input::TermRead, raw::IntoRawMode, screen::IntoAlternateScreen,
+};
+
+use super::{
+    cpu::{parse_program, Cpu, Overflow},
+    device::Display,
+};
+use crate::{parse::ParseError, Input};
+
+/// Commands the debugger takes, as shown under the prompt
+pub const HELP: &str =
+    "s step cycle  n next instruction  c continue  b cycle N  b instruction N  w watch X  q quit";
+
+/// A place for the program to stop when continuing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Breakpoint {
+    /// Before the cycle with this 1-based number
+    Cycle(usize),
+    /// Before the instruction with this index starts
+    Instruction(usize),
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Breakpoint::Cycle(n) => write!(f, "cycle {n}"),
+            Breakpoint::Instruction(n) => write!(f, "instruction {n}"),
+        }
+    }
+}
+
+impl FromStr for Breakpoint {
+    type Err = eyre::Report;
+
+    /// Parses `cycle 20` or `instruction 3`, or the same with `=` in place of the space
+    fn from_str(s: &str) -> Result<Self> {
+        let words = s
+            .split(|c: char| c == '=' || c.is_whitespace())
+            .filter(|w| !w.is_empty())
+            .collect::<Vec<_>>();
+        let number = |n: &str| n.parse().map_err(|_| eyre!("expected a number, got {n:?}"));
+        match words.as_slice() {
+            ["cycle", n] => Ok(Breakpoint::Cycle(number(n)?)),
+            ["instruction", n] => Ok(Breakpoint::Instruction(number(n)?)),
+            _ => Err(eyre!(
+                "expected a breakpoint like `cycle 20` or `instruction 3`, got {s:?}"
+            )),
+        }
+    }
+}
+
+/// What the debugger is told to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Run one cycle
+    Step,
+    /// Run to the start of the next instruction
+    Next,
+    /// Run until a breakpoint, a change to a watched `X` or the end of the program
+    Continue,
+    /// Set the breakpoint, or clear it if it is already set
+    Break(Breakpoint),
+    /// Start or stop watching `X`
+    Watch,
+    Quit,
+}
+
+impl FromStr for Command {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let words = s.split_whitespace().collect::<Vec<_>>();
+        match words.as_slice() {
+            ["s" | "step"] => Ok(Command::Step),
+            ["n" | "next"] => Ok(Command::Next),
+            ["c" | "continue"] => Ok(Command::Continue),
+            ["b" | "break", breakpoint @ ..] => Ok(Command::Break(breakpoint.join(" ").parse()?)),
+            ["w" | "watch"] | ["w" | "watch", "x" | "X"] => Ok(Command::Watch),
+            ["q" | "quit"] => Ok(Command::Quit),
+            _ => Err(eyre!("unknown command {:?}, try: {HELP}", s.trim())),
+        }
+    }
+}
+
+/// Why the program stopped running
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// The command ran as far as it goes
+    Done,
+    Breakpoint(Breakpoint),
+    /// `X` changed while it was watched
+    Watch {
+        from: i32,
+        to: i32,
+    },
+    /// The program has no more instructions
+    Finished,
+    /// An instruction overflowed `X`, which ends the program
+    Fault(Overflow),
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stop::Done => write!(f, "ok"),
+            Stop::Breakpoint(b) => write!(f, "stopped at {b}"),
+            Stop::Watch { from, to } => write!(f, "X changed from {from} to {to}"),
+            Stop::Finished => write!(f, "the program has finished"),
+            Stop::Fault(fault) => write!(f, "the program stopped: {fault}"),
+        }
+    }
+}
+
+/// A program on the CPU that runs as it is told to
+#[derive(Debug, Clone)]
+pub struct Debugger {
+    cpu: Cpu,
+    display: Display,
+    /// Whether the CRT pixel drawn during each cycle so far was lit
+    drawn: Vec<bool>,
+    breakpoints: BTreeSet<Breakpoint>,
+    watch: bool,
+}
+
+impl Debugger {
+    pub fn new(input: &Input) -> Result<Debugger, ParseError> {
+        Ok(Debugger {
+            cpu: Cpu::new(parse_program(input)?),
+            display: Display::DEVICE,
+            drawn: vec![],
+            breakpoints: BTreeSet::new(),
+            watch: false,
+        })
+    }
+
+    /// The debugger drawing on the display instead of the puzzle's CRT
+    pub fn with_display(self, display: Display) -> Debugger {
+        Debugger { display, ..self }
+    }
+
+    pub fn breakpoints(&self) -> &BTreeSet<Breakpoint> {
+        &self.breakpoints
+    }
+
+    pub fn watching(&self) -> bool {
+        self.watch
+    }
+
+    /// `X` between cycles
+    pub fn x(&self) -> i32 {
+        self.cpu.registers().x
+    }
+
+    /// Cycles run so far
+    pub fn cycles(&self) -> usize {
+        self.cpu.cycles()
+    }
+
+    pub fn execute(&mut self, command: Command) -> Stop {
+        match command {
+            Command::Step => self.run(|_| true),
+            Command::Next => self.run(|cpu| cpu.elapsed() == 0),
+            Command::Continue => self.run(|_| false),
+            Command::Break(breakpoint) => {
+                if !self.breakpoints.remove(&breakpoint) {
+                    self.breakpoints.insert(breakpoint);
+                }
+                Stop::Done
+            }
+            Command::Watch => {
+                self.watch = !self.watch;
+                Stop::Done
+            }
+            Command::Quit => Stop::Done,
+        }
+    }
+
+    /// Runs cycles until `done` is true between two of them, or something stops the program
+    fn run(&mut self, done: impl Fn(&Cpu) -> bool) -> Stop {
+        loop {
+            let from = self.x();
+            let state = match self.cpu.next() {
+                Some(Ok(state)) => state,
+                Some(Err(fault)) => return Stop::Fault(fault),
+                None => return self.cpu.fault().map_or(Stop::Finished, Stop::Fault),
+            };
+            self.drawn.push(self.display.pixel(&state).1);
+
+            // A change made by the last instruction is still reported before finishing
+            let to = self.x();
+            if self.watch && from != to {
+                return Stop::Watch { from, to };
+            } else if self.cpu.finished() {
+                return Stop::Finished;
+            } else if let Some(&b) = self.breakpoints.iter().find(|b| self.reached(**b)) {
+                return Stop::Breakpoint(b);
+            } else if done(&self.cpu) {
+                return Stop::Done;
+            }
+        }
+    }
+
+    fn reached(&self, breakpoint: Breakpoint) -> bool {
+        match breakpoint {
+            Breakpoint::Cycle(n) => self.cpu.cycles() + 1 == n,
+            Breakpoint::Instruction(idx) => self.cpu.pc() == idx && self.cpu.elapsed() == 0,
+        }
+    }
+
+    /// Lines showing the registers, the program around the instruction being run and the CRT
+    /// row being drawn, with the listing cut to fit in `height` lines
+    pub fn render(&self, height: usize) -> Vec<String> {
+        let cpu = &self.cpu;
+        let program = cpu.program();
+        let watching = if self.watch { "  (watching X)" } else { "" };
+        let mut lines = vec![if let Some(fault) = cpu.fault() {
+            format!("stopped: {fault}  X={}{watching}", self.x())
+        } else if cpu.finished() {
+            format!(
+                "finished after {} cycles  X={}{watching}",
+                cpu.cycles(),
+                self.x()
+            )
+        } else {
+            format!(
+                "cycle {}  instruction {} of {}  X={}{watching}",
+                cpu.cycles() + 1,
+                cpu.pc(),
+                program.len(),
+                self.x()
+            )
+        }];
+        let breakpoints = self
+            .breakpoints
+            .iter()
+            .map(|b| b.to_string())
+            .collect::<Vec<_>>();
+        lines.push(if breakpoints.is_empty() {
+            "no breakpoints".to_string()
+        } else {
+            format!("breakpoints: {}", breakpoints.join(", "))
+        });
+        lines.push(String::new());
+
+        // The header and the CRT take 7 lines, leaving the rest for the listing
+        let rows = height.saturating_sub(7).max(1);
+        let start = cpu
+            .pc()
+            .saturating_sub(rows / 3)
+            .min(program.len().saturating_sub(rows));
+        for (idx, instruction) in program.iter().enumerate().skip(start).take(rows) {
+            let current = idx == cpu.pc();
+            let breakpoint = self.breakpoints.contains(&Breakpoint::Instruction(idx));
+            let mut line = format!(
+                "{}{}{idx:>4}  {instruction}",
+                if current { '>' } else { ' ' },
+                if breakpoint { '*' } else { ' ' },
+            );
+            if current {
+                line.push_str(&format!(
+                    "  (cycle {} of {})",
+                    cpu.elapsed() + 1,
+                    instruction.opcode.cycles
+                ));
+            }
+            lines.push(line);
+        }
+
+        lines.push(String::new());
+        lines.extend(self.crt_row());
+        lines
+    }
+
+    /// The CRT row being drawn, and under it the sprite as `=` and the beam as `^`
+    fn crt_row(&self) -> [String; 3] {
+        // Once the program has finished, the last row drawn is shown without a beam
+        let display = &self.display;
+        let (cycle, beam) = match self.cpu.finished() {
+            true => (self.cycles().max(1), false),
+            false => (self.cycles() + 1, true),
+        };
+        let (row, column) = display.position(cycle);
+        let pixels = self
+            .drawn
+            .iter()
+            .skip(cycle - 1 - column)
+            .map(|&lit| if lit { '#' } else { '.' })
+            .collect::<String>();
+        let sprite = display.sprite(self.x());
+        let marks = (0..display.width.get())
+            .map(|col| {
+                if beam && col == column {
+                    '^'
+                } else if sprite.contains(&(col as i32)) {
+                    '='
+                } else {
+                    ' '
+                }
+            })
+            .collect::<String>();
+        [
+            format!("CRT row {row}"),
+            pixels,
+            marks.trim_end().to_string(),
+        ]
+    }
+}
+
+/// Runs the debugger in the terminal, taking commands typed at a prompt until it is quit. An
+/// empty command repeats the last step, next or continue.
+pub fn run_terminal(debugger: &mut Debugger) -> Result<()> {
+    let mut out = io::stdout().into_raw_mode()?.into_alternate_screen()?;
+    let mut keys = io::stdin().keys();
+    let mut line = String::new();
+    let mut last = Command::Step;
+    let mut status = HELP.to_string();
+
+    loop {
+        let (width, height) = termion::terminal_size()?;
+        // The status and the prompt keep their two rows, however short the terminal
+        let prompt_row = height.max(2);
+        let fit = |s: &str| s.chars().take(width as usize).collect::<String>();
+        write!(out, "{}", clear::All)?;
+        for (y, text) in debugger
+            .render((height as usize).saturating_sub(2))
+            .iter()
+            .enumerate()
+        {
+            write!(out, "{}{}", cursor::Goto(1, y as u16 + 1), fit(text))?;
+        }
+        write!(
+            out,
+            "{}{}{}> {line}",
+            cursor::Goto(1, prompt_row - 1),
+            fit(&status),
+            cursor::Goto(1, prompt_row)
+        )?;
+        out.flush()?;
+
+        let Some(key) = keys.next() else {
+            return Ok(());
+        };
+        match key? {
+            Key::Char('\n') => {
+                let command = match line.trim() {
+                    "" => Ok(last),
+                    text => text.parse(),
+                };
+                line.clear();
+                status = match command {
+                    Ok(Command::Quit) => return Ok(()),
+                    Ok(command) => {
+                        if matches!(command, Command::Step | Command::Next | Command::Continue) {
+                            last = command;
+                        }
+                        match debugger.execute(command) {
+                            Stop::Done => HELP.to_string(),
+                            stop => stop.to_string(),
+                        }
+                    }
+                    Err(e) => e.to_string(),
+                };
+            }
+            Key::Char(c) => line.push(c),
+            Key::Backspace => {
+                line.pop();
+            }
+            Key::Esc | Key::Ctrl('c') | Key::Ctrl('d') => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn debugger() -> Debugger {
+        Debugger::new(&Input::new("noop\naddx 3\naddx -5\nnoop")).unwrap()
+    }
+
+    #[test]
+    fn step_test() {
+        let mut debugger = debugger();
+        assert_eq!(debugger.execute(Command::Step), Stop::Done);
+        assert_eq!((debugger.cycles(), debugger.x()), (1, 1));
+        assert_eq!(debugger.execute(Command::Next), Stop::Done);
+        assert_eq!((debugger.cycles(), debugger.x()), (3, 4));
+        assert_eq!(debugger.execute(Command::Step), Stop::Done);
+        assert_eq!(debugger.execute(Command::Next), Stop::Done);
+        assert_eq!((debugger.cycles(), debugger.x()), (5, -1));
+        assert_eq!(debugger.execute(Command::Next), Stop::Finished);
+        assert_eq!(debugger.execute(Command::Step), Stop::Finished);
+        assert_eq!(debugger.cycles(), 6);
+    }
+
+    #[test]
+    fn breakpoint_test() {
+        let mut debugger = debugger();
+        debugger.execute(Command::Break(Breakpoint::Cycle(3)));
+        debugger.execute(Command::Break(Breakpoint::Instruction(2)));
+        assert_eq!(
+            debugger.execute(Command::Continue),
+            Stop::Breakpoint(Breakpoint::Cycle(3))
+        );
+        assert_eq!(debugger.cycles(), 2);
+        assert_eq!(
+            debugger.execute(Command::Continue),
+            Stop::Breakpoint(Breakpoint::Instruction(2))
+        );
+        assert_eq!((debugger.cycles(), debugger.x()), (3, 4));
+
+        debugger.execute(Command::Break(Breakpoint::Cycle(3)));
+        assert_eq!(
+            debugger.breakpoints().iter().collect::<Vec<_>>(),
+            [&Breakpoint::Instruction(2)]
+        );
+        assert_eq!(debugger.execute(Command::Continue), Stop::Finished);
+    }
+
+    #[test]
+    fn watch_test() {
+        let mut debugger = debugger();
+        debugger.execute(Command::Watch);
+        assert!(debugger.watching());
+        assert_eq!(
+            debugger.execute(Command::Continue),
+            Stop::Watch { from: 1, to: 4 }
+        );
+        assert_eq!(
+            debugger.execute(Command::Continue),
+            Stop::Watch { from: 4, to: -1 }
+        );
+        debugger.execute(Command::Watch);
+        assert_eq!(debugger.execute(Command::Continue), Stop::Finished);
+
+        // The last instruction's change to X is reported, and the next step finishes
+        let mut debugger = Debugger::new(&Input::new("noop\naddx 2")).unwrap();
+        debugger.execute(Command::Watch);
+        assert_eq!(
+            debugger.execute(Command::Continue),
+            Stop::Watch { from: 1, to: 3 }
+        );
+        assert_eq!(debugger.execute(Command::Step), Stop::Finished);
+    }
+
+    #[test]
+    fn fault_test() {
+        let input = Input::from("addx 2147483646\naddx 1\nnoop");
+        let mut debugger = Debugger::new(&input).unwrap();
+        let Stop::Fault(fault) = debugger.execute(Command::Continue) else {
+            panic!("the second addx should overflow X");
+        };
+        assert_eq!(fault.cycle, 4);
+        assert_eq!(debugger.execute(Command::Step), Stop::Fault(fault));
+        assert_eq!(
+            debugger.render(8)[0],
+            "stopped: `addx 1` overflows X=2147483647 on cycle 4  X=2147483647"
+        );
+    }
+
+    #[test]
+    fn render_test() {
+        let mut debugger = debugger();
+        debugger.execute(Command::Break(Breakpoint::Instruction(2)));
+        debugger.execute(Command::Step);
+        debugger.execute(Command::Step);
+        assert_eq!(
+            debugger.render(20),
+            [
+                "cycle 3  instruction 1 of 4  X=1",
+                "breakpoints: instruction 2",
+                "",
+                "     0  noop",
+                ">    1  addx 3  (cycle 2 of 2)",
+                " *   2  addx -5",
+                "     3  noop",
+                "",
+                "CRT row 0",
+                "##",
+                "==^",
+            ]
+        );
+
+        debugger.execute(Command::Continue);
+        assert_eq!(debugger.execute(Command::Continue), Stop::Finished);
+        debugger.execute(Command::Watch);
+        let lines = debugger.render(8);
+        assert_eq!(lines[0], "finished after 6 cycles  X=-1  (watching X)");
+        assert_eq!(lines[3], "     3  noop");
+        assert_eq!(lines[6], "#####.");
+        assert_eq!(lines[7], "=");
+    }
+
+    #[test]
+    fn display_test() {
+        let display = Display::new(4, 2, 1).unwrap();
+        let mut debugger = debugger().with_display(display);
+        debugger.execute(Command::Next);
+        debugger.execute(Command::Next);
+        // X is 4, which puts the sprite off the right of the screen
+        assert_eq!(debugger.render(8)[5..], ["CRT row 0", ".#.", "   ^"]);
+        assert_eq!(debugger.execute(Command::Continue), Stop::Finished);
+        assert_eq!(debugger.render(8)[5..], ["CRT row 1", "..", ""]);
+    }
+
+    #[test]
+    fn command_test() {
+        assert_eq!("n".parse::<Command>().unwrap(), Command::Next);
+        assert_eq!(
+            "b cycle 20".parse::<Command>().unwrap(),
+            Command::Break(Breakpoint::Cycle(20))
+        );
+        assert_eq!(
+            "break instruction=3".parse::<Command>().unwrap(),
+            Command::Break(Breakpoint::Instruction(3))
+        );
+        assert_eq!("watch x".parse::<Command>().unwrap(), Command::Watch);
+        assert!("b cycle".parse::<Command>().is_err());
+        assert!("b cycle x".parse::<Command>().is_err());
+        assert!("jump 3".parse::<Command>().is_err());
+    }
+}
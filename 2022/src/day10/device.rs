@@ -0,0 +1,254 @@
+//! The parts of the device around the CPU that can vary: the CRT it drives and the cycles it
+//! samples the signal strength on
+
+use std::{fmt, num::NonZeroUsize, ops::RangeInclusive, str::FromStr};
+
+use eyre::{eyre, Result};
+use itertools::Itertools;
+
+use super::cpu::{Cpu, Instruction, Overflow, State};
+
+/// A CRT that draws one pixel per cycle, left to right and top to bottom, lit where the sprite
+/// centred on `X` covers the beam's column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Display {
+    pub width: NonZeroUsize,
+    pub height: NonZeroUsize,
+    /// An even width has one more pixel right of `X` than left of it
+    pub sprite_width: NonZeroUsize,
+}
+
+impl Display {
+    /// The puzzle's 40x6 CRT with a 3 pixel sprite
+    pub const DEVICE: Display = Display {
+        width: NonZeroUsize::new(40).unwrap(),
+        height: NonZeroUsize::new(6).unwrap(),
+        sprite_width: NonZeroUsize::new(3).unwrap(),
+    };
+
+    /// Returns `None` if any of the sizes is 0
+    pub fn new(width: usize, height: usize, sprite_width: usize) -> Option<Display> {
+        Some(Display {
+            width: NonZeroUsize::new(width)?,
+            height: NonZeroUsize::new(height)?,
+            sprite_width: NonZeroUsize::new(sprite_width)?,
+        })
+    }
+
+    /// Pixels on the screen, and cycles it takes to draw them all
+    pub fn pixels(&self) -> usize {
+        self.width.get() * self.height.get()
+    }
+
+    /// Row and column the beam draws during the 1-based cycle, starting again at the top once
+    /// the screen is drawn
+    pub fn position(&self, cycle: usize) -> (usize, usize) {
+        let pixel = (cycle - 1) % self.pixels();
+        (pixel / self.width, pixel % self.width)
+    }
+
+    /// Columns the sprite covers with `X` at `x`, some of which may be off the screen. Columns
+    /// past the ends of `i32` are left out.
+    pub fn sprite(&self, x: i32) -> RangeInclusive<i32> {
+        let sprite_width = self.sprite_width.get() as i32;
+        let left = x.saturating_sub((sprite_width - 1) / 2);
+        left..=left.saturating_add(sprite_width - 1)
+    }
+
+    /// Values of `X` that put the sprite anywhere from just off the left of the screen to just
+    /// off the right, the only places that draw differently
+    pub fn sprite_positions(&self) -> RangeInclusive<i32> {
+        let reach = self.sprite_width.get() as i32;
+        -reach..=self.width.get() as i32 + reach
+    }
+
+    /// Column the beam draws during the cycle, and whether the sprite lights it
+    pub fn pixel(&self, state: &State) -> (usize, bool) {
+        let (_, column) = self.position(state.cycle);
+        (
+            column,
+            self.sprite(state.registers.x).contains(&(column as i32)),
+        )
+    }
+
+    /// The screen once the program has run, a line of `#` and `.` for each row. A program that
+    /// runs longer than a screen has only its first screen shown.
+    pub fn screen(&self, program: Vec<Instruction>) -> Result<String, Overflow> {
+        let mut output = String::new();
+        for state in Cpu::new(program).take(self.pixels()) {
+            let (column, lit) = self.pixel(&state?);
+            output.push(if lit { '#' } else { '.' });
+            if column == self.width.get() - 1 {
+                output.push('\n');
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// The cycles the signal strength is sampled on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schedule {
+    /// Every `stride` cycles from `start`, `count` times or until the program ends
+    Every {
+        start: usize,
+        stride: NonZeroUsize,
+        count: Option<usize>,
+    },
+    /// The listed cycles
+    Cycles(Vec<usize>),
+}
+
+impl Schedule {
+    /// The puzzle's samples, on cycles 20, 60, 100, 140, 180 and 220
+    pub const DEVICE: Schedule = Schedule::Every {
+        start: 20,
+        stride: NonZeroUsize::new(40).unwrap(),
+        count: Some(6),
+    };
+
+    /// Whether the 1-based cycle is sampled
+    pub fn contains(&self, cycle: usize) -> bool {
+        match self {
+            Schedule::Every {
+                start,
+                stride,
+                count,
+            } => {
+                cycle >= *start
+                    && (cycle - start).is_multiple_of(stride.get())
+                    && count.is_none_or(|count| (cycle - start) / *stride < count)
+            }
+            Schedule::Cycles(cycles) => cycles.contains(&cycle),
+        }
+    }
+
+    /// Sum of the cycle number times `X` over the sampled cycles of the program
+    pub fn signal_strength(&self, program: Vec<Instruction>) -> Result<i32> {
+        let mut sum = 0i32;
+        for state in Cpu::new(program) {
+            let state = state?;
+            if self.contains(state.cycle) {
+                sum = i32::try_from(state.cycle)
+                    .ok()
+                    .and_then(|cycle| cycle.checked_mul(state.registers.x))
+                    .and_then(|strength| sum.checked_add(strength))
+                    .ok_or_else(|| {
+                        eyre!("signal strength overflows 32 bits on cycle {}", state.cycle)
+                    })?;
+            }
+        }
+        Ok(sum)
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Schedule::Every {
+                start,
+                stride,
+                count,
+            } => {
+                write!(f, "{start}+{stride}")?;
+                match count {
+                    Some(count) => write!(f, "x{count}"),
+                    None => Ok(()),
+                }
+            }
+            Schedule::Cycles(cycles) => write!(f, "{}", cycles.iter().join(",")),
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = eyre::Report;
+
+    /// Parses `START+STRIDE` for every `STRIDE` cycles from `START`, `START+STRIDExCOUNT` for
+    /// only `COUNT` of them, or a list of cycles like `20,60,100`
+    fn from_str(s: &str) -> Result<Self> {
+        let number = |n: &str| n.parse().map_err(|_| eyre!("expected a number, got {n:?}"));
+        match s.split_once('+') {
+            Some((start, rest)) => {
+                let (stride, count) = match rest.split_once('x') {
+                    Some((stride, count)) => (stride, Some(number(count)?)),
+                    None => (rest, None),
+                };
+                Ok(Schedule::Every {
+                    start: number(start)?,
+                    stride: stride
+                        .parse()
+                        .map_err(|_| eyre!("expected a stride of at least 1, got {stride:?}"))?,
+                    count,
+                })
+            }
+            None => Ok(Schedule::Cycles(s.split(',').map(number).try_collect()?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day10::cpu::parse_program;
+
+    #[test]
+    fn display_test() {
+        let display = Display::new(5, 2, 2).unwrap();
+        assert_eq!(display.sprite(1), 1..=2);
+        assert_eq!(display.sprite(i32::MAX), i32::MAX..=i32::MAX);
+        assert_eq!(display.position(7), (1, 1));
+        assert_eq!(display.position(11), (0, 0));
+
+        // X moves to 3 after cycle 2 and to -1 after cycle 5, and the eleventh cycle is cut off
+        let program =
+            parse_program("addx 2\nnoop\naddx -4\nnoop\nnoop\nnoop\nnoop\nnoop\nnoop").unwrap();
+        assert_eq!(display.screen(program).unwrap(), ".#.##\n#....\n");
+
+        assert_eq!(Display::new(0, 6, 3), None);
+        assert_eq!(Display::new(40, 6, 0), None);
+    }
+
+    #[test]
+    fn schedule_test() {
+        let cycles = |schedule: &Schedule| {
+            (1..=300)
+                .filter(|&c| schedule.contains(c))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(cycles(&Schedule::DEVICE), [20, 60, 100, 140, 180, 220]);
+        let every = Schedule::Every {
+            start: 1,
+            stride: NonZeroUsize::new(100).unwrap(),
+            count: None,
+        };
+        assert_eq!(cycles(&every), [1, 101, 201]);
+
+        let program = parse_program("noop\naddx 3\naddx -5").unwrap();
+        assert_eq!(
+            Schedule::Cycles(vec![1, 4, 9])
+                .signal_strength(program)
+                .unwrap(),
+            1 + 4 * 4
+        );
+
+        let program = parse_program("addx 2147483646\nnoop").unwrap();
+        let error = Schedule::Cycles(vec![3]).signal_strength(program);
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "signal strength overflows 32 bits on cycle 3"
+        );
+    }
+
+    #[test]
+    fn schedule_from_str_test() {
+        assert_eq!("20+40x6".parse::<Schedule>().unwrap(), Schedule::DEVICE);
+        assert_eq!(Schedule::DEVICE.to_string(), "20+40x6");
+        for text in ["1+100", "1,4,9", "7"] {
+            assert_eq!(text.parse::<Schedule>().unwrap().to_string(), text);
+        }
+        for text in ["20+0", "20+40x", "+40", "1,,9", ""] {
+            assert!(text.parse::<Schedule>().is_err(), "{text}");
+        }
+    }
+}
@@ -1,7 +1,23 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, iter::once};
 
+use eyre::{eyre, Result};
 use itertools::Itertools;
-
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{self as cc, char, digit1, line_ending, multispace0},
+    combinator::{cut, map, map_res, value, verify},
+    error::context,
+    multi::{many0, separated_list1},
+    sequence::{delimited, pair, preceded, terminated, tuple},
+};
+
+use crate::{
+    parse::{parse_all, IResult, ParseError},
+    Answer, Input, Solution,
+};
+
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, PartialEq, Eq, Clone)]
 enum Operation {
     OldMultipliedByOld,
@@ -91,92 +107,136 @@ impl KeepAway {
     }
 }
 
-fn parse_monkey(input: &str) -> Monkey {
-    let mut lines = input.lines().skip(1);
-    let items = lines
-        .next()
-        .unwrap()
-        .split_once("Starting items: ")
-        .map(|(_, s)| {
-            s.split(", ")
-                .map(|i| i.parse::<u64>().unwrap())
-                .collect::<Vec<_>>()
-        })
-        .unwrap();
-
-    let operation = lines
-        .next()
-        .unwrap()
-        .split_once("Operation: new = ")
-        .map(|(_, s)| {
-            let mut parts = s.splitn(3, ' ');
-            match (
-                parts.next().unwrap(),
-                parts.next().unwrap(),
-                parts.next().unwrap(),
-            ) {
-                ("old", "*", "old") => Operation::OldMultipliedByOld,
-                ("old", "*", x) => Operation::OldMultipliedBy(x.parse::<u32>().unwrap()),
-                ("old", "+", x) => Operation::OldPlus(x.parse::<u32>().unwrap()),
-                _ => {
-                    panic!("unexpected operation")
-                }
-            }
-        })
-        .unwrap();
-
-    let test_divisible_by = lines
-        .next()
-        .unwrap()
-        .split_once("Test: divisible by ")
-        .map(|(_, s)| s.parse::<u32>().unwrap())
-        .unwrap();
-
-    let if_true_throw_to_monkey = lines
-        .next()
-        .unwrap()
-        .split_once("If true: throw to monkey ")
-        .map(|(_, s)| s.parse::<usize>().unwrap())
-        .unwrap();
-
-    let if_false_throw_to_monkey = lines
-        .next()
-        .unwrap()
-        .split_once("If false: throw to monkey ")
-        .map(|(_, s)| s.parse::<usize>().unwrap())
-        .unwrap();
+fn parse_operation(i: &str) -> IResult<'_, Operation> {
+    context(
+        "old * old, old * <n> or old + <n>",
+        alt((
+            value(Operation::OldMultipliedByOld, tag("old * old")),
+            map(preceded(tag("old * "), cc::u32), Operation::OldMultipliedBy),
+            map(preceded(tag("old + "), cc::u32), Operation::OldPlus),
+        )),
+    )(i)
+}
+
+fn parse_monkey_number(i: &str) -> IResult<'_, usize> {
+    context("monkey number", map_res(digit1, str::parse))(i)
+}
+
+fn parse_monkey_block(i: &str) -> IResult<'_, Monkey> {
+    let (i, _) = tuple((
+        context("monkey header", tag("Monkey ")),
+        parse_monkey_number,
+        char(':'),
+        line_ending,
+    ))(i)?;
+    let (i, items) = delimited(
+        context("starting items", tag("  Starting items: ")),
+        separated_list1(tag(", "), cut(context("worry level", cc::u64))),
+        line_ending,
+    )(i)?;
+    let (i, operation) = delimited(
+        context("operation", tag("  Operation: new = ")),
+        cut(parse_operation),
+        line_ending,
+    )(i)?;
+    let (i, divisible_by) = delimited(
+        context("test", tag("  Test: divisible by ")),
+        // The worry level is taken modulo the divisor
+        cut(context(
+            "divisor other than 0",
+            verify(cc::u32, |&d| d != 0),
+        )),
+        line_ending,
+    )(i)?;
+    let (i, if_true_throw_to_monkey) = delimited(
+        context("if true", tag("    If true: throw to monkey ")),
+        cut(parse_monkey_number),
+        line_ending,
+    )(i)?;
+    let (i, if_false_throw_to_monkey) = preceded(
+        context("if false", tag("    If false: throw to monkey ")),
+        cut(parse_monkey_number),
+    )(i)?;
 
     let test = Test {
-        divisible_by: test_divisible_by,
+        divisible_by,
         if_true_throw_to_monkey,
         if_false_throw_to_monkey,
     };
 
-    Monkey {
-        items,
-        operation,
-        test,
-    }
+    Ok((
+        i,
+        Monkey {
+            items,
+            operation,
+            test,
+        },
+    ))
 }
 
-fn day11(input: &str) -> u64 {
-    let monkeys = input
-        .split("\n\n")
-        .map(parse_monkey)
-        .collect::<Vec<Monkey>>();
+fn parse_monkey(input: &str) -> Result<Monkey, ParseError> {
+    parse_all(input, parse_monkey_block)
+}
 
-    let mut keep_away = KeepAway::new(monkeys, 20);
-    keep_away.play()
+fn parse_monkeys(input: &str) -> Result<Vec<Monkey>> {
+    let (first, rest) = parse_all(
+        input,
+        terminated(
+            pair(
+                parse_monkey_block,
+                many0(preceded(
+                    pair(line_ending, line_ending),
+                    cut(parse_monkey_block),
+                )),
+            ),
+            multispace0,
+        ),
+    )?;
+    let monkeys = once(first).chain(rest).collect::<Vec<_>>();
+
+    for (n, monkey) in monkeys.iter().enumerate() {
+        for target in [
+            monkey.test.if_true_throw_to_monkey,
+            monkey.test.if_false_throw_to_monkey,
+        ] {
+            if target >= monkeys.len() {
+                return Err(eyre!(
+                    "monkey {n} throws to monkey {target}, which does not exist"
+                ));
+            }
+        }
+    }
+
+    Ok(monkeys)
 }
 
-fn day11_part2(input: &str) -> u64 {
-    let monkeys = input
-        .split("\n\n")
-        .map(parse_monkey)
-        .collect::<Vec<Monkey>>();
+fn day11(input: &Input) -> Result<u64> {
+    let monkeys = parse_monkeys(input)?;
+    let mut keep_away = KeepAway::new(monkeys, 20);
+    Ok(keep_away.play())
+}
 
+fn day11_part2(input: &Input) -> Result<u64> {
+    let monkeys = parse_monkeys(input)?;
     let mut keep_away = KeepAway::new(monkeys, 10000);
-    keep_away.play()
+    Ok(keep_away.play())
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    fn part1(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day11(input)?))
+    }
+
+    fn part2(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day11_part2(input)?))
+    }
+
+    fn parse(&self, input: &Input) -> Result<()> {
+        parse_monkeys(input)?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -185,8 +245,8 @@ mod tests {
 
     #[test]
     fn day11_test() {
-        let input = include_str!("../testdata/day11");
-        assert_eq!(day11(input.trim_start_matches('\n')), 118674);
+        let input = Input::from_path("./testdata/day11").unwrap();
+        assert_eq!(day11(&input).unwrap(), 118674);
     }
 
     #[test]
@@ -219,13 +279,14 @@ Monkey 3:
   Test: divisible by 17
     If true: throw to monkey 0
     If false: throw to monkey 1";
-        assert_eq!(day11(input.trim_start_matches('\n')), 10605);
+        let input = Input::from(input);
+        assert_eq!(day11(&input).unwrap(), 10605);
     }
 
     #[test]
     fn day11_part2_test() {
-        let input = include_str!("../testdata/day11");
-        assert_eq!(day11_part2(input.trim_start_matches('\n')), 32333418600);
+        let input = Input::from_path("./testdata/day11").unwrap();
+        assert_eq!(day11_part2(&input).unwrap(), 32333418600);
     }
 
     #[test]
@@ -258,7 +319,8 @@ Monkey 3:
   Test: divisible by 17
     If true: throw to monkey 0
     If false: throw to monkey 1";
-        assert_eq!(day11_part2(input.trim_start_matches('\n')), 2713310158);
+        let input = Input::from(input);
+        assert_eq!(day11_part2(&input).unwrap(), 2713310158);
     }
 
     #[test]
@@ -270,7 +332,7 @@ Monkey 0:
   Test: divisible by 23
     If true: throw to monkey 2
     If false: throw to monkey 3";
-        let monkey = parse_monkey(input.trim_start_matches('\n'));
+        let monkey = parse_monkey(&Input::from(input)).unwrap();
 
         assert_eq!(
             monkey,
@@ -285,4 +347,74 @@ Monkey 0:
             }
         );
     }
+
+    #[test]
+    fn parse_monkey_error_test() {
+        let input = "
+Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old ^ 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3";
+        assert_eq!(
+            parse_monkey(&Input::from(input)),
+            Err(ParseError::new(
+                3,
+                20,
+                "old * old, old * <n> or old + <n>",
+                "old"
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_monkey_bad_token_test() {
+        let monkey = |items, if_true| {
+            format!(
+                "Monkey 0:
+  Starting items: {items}
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey {if_true}
+    If false: throw to monkey 3"
+            )
+        };
+        assert_eq!(
+            parse_monkey(&monkey("79, x", "2")),
+            Err(ParseError::new(2, 23, "worry level", "x"))
+        );
+        assert_eq!(
+            parse_monkey(&monkey("79, 98", "two")),
+            Err(ParseError::new(5, 30, "monkey number", "two"))
+        );
+    }
+
+    #[test]
+    fn parse_monkey_zero_divisor_test() {
+        let input = "
+Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 0
+    If true: throw to monkey 2
+    If false: throw to monkey 3";
+        assert_eq!(
+            parse_monkey(&Input::from(input)),
+            Err(ParseError::new(4, 22, "divisor other than 0", "0"))
+        );
+    }
+
+    #[test]
+    fn parse_monkeys_missing_target_test() {
+        let input = "
+Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 0
+    If false: throw to monkey 1
+";
+        assert!(parse_monkeys(&Input::from(input)).is_err());
+    }
 }
@@ -1,94 +1,44 @@
-use std::char;
-use std::collections::{HashMap, HashSet, VecDeque};
+use eyre::{eyre, Result};
 
-use itertools::Itertools;
+use crate::{
+    grid::{Grid, Pos},
+    image::{Gradient, Image},
+    parse::ParseError,
+    search::bfs,
+    Answer, Input, Solution,
+};
 
 #[derive(Debug)]
 struct Heightmap {
-    cells: Vec<Cell>,
-    height: usize,
-    width: usize,
+    grid: Grid<Cell>,
 }
 
 impl Heightmap {
-    fn get_cell_idx(&self, x: i64, y: i64) -> Option<usize> {
-        match (x, y) {
-            (x, y) if x >= 0 && y >= 0 => Some((x + (self.width as i64 * y)) as usize),
-            (_, _) => None,
-        }
+    /// Squares next to `pos` that are at most one higher than it
+    fn get_adjacent_positions(&self, pos: Pos) -> Vec<Pos> {
+        let elevation = self.grid[pos].elevation();
+        self.grid
+            .neighbours4(pos)
+            .filter(|&p| self.grid[p].elevation() <= elevation + 1)
+            .collect()
     }
 
-    fn get_cell(&self, x: i64, y: i64) -> Option<(&Cell, usize)> {
-        self.get_cell_idx(x, y)
-            .and_then(|idx| self.cells.get(idx).map(|c| (c, idx)))
+    fn get_start_pos(&self) -> Result<Pos> {
+        self.grid
+            .find(|cell| matches!(cell, Cell::Start))
+            .ok_or_else(|| eyre!("heightmap has no start position S"))
     }
 
-    fn get_adjacent_cell_idxs(&self, x: i64, y: i64) -> Vec<usize> {
-        let (cell, _idx) = self.get_cell(x, y).unwrap();
-        let left = self.get_cell(x - 1, y);
-        let right = self.get_cell(x + 1, y);
-        let top = self.get_cell(x, y - 1);
-        let bottom = self.get_cell(x, y + 1);
-        let sides = [left, right, top, bottom];
-
-        sides
-            .iter()
-            .filter_map(|x| match *x {
-                Some((c, idx)) if c.elevation() <= cell.elevation() + 1 => Some(idx),
-                Some(_) => None,
-                None => None,
-            })
-            .collect::<Vec<usize>>()
+    fn get_end_pos(&self) -> Result<Pos> {
+        self.grid
+            .find(|cell| matches!(cell, Cell::End))
+            .ok_or_else(|| eyre!("heightmap has no best signal position E"))
     }
 
-    fn get_start_idx(&self) -> usize {
-        let (idx, _cell) = self
-            .cells
-            .iter()
-            .enumerate()
-            .find(|(_idx, cell)| matches!(cell, Cell::Start))
-            .unwrap();
-
-        idx
-    }
-
-    fn get_end_idx(&self) -> usize {
-        let (idx, _cell) = self
-            .cells
-            .iter()
-            .enumerate()
-            .find(|(_idx, cell)| matches!(cell, Cell::End))
-            .unwrap();
-
-        idx
-    }
-
-    fn to_adjacency_list(&self) -> HashMap<usize, Vec<usize>> {
-        let mut list = HashMap::new();
-
-        for y in 0..self.height as i64 {
-            for x in 0..self.width as i64 {
-                let (_cell, idx) = self.get_cell(x, y).unwrap();
-                list.insert(idx, self.get_adjacent_cell_idxs(x, y));
-            }
-        }
-
-        list
-    }
-
-    fn parse(input: &str) -> Heightmap {
-        let lines = input.lines();
-        let height = lines.clone().count();
-        let width = lines.clone().next().unwrap().len();
-        let cells = lines
-            .flat_map(|l| l.chars().map(parse_cell).collect::<Vec<_>>())
-            .collect();
-
-        Heightmap {
-            cells,
-            height,
-            width,
-        }
+    fn parse(input: &str) -> Result<Heightmap, ParseError> {
+        Ok(Heightmap {
+            grid: Grid::parse(input, "a-z, S or E", parse_cell)?,
+        })
     }
 }
 
@@ -112,79 +62,77 @@ impl Cell {
     }
 }
 
-fn parse_cell(c: char) -> Cell {
+fn parse_cell(c: char) -> Option<Cell> {
     match c {
-        'S' => Cell::Start,
-        'E' => Cell::End,
-        'a'..='z' => Cell::Square(c as u8 - b'a'),
-        _ => panic!("invalid character: {c}"),
+        'S' => Some(Cell::Start),
+        'E' => Some(Cell::End),
+        'a'..='z' => Some(Cell::Square(c as u8 - b'a')),
+        _ => None,
     }
 }
 
-fn bfs(graph: &HashMap<usize, Vec<usize>>, start: usize, end: usize) -> Option<Vec<usize>> {
-    let mut queue = VecDeque::new();
-    queue.push_back(start);
-
-    let mut visited = HashSet::new();
-    visited.insert(start);
-
-    let mut prev_map: HashMap<usize, usize> = HashMap::new();
-    'outer: while !queue.is_empty() {
-        let current_node = queue.pop_front().unwrap();
-        for v in graph.get(&current_node).unwrap().iter() {
-            if *v == end {
-                prev_map.insert(*v, current_node);
-                break 'outer;
-            }
-
-            if !visited.contains(v) {
-                prev_map.insert(*v, current_node);
-                queue.push_back(*v);
-                visited.insert(*v);
-            }
-        }
-    }
-
-    let mut path = Vec::new();
-    let mut prev = Some(end);
-    while let Some(p) = prev {
-        path.push(p);
-        prev = prev_map.get(&p).cloned();
-    }
-    path.reverse();
+fn day12(input: &Input) -> Result<usize> {
+    let map = Heightmap::parse(input)?;
+    let start = map.get_start_pos()?;
+    let end = map.get_end_pos()?;
+    bfs(
+        [start],
+        |&pos| map.get_adjacent_positions(pos),
+        |&pos| pos == end,
+    )
+    .distance(&end)
+    .ok_or_else(|| eyre!("no path from S to E"))
+}
 
-    match path.first() {
-        Some(node) if *node == start => Some(path),
-        _ => None,
-    }
+fn day12_part2(input: &Input) -> Result<usize> {
+    let map = Heightmap::parse(input)?;
+    let end = map.get_end_pos()?;
+    // Searching from every lowest square at once finds the one closest to E
+    let starts = map
+        .grid
+        .iter()
+        .filter(|(_pos, c)| c.elevation() == MIN_ELEVATION)
+        .map(|(pos, _c)| pos);
+    bfs(
+        starts,
+        |&pos| map.get_adjacent_positions(pos),
+        |&pos| pos == end,
+    )
+    .distance(&end)
+    .ok_or_else(|| eyre!("no path from any lowest square to E"))
 }
 
-fn day12(input: &str) -> usize {
-    let map = Heightmap::parse(input.trim_start_matches('\n'));
-    let adjacency_list = map.to_adjacency_list();
-    let start = map.get_start_idx();
-    let end = map.get_end_idx();
-    let path = bfs(&adjacency_list, start, end);
-    path.unwrap().len() - 1
+/// The heightmap with higher squares lighter, the start in red and the best signal in blue
+pub fn image(input: &Input) -> Result<Image> {
+    let map = Heightmap::parse(input)?;
+    let gradient = Gradient {
+        from: [20, 70, 30],
+        to: [245, 245, 245],
+        min: MIN_ELEVATION as u32,
+        max: MAX_ELEVATION as u32,
+    };
+    Ok(Image::from_grid(&map.grid, |cell| match cell {
+        Cell::Start => [220, 40, 40],
+        Cell::End => [40, 90, 230],
+        Cell::Square(e) => gradient.colour(*e as u32),
+    }))
 }
 
-fn day12_part2(input: &str) -> usize {
-    let map = Heightmap::parse(input.trim_start_matches('\n'));
-    let adjacency_list = map.to_adjacency_list();
-    let end = map.get_end_idx();
-    let potential_start_cells = map
-        .cells
-        .iter()
-        .enumerate()
-        .filter(|(_idx, c)| c.elevation() == MIN_ELEVATION);
-
-    potential_start_cells
-        .filter_map(|(idx, _c)| bfs(&adjacency_list, idx, end))
-        .map(|path| path.len())
-        .sorted()
-        .next()
-        .unwrap()
-        - 1
+pub struct Day12;
+
+impl Solution for Day12 {
+    fn part1(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day12(input)?))
+    }
+
+    fn part2(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day12_part2(input)?))
+    }
+
+    fn parse(&self, input: &Input) -> Result<()> {
+        Heightmap::parse(input)?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -200,14 +148,14 @@ accszExk
 acctuvwj
 abdefghi
 ";
-
-        assert_eq!(day12(input), 31);
+        let input = Input::from(input);
+        assert_eq!(day12(&input).unwrap(), 31);
     }
 
     #[test]
     fn day12_test() {
-        let input = include_str!("../testdata/day12");
-        assert_eq!(day12(input), 361);
+        let input = Input::from_path("./testdata/day12").unwrap();
+        assert_eq!(day12(&input).unwrap(), 361);
     }
 
     #[test]
@@ -219,13 +167,19 @@ accszExk
 acctuvwj
 abdefghi
 ";
-
-        assert_eq!(day12_part2(input), 29);
+        let input = Input::from(input);
+        assert_eq!(day12_part2(&input).unwrap(), 29);
     }
 
     #[test]
     fn day12_part2_test() {
-        let input = include_str!("../testdata/day12");
-        assert_eq!(day12_part2(input), 354);
+        let input = Input::from_path("./testdata/day12").unwrap();
+        assert_eq!(day12_part2(&input).unwrap(), 354);
+    }
+
+    #[test]
+    fn heightmap_parse_error_test() {
+        let err = Heightmap::parse("Sabq\nab1d\nabcE").unwrap_err();
+        assert_eq!(err, ParseError::new(2, 3, "a-z, S or E", "1d"));
     }
 }
@@ -1,14 +1,20 @@
 use core::fmt;
-use std::io::{BufRead, BufReader};
+use std::iter::once;
 
+use eyre::Result;
 use itertools::Itertools;
 use nom::{
     branch::alt,
     character::complete::{char, digit1},
-    combinator::{all_consuming, map, map_res},
-    multi::separated_list0,
-    sequence::delimited,
-    Finish, IResult,
+    combinator::{cut, map, map_res, opt},
+    error::context,
+    multi::many0,
+    sequence::{delimited, pair, preceded},
+};
+
+use crate::{
+    parse::{parse_all, IResult, ParseError},
+    trace, Answer, Input, Solution,
 };
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -34,27 +40,40 @@ impl fmt::Display for Value {
 
 use Value::*;
 
-fn parse_number(i: &str) -> IResult<&str, Value> {
+fn parse_number(i: &str) -> IResult<'_, Value> {
     map(map_res(digit1, str::parse), Number)(i)
 }
 
-fn parse_list_item(i: &str) -> IResult<&str, Value> {
-    alt((parse_number, parse_list))(i)
+fn parse_list_item(i: &str) -> IResult<'_, Value> {
+    context("number or list", alt((parse_number, parse_list)))(i)
+}
+
+fn parse_list(i: &str) -> IResult<'_, Value> {
+    // An item must follow each comma, so don't backtrack to report a missing ']' instead
+    let items = map(
+        opt(pair(
+            parse_list_item,
+            many0(preceded(char(','), cut(parse_list_item))),
+        )),
+        |items| match items {
+            Some((first, rest)) => once(first).chain(rest).collect(),
+            None => vec![],
+        },
+    );
+    map(delimited(char('['), items, char(']')), List)(i)
+}
+
+fn parse(i: &str) -> Result<Value, ParseError> {
+    parse_all(i, parse_list)
 }
 
-fn parse_list(i: &str) -> IResult<&str, Value> {
-    map(
-        delimited(
-            char('['),
-            separated_list0(char(','), parse_list_item),
-            char(']'),
-        ),
-        List,
-    )(i)
+/// Reads the packets, numbering each by its line
+fn read_packets(input: &Input) -> Vec<(usize, &str)> {
+    input.lines().enumerate().collect()
 }
 
-fn parse(i: &str) -> Value {
-    all_consuming(parse_list)(i).finish().unwrap().1
+fn parse_packet((idx, line): &(usize, &str)) -> Result<Value, ParseError> {
+    parse(line).map_err(|e| e.offset_lines(*idx))
 }
 
 #[derive(Debug, PartialEq)]
@@ -65,17 +84,17 @@ enum Order {
 }
 
 fn compare_order_recur(l: &Value, r: &Value, depth: usize) -> Order {
-    let indent = "  ".repeat(depth);
-    println!("{indent}Compare {l} vs {r}");
+    let indent = depth * 2;
+    trace!(Debug, "{:indent$}Compare {l} vs {r}", "");
     let order = match (l, r) {
         (List(lv), List(rv)) => {
             if lv.is_empty() && rv.is_empty() {
                 Order::Continue
             } else if lv.is_empty() {
-                println!("{indent}Left list ran out of items => Right");
+                trace!(Debug, "{:indent$}Left list ran out of items => Right", "");
                 Order::Right
             } else if rv.is_empty() {
-                println!("{indent}Right list ran out of items => Wrong");
+                trace!(Debug, "{:indent$}Right list ran out of items => Wrong", "");
                 Order::Wrong
             } else {
                 let mut left_values = lv.iter().enumerate().peekable();
@@ -84,7 +103,7 @@ fn compare_order_recur(l: &Value, r: &Value, depth: usize) -> Order {
                     let r = match rv.get(idx) {
                         Some(x) => x,
                         None => {
-                            println!("{indent}Right list ran out of items => Wrong");
+                            trace!(Debug, "{:indent$}Right list ran out of items => Wrong", "");
                             o = Order::Wrong;
                             break;
                         }
@@ -93,14 +112,14 @@ fn compare_order_recur(l: &Value, r: &Value, depth: usize) -> Order {
                     match compare_order_recur(l, r, depth + 1) {
                         Order::Continue => {
                             if left_values.peek().is_none() && rv.get(idx + 1).is_some() {
-                                println!("{indent}Left list ran out of items => Right");
+                                trace!(Debug, "{:indent$}Left list ran out of items => Right", "");
                                 o = Order::Right;
                                 break;
                             }
                         }
                         order => {
                             o = order;
-                            println!("{indent}=> {o:?}");
+                            trace!(Debug, "{:indent$}=> {o:?}", "");
                             break;
                         }
                     }
@@ -124,149 +143,193 @@ fn compare_order_recur(l: &Value, r: &Value, depth: usize) -> Order {
 }
 
 fn compare_order(l: &Value, r: &Value) -> Order {
-    println!();
     compare_order_recur(l, r, 0)
 }
 
-fn day13<T>(reader: BufReader<T>) -> usize
-where
-    T: std::io::Read,
-{
-    let lines = reader.lines().map(|l| l.unwrap()).collect::<Vec<_>>();
-    let pairs = lines.split(|line| line.is_empty()).map(|pair| {
-        pair.iter()
-            .map(|e| parse(e))
-            .collect_tuple::<(_, _)>()
-            .unwrap()
-    });
-
-    pairs
+fn day13(input: &Input) -> Result<usize> {
+    let lines = read_packets(input);
+    let pairs = lines
+        .split(|(_, line)| line.is_empty())
+        .map(|pair| match pair {
+            [l, r] => Ok((parse_packet(l)?, parse_packet(r)?)),
+            [(idx, line)] => Err(ParseError::new(idx + 2, 1, "second packet of pair", line)),
+            [_, _, (idx, line), ..] => Err(ParseError::new(idx + 1, 1, "empty line", line)),
+            [] => Err(ParseError::new(1, 1, "packet", "")),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(pairs
+        .into_iter()
         .enumerate()
         .filter_map(|(idx, (l, r))| match compare_order(&l, &r) {
             Order::Right => Some(idx + 1),
             _ => None,
         })
-        .sum()
+        .sum())
 }
 
-fn day13_part2<T>(reader: BufReader<T>) -> usize
-where
-    T: std::io::Read,
-{
-    let packets = reader
-        .lines()
-        .filter_map(|l| l.ok())
-        .filter(|l| !l.is_empty());
-    let divider_packets = ["[[2]]".to_owned(), "[[6]]".to_owned()].into_iter();
-
-    packets
-        .chain(divider_packets)
-        .map(|l| parse(&l))
+fn day13_part2(input: &Input) -> Result<usize> {
+    let packets = read_packets(input)
+        .iter()
+        .filter(|(_, l)| !l.is_empty())
+        .map(parse_packet)
+        .collect::<Result<Vec<_>, _>>()?;
+    let divider_packets = [parse("[[2]]")?, parse("[[6]]")?];
+
+    Ok(packets
+        .into_iter()
+        .chain(divider_packets.clone())
         .sorted_by(|a, b| match compare_order(a, b) {
             Order::Right => std::cmp::Ordering::Less,
             Order::Wrong => std::cmp::Ordering::Greater,
-            _ => panic!(),
+            Order::Continue => std::cmp::Ordering::Equal,
         })
         .enumerate()
-        .filter(|(_idx, p)| p == &parse("[[2]]") || p == &parse("[[6]]"))
+        .filter(|(_idx, p)| divider_packets.contains(p))
         .map(|(idx, _p)| idx + 1)
-        .product()
+        .product())
+}
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    fn part1(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day13(input)?))
+    }
+
+    fn part2(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day13_part2(input)?))
+    }
+
+    fn parse(&self, input: &Input) -> Result<()> {
+        for packet in read_packets(input).iter().filter(|(_, l)| !l.is_empty()) {
+            parse_packet(packet)?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{fs::File, io::BufReader};
-
     use super::*;
 
     #[test]
     fn day13_simple_test() {
-        let input = File::open("./testdata/day13_simple").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day13(reader), 13);
+        let input = Input::from_path("./testdata/day13_simple").unwrap();
+        assert_eq!(day13(&input).unwrap(), 13);
     }
 
     #[test]
     fn day13_test() {
-        let input = File::open("./testdata/day13").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day13(reader), 5159);
+        let input = Input::from_path("./testdata/day13").unwrap();
+        // Includes pair 39, see compare_order_nested_number_test
+        assert_eq!(day13(&input).unwrap(), 5198);
     }
 
     #[test]
     fn day13_part2_simple_test() {
-        let input = File::open("./testdata/day13_simple").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day13_part2(reader), 140);
+        let input = Input::from_path("./testdata/day13_simple").unwrap();
+        assert_eq!(day13_part2(&input).unwrap(), 140);
     }
 
     #[test]
     fn day13_part2_test() {
-        let input = File::open("./testdata/day13").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day13_part2(reader), 22344);
+        let input = Input::from_path("./testdata/day13").unwrap();
+        assert_eq!(day13_part2(&input).unwrap(), 22344);
     }
 
     #[test]
     fn compare_order_test_1() {
-        let l = parse("[1,1,3,1,1]");
-        let r = parse("[1,1,5,1,1]");
+        let l = parse("[1,1,3,1,1]").unwrap();
+        let r = parse("[1,1,5,1,1]").unwrap();
         assert_eq!(compare_order(&l, &r), Order::Right);
     }
 
     #[test]
     fn compare_order_test_2() {
-        let l = parse("[[1],[2,3,4]]");
-        let r = parse("[[1],4]");
+        let l = parse("[[1],[2,3,4]]").unwrap();
+        let r = parse("[[1],4]").unwrap();
         assert_eq!(compare_order(&l, &r), Order::Right);
     }
 
     #[test]
     fn compare_order_test_3() {
-        let l = parse("[9]");
-        let r = parse("[[8,7,6]]");
+        let l = parse("[9]").unwrap();
+        let r = parse("[[8,7,6]]").unwrap();
         assert_eq!(compare_order(&l, &r), Order::Wrong);
     }
 
     #[test]
     fn compare_order_test_4() {
-        let l = parse("[[4,4],4,4]");
-        let r = parse("[[4,4],4,4,4]");
+        let l = parse("[[4,4],4,4]").unwrap();
+        let r = parse("[[4,4],4,4,4]").unwrap();
         assert_eq!(compare_order(&l, &r), Order::Right);
     }
 
     #[test]
     fn compare_order_test_5() {
-        let l = parse("[7,7,7,7]");
-        let r = parse("[7,7,7]");
+        let l = parse("[7,7,7,7]").unwrap();
+        let r = parse("[7,7,7]").unwrap();
         assert_eq!(compare_order(&l, &r), Order::Wrong);
     }
 
     #[test]
     fn compare_order_test_6() {
-        let l = parse("[]");
-        let r = parse("[3]");
+        let l = parse("[]").unwrap();
+        let r = parse("[3]").unwrap();
         assert_eq!(compare_order(&l, &r), Order::Right);
     }
 
     #[test]
     fn compare_order_test_7() {
-        let l = parse("[[[]]]");
-        let r = parse("[[]]");
+        let l = parse("[[[]]]").unwrap();
+        let r = parse("[[]]").unwrap();
         assert_eq!(compare_order(&l, &r), Order::Wrong);
     }
 
     #[test]
     fn compare_order_test_8() {
-        let l = parse("[1,[2,[3,[4,[5,6,7]]]],8,9]");
-        let r = parse("[1,[2,[3,[4,[5,6,0]]]],8,9]");
+        let l = parse("[1,[2,[3,[4,[5,6,7]]]],8,9]").unwrap();
+        let r = parse("[1,[2,[3,[4,[5,6,0]]]],8,9]").unwrap();
         assert_eq!(compare_order(&l, &r), Order::Wrong);
     }
 
+    #[test]
+    fn compare_order_nested_number_test() {
+        // Pair 39 of testdata/day13 is in the right order: the 6 on the right is wrapped in a
+        // list once against [[2,6],6] and again against [2,6], where 2 < 6. Leaving the pair
+        // out gives 5198 - 39 = 5159, the answer day13_test used to expect.
+        let l = parse("[[[],[[2,6],6],[],10]]").unwrap();
+        let r = parse("[[[],6,3,[[3,4,8]]]]").unwrap();
+        assert_eq!(compare_order(&l, &r), Order::Right);
+    }
+
+    #[test]
+    fn compare_order_trace_test() {
+        let l = parse("[[1],[2,3,4]]").unwrap();
+        let r = parse("[[1],4]").unwrap();
+        let (order, text) =
+            crate::trace::capture(crate::trace::Level::Debug, || compare_order(&l, &r));
+        assert_eq!(order, Order::Right);
+        assert_eq!(
+            text,
+            "\
+Compare [[1],[2,3,4]] vs [[1],4]
+  Compare [1] vs [1]
+    Compare 1 vs 1
+  Compare [2,3,4] vs 4
+    Compare [2,3,4] vs [4]
+      Compare 2 vs 4
+    => Right
+=> Right
+"
+        );
+    }
+
     #[test]
     fn parse_test() {
         assert_eq!(
-            parse("[1,[[1,2,3],1,2,3]]"),
+            parse("[1,[[1,2,3],1,2,3]]").unwrap(),
             List(vec![
                 Number(1),
                 List(vec![
@@ -278,4 +341,22 @@ mod tests {
             ],)
         );
     }
+
+    #[test]
+    fn parse_error_test() {
+        assert_eq!(
+            parse("[1,[2,x]]"),
+            Err(ParseError::new(1, 7, "number or list", "x]]"))
+        );
+    }
+
+    #[test]
+    fn day13_unpaired_packet_test() {
+        let input = Input::from("[1]\n[2]\n\n[3]\n");
+        let err = day13(&input).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ParseError>(),
+            Some(&ParseError::new(5, 1, "second packet of pair", "[3]"))
+        );
+    }
 }
@@ -1,248 +1,278 @@
-use std::{collections::HashMap, iter::once, ops::Add};
+use std::{fmt, iter::once};
 
+use eyre::Result;
 use itertools::Itertools;
 use nom::{
     bytes::complete::tag,
     character::complete::{char, digit1},
-    combinator::{all_consuming, map, map_res},
-    multi::separated_list1,
-    sequence::separated_pair,
-    Finish, IResult,
+    combinator::{cut, map, map_res},
+    error::context,
+    multi::many0,
+    sequence::{preceded, separated_pair},
 };
 
-#[derive(Debug, PartialEq)]
+use crate::{
+    animate::{Animate, Frame},
+    grid::{Grid, Pos},
+    image::{Image, Palette},
+    parse::{lines_of, parse_all, IResult, ParseError},
+    point::Point,
+    Answer, Input, Solution,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum Cell {
+    Air,
     Rock,
     Sand,
 }
 
-const SAND_SOURCE_POINT: Coord = Coord { x: 500, y: 0 };
+const SAND_SOURCE_POINT: Point = Point::new(500, 0);
 const SAND_SOURCE: char = '+';
 const SAND: char = 'o';
 const ROCK: char = '#';
 const AIR: char = '.';
 
+/// Directions sand tries to fall in, in order
+const FALL_STEPS: [Point; 3] = [Point::DOWN, Point::DOWN_LEFT, Point::DOWN_RIGHT];
+
 #[derive(Debug)]
-pub struct Grid {
-    data: HashMap<Coord, Cell>,
-    dropping_sand: Option<Coord>,
-    bounds: (Coord, Coord),
-    floor: bool,
+pub struct Cave {
+    /// The part of the cave sand can come to rest in, with the sand source on the top row
+    grid: Grid<Cell>,
+    /// x coordinate of the grid's left column
+    left: i64,
+    dropping_sand: Option<Pos>,
 }
 
-impl Grid {
-    pub fn from_rock_paths(input: &str, floor: bool) -> Grid {
-        let mut data = HashMap::new();
-        let rock_paths = all_consuming(parse_all_paths)(input.trim_end())
-            .finish()
-            .unwrap()
-            .1;
-        let rocks = rock_paths.into_iter().flat_map(|p| p.expand_to_coords());
-        for coord in rocks {
-            data.insert(coord, Cell::Rock);
+impl Cave {
+    pub fn from_rock_paths(input: &str, floor: bool) -> Result<Cave, ParseError> {
+        let rock_paths = parse_all(input.trim_end(), parse_all_paths)?;
+        let rocks = rock_paths
+            .iter()
+            .flat_map(|p| p.expand_to_coords())
+            .collect::<Vec<_>>();
+        let (min, max) = calc_bounds(&rocks);
+
+        // Sand on a floor piles up in a triangle under the source, as wide as it is high
+        let (left, right, bottom) = if floor {
+            let floor_y = max.y + 2;
+            (
+                min.x.min(SAND_SOURCE_POINT.x - floor_y),
+                max.x.max(SAND_SOURCE_POINT.x + floor_y),
+                floor_y,
+            )
+        } else {
+            (min.x, max.x, max.y)
+        };
+
+        let mut grid = Grid::new((right - left + 1) as usize, bottom as usize + 1, Cell::Air);
+        for rock in rocks {
+            grid[((rock.x - left) as usize, rock.y as usize)] = Cell::Rock;
+        }
+        if floor {
+            for x in 0..grid.width() {
+                grid[(x, bottom as usize)] = Cell::Rock;
+            }
         }
-        let bounds = calc_bounds(&data);
 
-        Grid {
-            data,
+        Ok(Cave {
+            grid,
+            left,
             dropping_sand: None,
-            bounds,
-            floor,
-        }
+        })
     }
 
-    fn coord_out_of_bounds(&self, coord: &Coord) -> bool {
-        let (min, max) = self.bounds;
-        coord.x < min.x || coord.x > max.x || coord.y < min.y || coord.y > max.y
+    fn source(&self) -> Pos {
+        (
+            (SAND_SOURCE_POINT.x - self.left) as usize,
+            SAND_SOURCE_POINT.y as usize,
+        )
     }
 
     fn sand_count(&self) -> usize {
-        self.data.values().filter(|v| **v == Cell::Sand).count()
-    }
-
-    pub fn draw(&self) {
-        let (min, max) = self.bounds;
-        for y in min.y..=max.y {
-            for x in min.x..=max.x {
-                let coord: Coord = (x, y).into();
-                let d = self.get_coord(&coord);
-                let ch = if d == Some(&Cell::Rock) {
-                    ROCK
-                } else if d == Some(&Cell::Sand) || self.dropping_sand.as_ref() == Some(&coord) {
-                    SAND
-                } else if coord == SAND_SOURCE_POINT {
-                    SAND_SOURCE
-                } else {
-                    AIR
-                };
-                print!("{}", ch);
-            }
-            println!()
-        }
+        self.grid.iter().filter(|(_, c)| **c == Cell::Sand).count()
     }
 
-    fn get_coord(&self, coord: &Coord) -> Option<&Cell> {
-        let floor_y = self.bounds.1.y + 2;
-        self.data.get(coord).or({
-            if coord.y == floor_y {
-                Some(&Cell::Rock)
-            } else {
-                None
+    /// Where the sand falls to next, which is where it is if it has come to rest, or `None` if
+    /// it falls out of the cave
+    fn next_dropping_sand_position(&self, sand: Pos) -> Option<Pos> {
+        for step in FALL_STEPS {
+            match self.grid.offset(sand, step) {
+                None => return None,
+                Some(pos) if self.grid[pos] == Cell::Air => return Some(pos),
+                Some(_) => {}
             }
-        })
-    }
-
-    fn next_dropping_sand_position(&self, sand: &Coord) -> Coord {
-        let bottom = self.get_coord(&sand.offset_xy(0, 1));
-        let bottom_left = self.get_coord(&sand.offset_xy(-1, 1));
-        let bottom_right = self.get_coord(&sand.offset_xy(1, 1));
-        match (bottom_left, bottom, bottom_right) {
-            (_, None, _) => sand.offset_xy(0, 1),
-            (None, Some(_), _) => sand.offset_xy(-1, 1),
-            (Some(_), Some(_), None) => sand.offset_xy(1, 1),
-            (Some(_), Some(_), Some(_)) => *sand,
         }
-    }
-
-    fn reset_dropping_sand(&mut self) {
-        self.dropping_sand = Some(SAND_SOURCE_POINT);
+        Some(sand)
     }
 
     pub fn step(&mut self) -> bool {
+        let source = self.source();
         match self.dropping_sand {
-            Some(s) => {
-                let next_sand_pos = self.next_dropping_sand_position(&s);
-                if !self.floor && self.coord_out_of_bounds(&next_sand_pos) {
-                    return false;
+            Some(s) => match self.next_dropping_sand_position(s) {
+                None => return false,
+                Some(next_sand_pos) if next_sand_pos == s => {
+                    self.grid[s] = Cell::Sand;
+                    self.dropping_sand = Some(source);
                 }
-
-                if next_sand_pos == s {
-                    self.data.insert(s, Cell::Sand);
-                    self.reset_dropping_sand();
-                } else {
+                Some(next_sand_pos) => {
                     self.dropping_sand = Some(next_sand_pos);
                 }
-            }
+            },
             None => {
-                self.reset_dropping_sand();
+                self.dropping_sand = Some(source);
             }
         }
 
-        if self.floor && self.data.get(&SAND_SOURCE_POINT).is_some() {
-            return false;
-        }
+        self.grid[source] != Cell::Sand
+    }
 
-        true
+    fn char_at(&self, pos: Pos) -> char {
+        match self.grid[pos] {
+            Cell::Rock => ROCK,
+            Cell::Sand => SAND,
+            _ if self.dropping_sand == Some(pos) => SAND,
+            _ if pos == self.source() => SAND_SOURCE,
+            Cell::Air => AIR,
+        }
     }
 }
 
-fn calc_bounds(data: &HashMap<Coord, Cell>) -> (Coord, Coord) {
-    let bounds_from = once(&SAND_SOURCE_POINT).chain(data.keys());
-    let (min_x, max_x, min_y, max_y) = bounds_from.fold(
-        (
-            std::usize::MAX,
-            std::usize::MIN,
-            std::usize::MAX,
-            std::usize::MIN,
-        ),
-        |acc, rock| {
-            (
-                acc.0.min(rock.x),
-                acc.1.max(rock.x),
-                acc.2.min(rock.y),
-                acc.3.max(rock.y),
-            )
-        },
-    );
-
-    ((min_x, min_y).into(), (max_x, max_y).into())
+impl fmt::Display for Cave {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for pos in self.grid.positions() {
+            write!(f, "{}", self.char_at(pos))?;
+            if pos.0 == self.grid.width() - 1 {
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct Coord {
-    x: usize,
-    y: usize,
-}
+impl Animate for Cave {
+    fn step(&mut self) {
+        if !self.finished() {
+            Cave::step(self);
+        }
+    }
 
-impl Coord {
-    fn offset_xy(&self, x: i64, y: i64) -> Self {
-        Coord {
-            x: if x < 0 {
-                self.x - (x.unsigned_abs() as usize)
-            } else {
-                self.x + (x as usize)
-            },
-            y: if y < 0 {
-                self.y - (y.unsigned_abs() as usize)
-            } else {
-                self.y + (y as usize)
-            },
+    fn render(&self, frame: &mut Frame) {
+        frame.resize(self.grid.width(), self.grid.height());
+        for pos in self.grid.positions() {
+            frame.set(pos, self.char_at(pos));
         }
     }
-}
 
-impl Add for Coord {
-    type Output = Self;
+    /// Sand has either blocked the source or started falling out of the cave
+    fn finished(&self) -> bool {
+        self.grid[self.source()] == Cell::Sand
+            || self
+                .dropping_sand
+                .is_some_and(|s| self.next_dropping_sand_position(s).is_none())
+    }
 
-    fn add(self, other: Self) -> Self::Output {
-        Coord {
-            x: self.x + other.x,
-            y: self.y + other.y,
-        }
+    fn focus(&self) -> Option<Pos> {
+        self.dropping_sand
     }
 }
 
-impl From<(usize, usize)> for Coord {
-    fn from((x, y): (usize, usize)) -> Self {
-        Coord { x, y }
-    }
+fn calc_bounds(rocks: &[Point]) -> (Point, Point) {
+    let bounds_from = once(&SAND_SOURCE_POINT).chain(rocks);
+    let (min_x, max_x, min_y, max_y) =
+        bounds_from.fold((i64::MAX, i64::MIN, i64::MAX, i64::MIN), |acc, rock| {
+            (
+                acc.0.min(rock.x),
+                acc.1.max(rock.x),
+                acc.2.min(rock.y),
+                acc.3.max(rock.y),
+            )
+        });
+
+    (Point::new(min_x, min_y), Point::new(max_x, max_y))
 }
 
 #[derive(Debug, PartialEq)]
-struct Path(Vec<Coord>);
+struct Path(Vec<Point>);
 
 impl Path {
-    fn expand_to_coords(&self) -> Vec<Coord> {
+    fn expand_to_coords(&self) -> Vec<Point> {
         self.0
             .windows(2)
             .flat_map(|w| {
                 let (a, b) = (&w[0], &w[1]);
                 let x_range = if a.x < b.x { a.x..=b.x } else { b.x..=a.x };
                 let y_range = if a.y < b.y { a.y..=b.y } else { b.y..=a.y };
-                x_range.flat_map(move |x| y_range.clone().map(move |y| Coord { x, y }))
+                x_range.flat_map(move |x| y_range.clone().map(move |y| Point { x, y }))
             })
             .unique()
             .collect::<Vec<_>>()
     }
 }
 
-fn parse_coord(i: &str) -> IResult<&str, Coord> {
+fn parse_coord(i: &str) -> IResult<'_, Point> {
     map(
         separated_pair(
-            map_res(digit1, |s: &str| s.parse()),
+            context("x coordinate", map_res(digit1, |s: &str| s.parse())),
             char(','),
-            map_res(digit1, |s: &str| s.parse()),
+            context("y coordinate", map_res(digit1, |s: &str| s.parse())),
         ),
         |(x, y)| (x, y).into(),
     )(i)
 }
 
-fn parse_path(i: &str) -> IResult<&str, Path> {
-    map(separated_list1(tag(" -> "), parse_coord), Path)(i)
+fn parse_path(i: &str) -> IResult<'_, Path> {
+    let (i, first) = parse_coord(i)?;
+    let (i, rest) = many0(preceded(tag(" -> "), cut(parse_coord)))(i)?;
+    Ok((i, Path(once(first).chain(rest).collect())))
 }
 
-fn parse_all_paths(i: &str) -> IResult<&str, Vec<Path>> {
-    separated_list1(tag("\n"), parse_path)(i)
+fn parse_all_paths(i: &str) -> IResult<'_, Vec<Path>> {
+    lines_of(parse_path)(i)
 }
 
-fn day14(input: &str, floor: bool) -> usize {
-    let mut grid = Grid::from_rock_paths(input, floor);
+fn day14(input: &Input, floor: bool) -> Result<usize> {
+    let mut cave = Cave::from_rock_paths(input, floor)?;
     loop {
-        if !grid.step() {
+        if !cave.step() {
             break;
         }
     }
-    grid.sand_count()
+    Ok(cave.sand_count())
+}
+
+/// The cave once the sand has come to rest
+pub fn image(input: &Input, floor: bool) -> Result<Image> {
+    let mut cave = Cave::from_rock_paths(input, floor)?;
+    while cave.step() {}
+    let palette = Palette::new([20, 20, 30])
+        .with(Cell::Rock, [110, 110, 110])
+        .with(Cell::Sand, [230, 190, 90]);
+    let mut image = Image::from_grid(&cave.grid, |c| palette.colour(c));
+    let (x, y) = cave.source();
+    image.set(x, y, [220, 40, 40]);
+    Ok(image)
+}
+
+/// The cave has no floor in part 1 and has one in part 2 unless overridden
+pub struct Day14 {
+    pub floor: Option<bool>,
+}
+
+impl Solution for Day14 {
+    fn part1(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day14(input, self.floor.unwrap_or(false))?))
+    }
+
+    fn part2(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day14(input, self.floor.unwrap_or(true))?))
+    }
+
+    fn parse(&self, input: &Input) -> Result<()> {
+        parse_all(input, parse_all_paths)?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -253,38 +283,80 @@ mod tests {
     fn day14_simple_test() {
         let input = "
 498,4 -> 498,6 -> 496,6
-503,4 -> 502,4 -> 502,9 -> 494,9"
-            .trim_start();
-
-        assert_eq!(day14(input, false), 24);
+503,4 -> 502,4 -> 502,9 -> 494,9";
+        let input = Input::from(input);
+        assert_eq!(day14(&input, false).unwrap(), 24);
     }
 
     #[test]
     fn day14_test() {
-        let input = include_str!("../testdata/day14");
-        assert_eq!(day14(input, false), 610);
+        let input = Input::from_path("./testdata/day14").unwrap();
+        assert_eq!(day14(&input, false).unwrap(), 610);
     }
 
     #[test]
     fn day14_part2_simple_test() {
         let input = "
 498,4 -> 498,6 -> 496,6
-503,4 -> 502,4 -> 502,9 -> 494,9"
-            .trim_start();
-
-        assert_eq!(day14(input, true), 93);
+503,4 -> 502,4 -> 502,9 -> 494,9";
+        let input = Input::from(input);
+        assert_eq!(day14(&input, true).unwrap(), 93);
     }
 
     #[test]
     fn day14_part2_test() {
-        let input = include_str!("../testdata/day14");
-        assert_eq!(day14(input, true), 27194);
+        let input = Input::from_path("./testdata/day14").unwrap();
+        assert_eq!(day14(&input, true).unwrap(), 27194);
+    }
+
+    #[test]
+    fn cave_display_test() {
+        let input = "498,4 -> 498,6 -> 496,6\n503,4 -> 502,4 -> 502,9 -> 494,9";
+        let cave = Cave::from_rock_paths(input, false).unwrap();
+        let expected = "
+......+...
+..........
+..........
+..........
+....#...##
+....#...#.
+..###...#.
+........#.
+........#.
+#########.
+";
+        assert_eq!(cave.to_string(), expected.trim_start_matches('\n'));
+    }
+
+    #[test]
+    fn cave_animate_test() {
+        let input = "498,4 -> 498,6 -> 496,6\n503,4 -> 502,4 -> 502,9 -> 494,9";
+        let mut cave = Cave::from_rock_paths(input, false).unwrap();
+        let mut frame = Frame::default();
+        Animate::step(&mut cave);
+        cave.render(&mut frame);
+        assert_eq!(frame.to_string(), cave.to_string());
+        assert_eq!(cave.focus(), Some((6, 0)));
+
+        while !cave.finished() {
+            Animate::step(&mut cave);
+        }
+        assert_eq!(cave.sand_count(), 24);
     }
 
     #[test]
     fn parse_coord_test() {
         let coord = parse_coord("123,123").unwrap().1;
-        assert_eq!(coord, Coord { x: 123, y: 123 });
+        assert_eq!(coord, Point { x: 123, y: 123 });
+    }
+
+    #[test]
+    fn from_rock_paths_error_test() {
+        let input = "498,4 -> 498,6 -> 496,6\n503,4 -> 502,x";
+        assert_eq!(
+            Cave::from_rock_paths(input, false).unwrap_err(),
+            ParseError::new(2, 14, "y coordinate", "x")
+        );
     }
 
     #[test]
@@ -293,9 +365,9 @@ mod tests {
         assert_eq!(
             path,
             Path(vec![
-                Coord { x: 498, y: 4 },
-                Coord { x: 498, y: 6 },
-                Coord { x: 496, y: 6 },
+                Point { x: 498, y: 4 },
+                Point { x: 498, y: 6 },
+                Point { x: 496, y: 6 },
             ])
         );
     }
@@ -315,15 +387,15 @@ mod tests {
             paths,
             vec![
                 Path(vec![
-                    Coord { x: 498, y: 4 },
-                    Coord { x: 498, y: 6 },
-                    Coord { x: 496, y: 6 },
+                    Point { x: 498, y: 4 },
+                    Point { x: 498, y: 6 },
+                    Point { x: 496, y: 6 },
                 ]),
                 Path(vec![
-                    Coord { x: 503, y: 4 },
-                    Coord { x: 502, y: 4 },
-                    Coord { x: 502, y: 9 },
-                    Coord { x: 494, y: 9 },
+                    Point { x: 503, y: 4 },
+                    Point { x: 502, y: 4 },
+                    Point { x: 502, y: 9 },
+                    Point { x: 494, y: 9 },
                 ]),
             ]
         );
@@ -335,11 +407,11 @@ mod tests {
         assert_eq!(
             path.expand_to_coords(),
             vec![
-                Coord { x: 498, y: 4 },
-                Coord { x: 498, y: 5 },
-                Coord { x: 498, y: 6 },
-                Coord { x: 496, y: 6 },
-                Coord { x: 497, y: 6 }
+                Point { x: 498, y: 4 },
+                Point { x: 498, y: 5 },
+                Point { x: 498, y: 6 },
+                Point { x: 496, y: 6 },
+                Point { x: 497, y: 6 }
             ]
         );
     }
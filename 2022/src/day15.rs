@@ -1,29 +1,22 @@
 #![allow(dead_code, unused_imports, unused_variables)]
 use std::collections::HashSet;
 
+use eyre::{eyre, Result};
 use itertools::Itertools;
 use nom::character::complete as cc;
-use nom::combinator::{all_consuming, map, opt};
-use nom::multi::separated_list1;
-use nom::sequence::tuple;
-use nom::Finish;
+use nom::combinator::map;
+use nom::error::context;
 use nom::{
     bytes::complete::tag,
     sequence::{preceded, separated_pair},
-    IResult,
 };
 
-#[derive(Debug, PartialEq, Clone, Hash, Eq, Copy)]
-struct Point {
-    x: i64,
-    y: i64,
-}
-
-impl Point {
-    fn taxicab_distance(&self, p: &Point) -> i64 {
-        (self.x.abs_diff(p.x) + self.y.abs_diff(p.y)) as i64
-    }
-}
+use crate::{
+    parse::{lines_of, parse_all, IResult, ParseError},
+    point::Point,
+    range_set::RangeSet,
+    Answer, Input, Solution,
+};
 
 #[derive(Debug, PartialEq)]
 struct Sensor {
@@ -31,20 +24,23 @@ struct Sensor {
     closest_beacon: Point,
 }
 
-fn parse_point(i: &str) -> IResult<&str, Point> {
+fn parse_point(i: &str) -> IResult<'_, Point> {
     map(
         separated_pair(
-            preceded(tag("x="), cc::i64),
-            tag(", "),
-            preceded(tag("y="), cc::i64),
+            preceded(context("x=", tag("x=")), context("x coordinate", cc::i64)),
+            context("\", \"", tag(", ")),
+            preceded(context("y=", tag("y=")), context("y coordinate", cc::i64)),
         ),
         |(x, y)| Point { x, y },
     )(i)
 }
 
-fn parse_sensor(i: &str) -> IResult<&str, Sensor> {
-    let (i, sensor_point) = preceded(tag("Sensor at "), parse_point)(i)?;
-    let (i, closest_beacon_point) = preceded(tag(": closest beacon is at "), parse_point)(i)?;
+fn parse_sensor(i: &str) -> IResult<'_, Sensor> {
+    let (i, sensor_point) = preceded(context("\"Sensor at\"", tag("Sensor at ")), parse_point)(i)?;
+    let (i, closest_beacon_point) = preceded(
+        context("\": closest beacon is at\"", tag(": closest beacon is at ")),
+        parse_point,
+    )(i)?;
 
     Ok((
         i,
@@ -55,99 +51,75 @@ fn parse_sensor(i: &str) -> IResult<&str, Sensor> {
     ))
 }
 
-fn parse_all_sensors(i: &str) -> IResult<&str, Vec<Sensor>> {
-    let (i, (sensors, _)) = all_consuming(tuple((
-        separated_list1(tag("\n"), parse_sensor),
-        opt(tag("\n")),
-    )))(i)?;
-    Ok((i, sensors))
+fn parse_all_sensors(i: &str) -> Result<Vec<Sensor>, ParseError> {
+    parse_all(i, lines_of(parse_sensor))
 }
 
-fn day15(i: &str, y: i64) -> usize {
-    let sensors = parse_all_sensors(i).finish().unwrap().1;
+/// Positions in row `y` that are closer to a sensor than its closest beacon is
+fn row_coverage(sensors: &[Sensor], y: i64) -> RangeSet {
+    sensors
+        .iter()
+        .filter_map(|s| {
+            let radius = s.point.manhattan(s.closest_beacon);
+            let n = radius - s.point.y.abs_diff(y) as i64;
+            (n >= 0).then(|| s.point.x - n..=s.point.x + n)
+        })
+        .collect()
+}
+
+fn day15(input: &Input, y: i64) -> Result<usize> {
+    let sensors = parse_all_sensors(input)?;
     let beacons = sensors
         .iter()
         .map(|s| s.closest_beacon)
         .collect::<HashSet<_>>();
 
-    let mut ranges = vec![];
-    for s in sensors {
-        let radius = s.point.taxicab_distance(&s.closest_beacon);
-        let dist_from_y = s.point.y.abs_diff(y) as i64;
-        if dist_from_y > radius {
-            continue;
-        }
-        let n = i64::abs(dist_from_y - radius);
-        let xs = s.point.x - n..=s.point.x + n;
-        ranges.push(xs);
-    }
-
-    ranges.sort_by_key(|r| *r.start());
-    ranges
-        .into_iter()
-        .coalesce(|prev, curr| {
-            if curr.start() - 1 <= *prev.end() {
-                if curr.end() > prev.end() {
-                    Ok(*prev.start()..=*curr.end())
-                } else {
-                    Ok(prev)
-                }
-            } else {
-                Err((prev, curr))
-            }
-        })
-        .map(|r| {
-            let range_size = (r.end() - r.start() + 1) as usize;
-            let beacons_in_range = beacons
-                .iter()
-                .filter(|b| r.contains(&b.x) && b.y == y)
-                .count();
-            range_size - beacons_in_range
-        })
-        .sum()
+    let covered = row_coverage(&sensors, y);
+    let beacons_in_row = beacons
+        .iter()
+        .filter(|b| b.y == y && covered.contains(b.x))
+        .count();
+    Ok(covered.len() as usize - beacons_in_row)
 }
 
 // Couldn't have solved this without the range idea from
 // https://fasterthanli.me/series/advent-of-code-2022/part-15
-fn day15_part2(i: &str, max_xy: i64) -> i64 {
-    let sensors = parse_all_sensors(i).finish().unwrap().1;
-    let beacons = sensors
-        .iter()
-        .map(|s| s.closest_beacon)
-        .collect::<HashSet<_>>();
+fn day15_part2(input: &Input, max_xy: i64) -> Result<i64> {
+    let sensors = parse_all_sensors(input)?;
 
     for y in 0..=max_xy {
-        let mut ranges = vec![];
-        for s in &sensors {
-            let radius = s.point.taxicab_distance(&s.closest_beacon);
-            let dist_from_y = s.point.y.abs_diff(y) as i64;
-            if dist_from_y > radius {
-                continue;
-            }
-            let n = i64::abs(dist_from_y - radius);
-            let xs = (s.point.x - n).max(0)..=(s.point.x + n).min(max_xy);
-            ranges.push(xs);
+        let covered = row_coverage(&sensors, y);
+        if covered.contains_range(&(0..=max_xy)) {
+            continue;
         }
-        ranges.sort_by_key(|r| *r.start());
-        for r in ranges.into_iter().coalesce(|prev, curr| {
-            if curr.start() - 1 <= *prev.end() {
-                if curr.end() > prev.end() {
-                    Ok(*prev.start()..=*curr.end())
-                } else {
-                    Ok(prev)
-                }
-            } else {
-                Err((prev, curr))
-            }
-        }) {
-            let range_size = r.end() - r.start() + 1;
-            if range_size != max_xy + 1 {
-                return range_size * 4000000 + y;
-            }
+        if let Some(gap) = covered.gaps(0..=max_xy).ranges().first() {
+            return Ok(gap.start() * 4000000 + y);
         }
     }
 
-    panic!("no solution")
+    Err(eyre!("no uncovered position within 0..={max_xy}"))
+}
+
+pub struct Day15 {
+    /// Row in which to count positions where a beacon cannot be
+    pub row: i64,
+    /// Upper bound of both coordinates of the distress beacon
+    pub bound: i64,
+}
+
+impl Solution for Day15 {
+    fn part1(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day15(input, self.row)?))
+    }
+
+    fn part2(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day15_part2(input, self.bound)?))
+    }
+
+    fn parse(&self, input: &Input) -> Result<()> {
+        parse_all_sensors(input)?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -156,26 +128,26 @@ mod tests {
 
     #[test]
     fn day15_simple_test() {
-        let input = include_str!("../testdata/day15_simple");
-        assert_eq!(day15(input, 10), 26);
+        let input = Input::from_path("./testdata/day15_simple").unwrap();
+        assert_eq!(day15(&input, 10).unwrap(), 26);
     }
 
     #[test]
     fn day15_test() {
-        let input = include_str!("../testdata/day15");
-        assert_eq!(day15(input, 2000000), 5870800);
+        let input = Input::from_path("./testdata/day15").unwrap();
+        assert_eq!(day15(&input, 2000000).unwrap(), 5870800);
     }
 
     #[test]
     fn day15_part2_simple_test() {
-        let input = include_str!("../testdata/day15_simple");
-        assert_eq!(day15_part2(input, 20), 56000011);
+        let input = Input::from_path("./testdata/day15_simple").unwrap();
+        assert_eq!(day15_part2(&input, 20).unwrap(), 56000011);
     }
 
     #[test]
     fn day15_part2_test() {
-        let input = include_str!("../testdata/day15");
-        assert_eq!(day15_part2(input, 4000000), 10908230916597);
+        let input = Input::from_path("./testdata/day15").unwrap();
+        assert_eq!(day15_part2(&input, 4000000).unwrap(), 10908230916597);
     }
 
     #[test]
@@ -195,4 +167,15 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn parse_all_sensors_error_test() {
+        let input = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+Sensor at x=9, y=16: closest beacon is at x=10 y=16
+";
+        assert_eq!(
+            parse_all_sensors(input),
+            Err(ParseError::new(2, 47, "\", \"", "y=16"))
+        );
+    }
 }
@@ -1,12 +1,19 @@
-use std::{
-    char,
-    cmp::Ordering,
-    fs::File,
-    io::{BufRead, BufReader},
+use std::cmp::Ordering;
+
+use eyre::Result;
+use nom::{
+    character::complete::{char, one_of},
+    error::context,
+    sequence::separated_pair,
+};
+
+use crate::{
+    parse::{parse_all, ParseError},
+    Answer, Input, Solution,
 };
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum RPS {
+enum Rps {
     Rock,
     Paper,
     Scissors,
@@ -30,32 +37,32 @@ impl GameResult {
 }
 
 use GameResult::*;
-use RPS::*;
+use Rps::*;
 
-impl PartialOrd for RPS {
+impl PartialOrd for Rps {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match (self, other) {
-            (Paper, Rock) => Some(Ordering::Greater),
-            (Paper, Scissors) => Some(Ordering::Less),
-            (Paper, Paper) => Some(Ordering::Equal),
-            (Scissors, Paper) => Some(Ordering::Greater),
-            (Scissors, Rock) => Some(Ordering::Less),
-            (Scissors, Scissors) => Some(Ordering::Equal),
-            (Rock, Paper) => Some(Ordering::Less),
-            (Rock, Scissors) => Some(Ordering::Greater),
-            (Rock, Rock) => Some(Ordering::Equal),
-        }
+        Some(self.cmp(other))
     }
 }
 
-impl Ord for RPS {
+impl Ord for Rps {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        match (self, other) {
+            (Paper, Rock) => Ordering::Greater,
+            (Paper, Scissors) => Ordering::Less,
+            (Paper, Paper) => Ordering::Equal,
+            (Scissors, Paper) => Ordering::Greater,
+            (Scissors, Rock) => Ordering::Less,
+            (Scissors, Scissors) => Ordering::Equal,
+            (Rock, Paper) => Ordering::Less,
+            (Rock, Scissors) => Ordering::Greater,
+            (Rock, Rock) => Ordering::Equal,
+        }
     }
 }
 
-impl RPS {
-    fn fight(&self, opponent: RPS) -> GameResult {
+impl Rps {
+    fn fight(&self, opponent: Rps) -> GameResult {
         match (*self).cmp(&opponent) {
             Ordering::Less => Loss,
             Ordering::Equal => Draw,
@@ -72,21 +79,21 @@ impl RPS {
     }
 }
 
-fn parse_opponent(c: char) -> RPS {
+fn parse_opponent(c: char) -> Rps {
     match c {
         'A' => Rock,
         'B' => Paper,
         'C' => Scissors,
-        _ => panic!("unexpected char"),
+        _ => unreachable!("unexpected char"),
     }
 }
 
-fn parse_response(c: char) -> RPS {
+fn parse_response(c: char) -> Rps {
     match c {
         'X' => Rock,
         'Y' => Paper,
         'Z' => Scissors,
-        _ => panic!("unexpected char"),
+        _ => unreachable!("unexpected char"),
     }
 }
 
@@ -95,77 +102,107 @@ fn parse_desired_outcome(c: char) -> GameResult {
         'X' => Loss,
         'Y' => Draw,
         'Z' => Win,
-        _ => panic!("unexpected char"),
+        _ => unreachable!("unexpected char"),
     }
 }
 
-fn parse_line_chars(line: &str) -> (char, char) {
-    let mut split = line.split(' ').map(|s| s.chars().next().unwrap());
-    (split.next().unwrap(), split.next().unwrap())
+fn parse_line_chars(line: &str) -> Result<(char, char), ParseError> {
+    parse_all(
+        line,
+        separated_pair(
+            context("A, B or C", one_of("ABC")),
+            char(' '),
+            context("X, Y or Z", one_of("XYZ")),
+        ),
+    )
 }
 
-fn parse_round(input: &str) -> (RPS, RPS) {
-    let (a, b) = parse_line_chars(input);
-    (parse_opponent(a), parse_response(b))
+fn parse_round(input: &str) -> Result<(Rps, Rps), ParseError> {
+    let (a, b) = parse_line_chars(input)?;
+    Ok((parse_opponent(a), parse_response(b)))
 }
 
-fn parse_round_part2(input: &str) -> (RPS, GameResult) {
-    let (a, b) = parse_line_chars(input);
-    (parse_opponent(a), parse_desired_outcome(b))
+fn parse_round_part2(input: &str) -> Result<(Rps, GameResult), ParseError> {
+    let (a, b) = parse_line_chars(input)?;
+    Ok((parse_opponent(a), parse_desired_outcome(b)))
 }
 
-fn calculate_round_score(opponent: RPS, response: RPS) -> u32 {
+fn calculate_round_score(opponent: Rps, response: Rps) -> u32 {
     let shape_score = response.score();
     let outcome = response.fight(opponent);
     let outcome_score = outcome.score();
     shape_score + outcome_score
 }
 
-fn get_response_for_desired_outcome(opponent: RPS, desired_outcome: GameResult) -> RPS {
+fn get_response_for_desired_outcome(opponent: Rps, desired_outcome: GameResult) -> Rps {
     [Rock, Paper, Scissors]
         .into_iter()
         .find(|rps| rps.fight(opponent) == desired_outcome)
         .unwrap()
 }
 
-fn calculate_round_score_part2(opponent: RPS, desired_outcome: GameResult) -> u32 {
+fn calculate_round_score_part2(opponent: Rps, desired_outcome: GameResult) -> u32 {
     let response = get_response_for_desired_outcome(opponent, desired_outcome);
     let shape_score = response.score();
     shape_score + desired_outcome.score()
 }
 
-fn day2(reader: BufReader<File>) -> u32 {
-    reader
+fn day2(input: &Input) -> Result<u32> {
+    input
         .lines()
-        .map(|line| parse_round(&line.unwrap()))
-        .map(|(opponent, response)| calculate_round_score(opponent, response))
+        .enumerate()
+        .map(|(idx, line)| {
+            let (opponent, response) = parse_round(line).map_err(|e| e.offset_lines(idx))?;
+            Ok(calculate_round_score(opponent, response))
+        })
         .sum()
 }
 
-fn day2_part2(reader: BufReader<File>) -> u32 {
-    reader
+fn day2_part2(input: &Input) -> Result<u32> {
+    input
         .lines()
-        .map(|line| parse_round_part2(&line.unwrap()))
-        .map(|(opponent, desired_outcome)| calculate_round_score_part2(opponent, desired_outcome))
+        .enumerate()
+        .map(|(idx, line)| {
+            let (opponent, desired_outcome) =
+                parse_round_part2(line).map_err(|e| e.offset_lines(idx))?;
+            Ok(calculate_round_score_part2(opponent, desired_outcome))
+        })
         .sum()
 }
 
+pub struct Day2;
+
+impl Solution for Day2 {
+    fn part1(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day2(input)?))
+    }
+
+    fn part2(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day2_part2(input)?))
+    }
+
+    fn parse(&self, input: &Input) -> Result<()> {
+        for (idx, line) in input.lines().enumerate() {
+            parse_line_chars(line).map_err(|e| e.offset_lines(idx))?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::{fs::File, io::BufReader};
-
     use super::*;
 
     #[test]
     fn parse_round_test() {
         let input = "A Y";
-        assert_eq!(parse_round(input), (Rock, Paper));
+        assert_eq!(parse_round(input).unwrap(), (Rock, Paper));
     }
 
     #[test]
     fn parse_round_part2_test() {
         let input = "A Y";
-        assert_eq!(parse_round_part2(input), (Rock, Draw));
+        assert_eq!(parse_round_part2(input).unwrap(), (Rock, Draw));
     }
 
     #[test]
@@ -197,15 +234,30 @@ mod tests {
 
     #[test]
     fn day2_test() {
-        let input = File::open("./testdata/day2").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day2(reader), 10718);
+        let input = Input::from_path("./testdata/day2").unwrap();
+        assert_eq!(day2(&input).unwrap(), 10718);
     }
 
     #[test]
     fn day2_part2_test() {
-        let input = File::open("./testdata/day2").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day2_part2(reader), 14652);
+        let input = Input::from_path("./testdata/day2").unwrap();
+        assert_eq!(day2_part2(&input).unwrap(), 14652);
+    }
+
+    #[test]
+    fn parse_round_error_test() {
+        assert_eq!(
+            parse_round("A W"),
+            Err(ParseError::new(1, 3, "X, Y or Z", "W"))
+        );
+        assert_eq!(
+            parse_round("D Y"),
+            Err(ParseError::new(1, 1, "A, B or C", "D"))
+        );
+        assert_eq!(parse_round("A"), Err(ParseError::new(1, 2, "' '", "")));
+        assert_eq!(
+            parse_round("A YY"),
+            Err(ParseError::new(1, 4, "end of input", "Y"))
+        );
     }
 }
@@ -1,12 +1,13 @@
+use eyre::Result;
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use std::{
     collections::{HashMap, HashSet},
-    fs::File,
-    io::{BufRead, BufReader},
     iter,
 };
 
+use crate::{parse::ParseError, Answer, Input, Solution};
+
 lazy_static! {
     static ref ITEM_PRIORITY: HashMap<char, u32> = iter::zip(97..=122, 1..=26)
         .chain(iter::zip(65..=91, 27..=52))
@@ -18,6 +19,22 @@ fn item_priority(item: char) -> u32 {
     *ITEM_PRIORITY.get(&item).unwrap()
 }
 
+/// Checks that every item in the rucksack is a letter, and that it divides into two compartments
+fn parse_rucksack(line: &str) -> Result<&str, ParseError> {
+    if let Some((idx, _)) = line.char_indices().find(|(_, c)| !c.is_ascii_alphabetic()) {
+        return Err(ParseError::at(line, &line[idx..], "item letter"));
+    }
+    if !line.len().is_multiple_of(2) {
+        return Err(ParseError::at(
+            line,
+            &line[line.len()..],
+            "an even number of items",
+        ));
+    }
+
+    Ok(line)
+}
+
 fn partition_rucksack(rucksack: &str) -> (Vec<char>, Vec<char>) {
     let chars = rucksack.chars().collect::<Vec<char>>();
     let (p1, p2) = chars.split_at(rucksack.len() / 2);
@@ -42,45 +59,82 @@ fn find_duplicate_item_in_rucksacks(r1: &str, r2: &str, r3: &str) -> Vec<char> {
     )
 }
 
-fn day3(reader: BufReader<File>) -> u32 {
-    reader
+fn read_rucksacks(input: &Input) -> Result<Vec<&str>, ParseError> {
+    input
         .lines()
-        .flat_map(|line| find_duplicate_items(&line.unwrap()))
+        .enumerate()
+        .map(|(idx, line)| parse_rucksack(line).map_err(|e| e.offset_lines(idx)))
+        .collect()
+}
+
+fn day3(input: &Input) -> Result<u32> {
+    Ok(read_rucksacks(input)?
+        .iter()
+        .flat_map(|line| find_duplicate_items(line))
         .map(item_priority)
-        .sum()
+        .sum())
 }
 
-fn day3_part2(reader: BufReader<File>) -> u32 {
-    reader
-        .lines()
-        .map(|l| l.unwrap())
-        .chunks(3)
-        .into_iter()
-        .flat_map(|c| {
-            let (r1, r2, r3) = c.collect_tuple().expect("expected 3 items");
-            find_duplicate_item_in_rucksacks(&r1, &r2, &r3)
-        })
+fn day3_part2(input: &Input) -> Result<u32> {
+    let rucksacks = read_rucksacks(input)?;
+    if !rucksacks.len().is_multiple_of(3) {
+        return Err(
+            ParseError::new(rucksacks.len() + 1, 1, "another rucksack in group", "").into(),
+        );
+    }
+
+    Ok(rucksacks
+        .iter()
+        .tuples()
+        .flat_map(|(r1, r2, r3)| find_duplicate_item_in_rucksacks(r1, r2, r3))
         .map(item_priority)
-        .sum()
+        .sum())
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    fn part1(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day3(input)?))
+    }
+
+    fn part2(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day3_part2(input)?))
+    }
+
+    fn parse(&self, input: &Input) -> Result<()> {
+        read_rucksacks(input)?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::{fs::File, io::BufReader};
 
     #[test]
     fn day3_test() {
-        let input = File::open("./testdata/day3").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day3(reader), 7821);
+        let input = Input::from_path("./testdata/day3").unwrap();
+        assert_eq!(day3(&input).unwrap(), 7821);
     }
 
     #[test]
     fn day3_part2_test() {
-        let input = File::open("./testdata/day3").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day3_part2(reader), 2752);
+        let input = Input::from_path("./testdata/day3").unwrap();
+        assert_eq!(day3_part2(&input).unwrap(), 2752);
+    }
+
+    #[test]
+    fn parse_rucksack_test() {
+        assert_eq!(parse_rucksack("abCD"), Ok("abCD"));
+        assert_eq!(
+            parse_rucksack("ab-D"),
+            Err(ParseError::new(1, 3, "item letter", "-D"))
+        );
+        assert_eq!(
+            parse_rucksack("abc"),
+            Err(ParseError::new(1, 4, "an even number of items", ""))
+        );
     }
 
     #[test]
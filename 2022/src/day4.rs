@@ -1,64 +1,85 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-    ops::RangeInclusive,
+use std::ops::RangeInclusive;
+
+use eyre::Result;
+use nom::{
+    character::complete::{self as cc, char},
+    combinator::map,
+    error::context,
+    sequence::separated_pair,
+};
+
+use crate::{
+    parse::{parse_all, IResult, ParseError},
+    range_set::RangeSet,
+    Answer, Input, Solution,
 };
 
 type Assignment = RangeInclusive<i32>;
 
-fn parse_range(s: &str) -> Assignment {
-    let mut split = s.split('-').map(|s| s.parse::<i32>().unwrap());
-    let (start, end) = (split.next().unwrap(), split.next().unwrap());
-    start..=end
+fn parse_range(i: &str) -> IResult<'_, Assignment> {
+    map(
+        separated_pair(
+            context("section id", cc::i32),
+            char('-'),
+            context("section id", cc::i32),
+        ),
+        |(start, end)| start..=end,
+    )(i)
 }
 
-fn parse_assignments(line: &str) -> (Assignment, Assignment) {
-    let mut split = line.split(',').map(parse_range);
-    (split.next().unwrap(), split.next().unwrap())
+fn parse_assignments(line: &str) -> Result<(Assignment, Assignment), ParseError> {
+    parse_all(line, separated_pair(parse_range, char(','), parse_range))
 }
 
 /// Assignment pair is bad if either of the assignments sections are fully contained in the other
 fn is_bad_assignment_pair(assignments: (Assignment, Assignment)) -> bool {
     let (a1, a2) = assignments;
-    range_contains(&a1, &a2) || range_contains(&a2, &a1)
+    RangeSet::from(a1.clone()).contains_range(&a2) || RangeSet::from(a2).contains_range(&a1)
 }
 
 /// Assignment pair is bad if assignments overlap
 fn is_bad_assignment_pair_part2(assignments: (Assignment, Assignment)) -> bool {
     let (a1, a2) = assignments;
-    ranges_overlap(&a1, &a2)
+    RangeSet::from(a1).overlaps(&a2)
 }
 
-/// Returns true if r1 contains r2
-fn range_contains<T>(r1: &RangeInclusive<T>, r2: &RangeInclusive<T>) -> bool
-where
-    T: PartialOrd,
-{
-    r2.start() >= r1.start() && r2.end() <= r1.end()
+fn read_assignments(input: &Input) -> Result<Vec<(Assignment, Assignment)>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, l)| parse_assignments(l).map_err(|e| e.offset_lines(idx)))
+        .collect()
 }
 
-/// Returns true if r1 contains r2
-fn ranges_overlap<T>(r1: &RangeInclusive<T>, r2: &RangeInclusive<T>) -> bool
-where
-    T: PartialOrd,
-{
-    r1.start() <= r2.end() && r2.start() <= r1.end()
+fn day4(input: &Input) -> Result<u32> {
+    Ok(read_assignments(input)?
+        .into_iter()
+        .filter(|a| is_bad_assignment_pair(a.clone()))
+        .count() as u32)
 }
 
-fn day4(reader: BufReader<File>) -> u32 {
-    reader
-        .lines()
-        .map(|l| l.unwrap())
-        .filter(|l| is_bad_assignment_pair(parse_assignments(l)))
-        .count() as u32
+fn day4_part2(input: &Input) -> Result<u32> {
+    Ok(read_assignments(input)?
+        .into_iter()
+        .filter(|a| is_bad_assignment_pair_part2(a.clone()))
+        .count() as u32)
 }
 
-fn day4_part2(reader: BufReader<File>) -> u32 {
-    reader
-        .lines()
-        .map(|l| l.unwrap())
-        .filter(|l| is_bad_assignment_pair_part2(parse_assignments(l)))
-        .count() as u32
+pub struct Day4;
+
+impl Solution for Day4 {
+    fn part1(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day4(input)?))
+    }
+
+    fn part2(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day4_part2(input)?))
+    }
+
+    fn parse(&self, input: &Input) -> Result<()> {
+        read_assignments(input)?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -67,37 +88,48 @@ mod tests {
 
     #[test]
     fn day4_test() {
-        let input = File::open("./testdata/day4").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day4(reader), 448);
+        let input = Input::from_path("./testdata/day4").unwrap();
+        assert_eq!(day4(&input).unwrap(), 448);
     }
 
     #[test]
     fn day4_part2_test() {
-        let input = File::open("./testdata/day4").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day4_part2(reader), 794);
+        let input = Input::from_path("./testdata/day4").unwrap();
+        assert_eq!(day4_part2(&input).unwrap(), 794);
     }
 
     #[test]
-    fn range_contains_test() {
-        assert!(range_contains(&(1..=5), &(2..=4)))
+    fn is_bad_assignment_pair_contained_test() {
+        assert!(is_bad_assignment_pair((1..=5, 2..=4)));
+        assert!(is_bad_assignment_pair((2..=4, 1..=5)));
     }
 
     #[test]
-    fn ranges_overlap_test() {
-        assert!(ranges_overlap(&(1..=5), &(4..=7)));
-        assert!(!ranges_overlap(&(1..=5), &(6..=7)));
+    fn is_bad_assignment_pair_part2_test() {
+        assert!(is_bad_assignment_pair_part2((1..=5, 4..=7)));
+        assert!(!is_bad_assignment_pair_part2((1..=5, 6..=7)));
     }
 
     #[test]
     fn parse_elf_sections_test() {
-        assert_eq!(parse_assignments("8-18,10-19"), (8..=18, 10..=19))
+        assert_eq!(parse_assignments("8-18,10-19"), Ok((8..=18, 10..=19)))
+    }
+
+    #[test]
+    fn parse_assignments_error_test() {
+        assert_eq!(
+            parse_assignments("8-18;10-19"),
+            Err(ParseError::new(1, 5, "','", ";10-19"))
+        );
+        assert_eq!(
+            parse_assignments("8-18,10-x"),
+            Err(ParseError::new(1, 9, "section id", "x"))
+        );
     }
 
     #[test]
     fn parse_range_test() {
-        assert_eq!(parse_range("8-18"), 8..=18)
+        assert_eq!(parse_range("8-18").unwrap().1, 8..=18)
     }
 
     #[test]
@@ -1,24 +1,43 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::iter::repeat_n;
 
+use eyre::{eyre, Result, WrapErr};
 use itertools::Itertools;
 use ndarray::{Array, Array2};
+use nom::{
+    bytes::complete::tag,
+    character::complete::digit1,
+    combinator::{map, map_res},
+    error::context,
+    sequence::{preceded, tuple},
+};
+
+use crate::{
+    animate::{Animate, Frame},
+    parse::{parse_all, IResult, ParseError},
+    Answer, Input, Part, Solution,
+};
 
-fn stacks_from_diagram(input: &str) -> Vec<Vec<Crate>> {
+fn stacks_from_diagram(input: &str) -> Result<Vec<Vec<Crate>>, ParseError> {
     let diagram_chars = input
         .lines()
         .map(|l| l.chars().skip(1).step_by(4).collect::<Vec<char>>())
         .filter(|chars| !chars.iter().all(|c| c.is_numeric()))
         .collect::<Vec<Vec<char>>>();
-    let width = diagram_chars[0].len();
+    if diagram_chars.is_empty() {
+        return Err(ParseError::at(input, input, "crate diagram"));
+    }
+
+    // Editors like to strip the trailing spaces that keep the rows the same width
+    let width = diagram_chars.iter().map(|row| row.len()).max().unwrap_or(0);
     let height = diagram_chars.len();
     let array: Array2<char> = Array::from_shape_vec(
         (height, width),
-        diagram_chars.into_iter().flatten().collect(),
+        diagram_chars
+            .into_iter()
+            .flat_map(|row| row.into_iter().pad_using(width, |_| ' '))
+            .collect(),
     )
-    .unwrap();
+    .expect("rows are padded to the same width");
 
     let stacks = array
         .columns()
@@ -31,7 +50,7 @@ fn stacks_from_diagram(input: &str) -> Vec<Vec<Crate>> {
         })
         .collect();
 
-    stacks
+    Ok(stacks)
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -46,123 +65,305 @@ impl Crate {
     }
 }
 
-#[derive(Debug)]
+/// A crane that rearranges stacks of crates
+trait Crane {
+    fn stacks(&self) -> &[Vec<Crate>];
+
+    fn apply_step(&mut self, step: &Step) -> Result<()>;
+
+    fn get_topmost_crates(&self) -> Vec<Crate> {
+        self.stacks()
+            .iter()
+            .filter_map(|s| s.last().copied())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
 struct CrateMover9000 {
     stacks: Vec<Vec<Crate>>,
 }
 
+/// Returns the stack numbered `n`, counting from 1
+fn get_stack(stacks: &mut [Vec<Crate>], n: usize) -> Result<&mut Vec<Crate>> {
+    let count = stacks.len();
+    n.checked_sub(1)
+        .and_then(|idx| stacks.get_mut(idx))
+        .ok_or_else(|| eyre!("stack {n} does not exist, there are {count} stacks"))
+}
+
 impl CrateMover9000 {
-    fn from_diagram(input: &str) -> CrateMover9000 {
-        CrateMover9000 {
-            stacks: stacks_from_diagram(input),
-        }
+    fn from_diagram(input: &str) -> Result<CrateMover9000, ParseError> {
+        Ok(CrateMover9000 {
+            stacks: stacks_from_diagram(input)?,
+        })
     }
 
-    fn pop_from_stack(&mut self, n: usize) -> Crate {
-        let stack = self.stacks.get_mut(n - 1).unwrap();
-        stack.pop().expect("expected crate to exist in stack")
+    fn pop_from_stack(&mut self, n: usize) -> Result<Crate> {
+        let stack = get_stack(&mut self.stacks, n)?;
+        stack
+            .pop()
+            .ok_or_else(|| eyre!("expected crate to exist in stack {n}"))
     }
 
-    fn push_to_stack(&mut self, n: usize, c: Crate) {
-        let stack = self.stacks.get_mut(n - 1).unwrap();
+    fn push_to_stack(&mut self, n: usize, c: Crate) -> Result<()> {
+        let stack = get_stack(&mut self.stacks, n)?;
         stack.push(c);
+        Ok(())
+    }
+}
+
+impl Crane for CrateMover9000 {
+    fn stacks(&self) -> &[Vec<Crate>] {
+        &self.stacks
     }
 
-    fn apply_step(&mut self, step: &Step) {
+    fn apply_step(&mut self, step: &Step) -> Result<()> {
         for _n in 0..step.count {
-            let popped_crate = self.pop_from_stack(step.from_stack);
-            self.push_to_stack(step.to_stack, popped_crate);
+            let popped_crate = self.pop_from_stack(step.from_stack)?;
+            self.push_to_stack(step.to_stack, popped_crate)?;
         }
-    }
 
-    fn get_topmost_crates(&self) -> Vec<Crate> {
-        self.stacks
-            .iter()
-            .filter_map(|s| s.last().copied())
-            .collect()
+        Ok(())
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct CrateMover9001 {
     stacks: Vec<Vec<Crate>>,
 }
 
 impl CrateMover9001 {
-    fn from_diagram(input: &str) -> CrateMover9001 {
-        CrateMover9001 {
-            stacks: stacks_from_diagram(input),
-        }
+    fn from_diagram(input: &str) -> Result<CrateMover9001, ParseError> {
+        Ok(CrateMover9001 {
+            stacks: stacks_from_diagram(input)?,
+        })
     }
 
-    fn pop_from_stack(&mut self, n: usize, count: usize) -> Vec<Crate> {
-        let stack = self.stacks.get_mut(n - 1).unwrap();
-        stack.drain((stack.len() - count)..).collect::<Vec<Crate>>()
+    fn pop_from_stack(&mut self, n: usize, count: usize) -> Result<Vec<Crate>> {
+        let stack = get_stack(&mut self.stacks, n)?;
+        let start = stack.len().checked_sub(count).ok_or_else(|| {
+            eyre!(
+                "expected {count} crates in stack {n}, found {}",
+                stack.len()
+            )
+        })?;
+        Ok(stack.drain(start..).collect::<Vec<Crate>>())
     }
 
-    fn push_to_stack(&mut self, n: usize, cs: Vec<Crate>) {
-        let stack = self.stacks.get_mut(n as usize - 1).unwrap();
+    fn push_to_stack(&mut self, n: usize, cs: Vec<Crate>) -> Result<()> {
+        let stack = get_stack(&mut self.stacks, n)?;
         stack.extend(cs);
+        Ok(())
     }
+}
 
-    fn apply_step(&mut self, step: &Step) {
-        let popped_crate = self.pop_from_stack(step.from_stack, step.count);
-        self.push_to_stack(step.to_stack, popped_crate);
+impl Crane for CrateMover9001 {
+    fn stacks(&self) -> &[Vec<Crate>] {
+        &self.stacks
     }
 
-    fn get_topmost_crates(&self) -> Vec<Crate> {
-        self.stacks
-            .iter()
-            .filter_map(|s| s.last().copied())
-            .collect()
+    fn apply_step(&mut self, step: &Step) -> Result<()> {
+        let popped_crate = self.pop_from_stack(step.from_stack, step.count)?;
+        self.push_to_stack(step.to_stack, popped_crate)
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 struct Step {
     count: usize,
     from_stack: usize,
     to_stack: usize,
 }
 
-impl Step {
-    fn from_str(s: &str) -> Step {
-        let (count, from_stack, to_stack) = s
-            .split(' ')
-            .filter_map(|s| s.parse::<usize>().ok())
-            .collect_tuple()
-            .unwrap();
+fn parse_number(i: &str) -> IResult<'_, usize> {
+    map_res(digit1, str::parse)(i)
+}
 
-        Step {
-            count,
-            from_stack,
-            to_stack,
-        }
+impl Step {
+    fn from_str(s: &str) -> Result<Step, ParseError> {
+        parse_all(
+            s,
+            map(
+                tuple((
+                    preceded(
+                        context("\"move \"", tag("move ")),
+                        context("crate count", parse_number),
+                    ),
+                    preceded(
+                        context("\" from \"", tag(" from ")),
+                        context("stack number", parse_number),
+                    ),
+                    preceded(
+                        context("\" to \"", tag(" to ")),
+                        context("stack number", parse_number),
+                    ),
+                )),
+                |(count, from_stack, to_stack)| Step {
+                    count,
+                    from_stack,
+                    to_stack,
+                },
+            ),
+        )
     }
 }
 
-fn day5(reader: BufReader<File>) -> String {
-    let mut lines = reader.lines().map(|l| l.unwrap());
-    let diagram = lines.by_ref().take_while(|l| !l.is_empty()).join("\n");
-    let mut crane = CrateMover9000::from_diagram(&diagram);
-    let procedure = lines.map(|l| Step::from_str(&l));
+/// Splits the input into the crate diagram and the rearrangement procedure
+fn parse_input(input: &Input) -> Result<(String, Vec<Step>), ParseError> {
+    let mut lines = input.lines().enumerate();
+    let diagram = lines
+        .by_ref()
+        .map(|(_idx, line)| line)
+        .take_while(|l| !l.is_empty())
+        .join("\n");
+    let procedure = lines
+        .map(|(idx, l)| Step::from_str(l).map_err(|e| e.offset_lines(idx)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((diagram, procedure))
+}
+
+fn day5(input: &Input) -> Result<String> {
+    let (diagram, procedure) = parse_input(input)?;
+    let mut crane = CrateMover9000::from_diagram(&diagram)?;
     for step in procedure {
-        crane.apply_step(&step);
+        crane.apply_step(&step)?;
     }
 
-    crane.get_topmost_crates().iter().map(|c| c.0).join("")
+    Ok(crane.get_topmost_crates().iter().map(|c| c.0).join(""))
 }
 
-fn day5_part2(reader: BufReader<File>) -> String {
-    let mut lines = reader.lines().map(|l| l.unwrap());
-    let diagram = lines.by_ref().take_while(|l| !l.is_empty()).join("\n");
-    let mut crane = CrateMover9001::from_diagram(&diagram);
-    let procedure = lines.map(|l| Step::from_str(&l));
+fn day5_part2(input: &Input) -> Result<String> {
+    let (diagram, procedure) = parse_input(input)?;
+    let mut crane = CrateMover9001::from_diagram(&diagram)?;
     for step in procedure {
-        crane.apply_step(&step);
+        crane.apply_step(&step)?;
+    }
+
+    Ok(crane.get_topmost_crates().iter().map(|c| c.0).join(""))
+}
+
+/// A crane carrying out the rearrangement procedure one move at a time
+struct Rearrangement<C> {
+    crane: C,
+    moves: Vec<Step>,
+    /// Number of moves made
+    made: usize,
+    /// Height of the tallest stack there will ever be, so the frame keeps the same size
+    tallest: usize,
+}
+
+impl<C: Crane + Clone> Rearrangement<C> {
+    /// Carries out the moves on a copy of the crane first, to find the tallest stack and to
+    /// reject moves that cannot be made
+    fn new(crane: C, moves: Vec<Step>) -> Result<Rearrangement<C>> {
+        let height = |crane: &C| crane.stacks().iter().map(|s| s.len()).max().unwrap_or(0);
+        let mut check = crane.clone();
+        let mut tallest = height(&check);
+        for (idx, step) in moves.iter().enumerate() {
+            check
+                .apply_step(step)
+                .wrap_err_with(|| format!("move {}", idx + 1))?;
+            tallest = tallest.max(height(&check));
+        }
+
+        Ok(Rearrangement {
+            crane,
+            moves,
+            made: 0,
+            tallest,
+        })
+    }
+}
+
+/// Lines above the stacks, for the last move made and a gap under it
+const MOVE_LINES: usize = 2;
+
+impl<C: Crane + Clone> Animate for Rearrangement<C> {
+    fn step(&mut self) {
+        if let Some(step) = self.moves.get(self.made) {
+            self.crane
+                .apply_step(step)
+                .expect("moves are checked when the rearrangement is created");
+            self.made += 1;
+        }
+    }
+
+    fn render(&self, frame: &mut Frame) {
+        let stacks = self.crane.stacks();
+        let last_move = match self.made.checked_sub(1) {
+            Some(idx) => {
+                let step = &self.moves[idx];
+                format!(
+                    "move {} from {} to {}",
+                    step.count, step.from_stack, step.to_stack
+                )
+            }
+            None => String::new(),
+        };
+        let width = (stacks.len() * 4).saturating_sub(1).max(last_move.len());
+        frame.resize(width, MOVE_LINES + self.tallest + 1);
+
+        frame.text((0, 0), &last_move);
+        let bottom = MOVE_LINES + self.tallest - 1;
+        for (n, stack) in stacks.iter().enumerate() {
+            for (height, c) in stack.iter().enumerate() {
+                frame.text((n * 4, bottom - height), &format!("[{}]", c.0));
+            }
+            frame.text((n * 4 + 1, bottom + 1), &(n + 1).to_string());
+        }
+    }
+
+    fn finished(&self) -> bool {
+        self.made == self.moves.len()
+    }
+}
+
+/// The procedure carried out by the part's crane, one move per step
+pub fn animation(input: &Input, part: Part) -> Result<Box<dyn Animate>> {
+    let (diagram, procedure) = parse_input(input)?;
+    Ok(match part {
+        Part::One => {
+            // The CrateMover 9000 moves crates one at a time, so each is a move of its own
+            let moves = procedure
+                .iter()
+                .flat_map(|step| {
+                    repeat_n(
+                        Step {
+                            count: 1,
+                            ..step.clone()
+                        },
+                        step.count,
+                    )
+                })
+                .collect();
+            let crane = CrateMover9000::from_diagram(&diagram)?;
+            Box::new(Rearrangement::new(crane, moves)?)
+        }
+        Part::Two => {
+            let crane = CrateMover9001::from_diagram(&diagram)?;
+            Box::new(Rearrangement::new(crane, procedure)?)
+        }
+    })
+}
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    fn part1(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day5(input)?))
+    }
+
+    fn part2(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day5_part2(input)?))
     }
 
-    crane.get_topmost_crates().iter().map(|c| c.0).join("")
+    fn parse(&self, input: &Input) -> Result<()> {
+        let (diagram, _procedure) = parse_input(input)?;
+        stacks_from_diagram(&diagram)?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -171,16 +372,14 @@ mod tests {
 
     #[test]
     fn day5_test() {
-        let input = File::open("./testdata/day5").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day5(reader), "MQTPGLLDN");
+        let input = Input::from_path("./testdata/day5").unwrap();
+        assert_eq!(day5(&input).unwrap(), "MQTPGLLDN");
     }
 
     #[test]
     fn day5_part2_test() {
-        let input = File::open("./testdata/day5").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day5_part2(reader), "LVZPSTTCZ");
+        let input = Input::from_path("./testdata/day5").unwrap();
+        assert_eq!(day5_part2(&input).unwrap(), "LVZPSTTCZ");
     }
 
     #[test]
@@ -195,7 +394,7 @@ mod tests {
 [J] [S] [Q] [S] [Z] [W] [P] [G] [D]
 [Z] [G] [V] [V] [Q] [M] [L] [N] [R]
  1   2   3   4   5   6   7   8   9 ";
-        let crane = CrateMover9000::from_diagram(input.trim_start_matches('\n'));
+        let crane = CrateMover9000::from_diagram(&Input::from(input)).unwrap();
         assert_eq!(
             crane.stacks[0],
             vec![
@@ -215,32 +414,38 @@ mod tests {
 [A]    
 [B] [C]
  1   2 ";
-        let mut crane = CrateMover9000::from_diagram(input.trim_start_matches('\n'));
-        crane.apply_step(&Step {
-            count: 1,
-            from_stack: 1,
-            to_stack: 2,
-        });
+        let mut crane = CrateMover9000::from_diagram(&Input::from(input)).unwrap();
+        crane
+            .apply_step(&Step {
+                count: 1,
+                from_stack: 1,
+                to_stack: 2,
+            })
+            .unwrap();
         assert_eq!(
             crane.stacks,
             vec![vec![Crate('B')], vec![Crate('C'), Crate('A')]]
         );
 
-        crane.apply_step(&Step {
-            count: 2,
-            from_stack: 2,
-            to_stack: 1,
-        });
+        crane
+            .apply_step(&Step {
+                count: 2,
+                from_stack: 2,
+                to_stack: 1,
+            })
+            .unwrap();
         assert_eq!(
             crane.stacks,
             vec![vec![Crate('B'), Crate('A'), Crate('C')], vec![]]
         );
 
-        crane.apply_step(&Step {
-            count: 3,
-            from_stack: 1,
-            to_stack: 2,
-        });
+        crane
+            .apply_step(&Step {
+                count: 3,
+                from_stack: 1,
+                to_stack: 2,
+            })
+            .unwrap();
         assert_eq!(
             crane.stacks,
             vec![vec![], vec![Crate('C'), Crate('A'), Crate('B')]]
@@ -253,12 +458,14 @@ mod tests {
 [A]    
 [B] [C]
  1   2 ";
-        let mut crane = CrateMover9001::from_diagram(input.trim_start_matches('\n'));
-        crane.apply_step(&Step {
-            count: 2,
-            from_stack: 1,
-            to_stack: 2,
-        });
+        let mut crane = CrateMover9001::from_diagram(&Input::from(input)).unwrap();
+        crane
+            .apply_step(&Step {
+                count: 2,
+                from_stack: 1,
+                to_stack: 2,
+            })
+            .unwrap();
         assert_eq!(
             crane.stacks,
             vec![vec![], vec![Crate('C'), Crate('B'), Crate('A')]]
@@ -271,17 +478,39 @@ mod tests {
 [F]     [O]
 [Z] [O] [V]
  1   2   3 ";
-        let crane = CrateMover9000::from_diagram(input.trim_start_matches('\n'));
+        let crane = CrateMover9000::from_diagram(&Input::from(input)).unwrap();
         assert_eq!(
             crane.get_topmost_crates(),
             vec![Crate('F'), Crate('O'), Crate('O')]
         );
     }
 
+    #[test]
+    fn rearrangement_animate_test() {
+        let input = Input::from("[A]    \n[B] [C]\n 1   2 \n\nmove 2 from 1 to 2");
+        let mut rearrangement = animation(&input, Part::One).unwrap();
+        let mut frame = Frame::default();
+        rearrangement.step();
+        rearrangement.render(&mut frame);
+        let expected = "
+move 1 from 1 to 2
+                  
+                  
+    [A]           
+[B] [C]           
+ 1   2            
+";
+        assert_eq!(frame.to_string(), expected.trim_start_matches('\n'));
+
+        rearrangement.step();
+        assert!(rearrangement.finished());
+        assert!(animation(&Input::from("[A]\n 1 \n\nmove 2 from 1 to 1"), Part::Two).is_err());
+    }
+
     #[test]
     fn step_from_str() {
         let input = "move 3 from 2 to 5";
-        let step = Step::from_str(input);
+        let step = Step::from_str(input).unwrap();
 
         assert_eq!(
             step,
@@ -292,4 +521,32 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn step_from_str_error_test() {
+        assert_eq!(
+            Step::from_str("move 3 from x to 5"),
+            Err(ParseError::new(1, 13, "stack number", "x"))
+        );
+        assert_eq!(
+            Step::from_str("move 3 to 5"),
+            Err(ParseError::new(1, 7, "\" from \"", "to"))
+        );
+    }
+
+    #[test]
+    fn crane_apply_step_error_test() {
+        let input = "
+[A]
+[B] [C]
+ 1   2 ";
+        let mut crane = CrateMover9000::from_diagram(&Input::from(input)).unwrap();
+        assert!(crane
+            .apply_step(&Step {
+                count: 1,
+                from_stack: 3,
+                to_stack: 1,
+            })
+            .is_err());
+    }
 }
@@ -1,13 +1,16 @@
+use std::hint::black_box;
+
+use eyre::{eyre, Result};
 use itertools::Itertools;
 
-fn day6(input: &str, distinct_count: usize) -> Option<usize> {
-    for (idx, slice) in input
-        .chars()
-        .collect::<Vec<char>>()
-        .as_slice()
-        .windows(distinct_count)
-        .enumerate()
-    {
+use crate::{Answer, Input, Solution};
+
+fn datastream(input: &Input) -> Vec<char> {
+    input.chars().collect()
+}
+
+fn day6(input: &Input, distinct_count: usize) -> Option<usize> {
+    for (idx, slice) in datastream(input).windows(distinct_count).enumerate() {
         if slice.iter().all_unique() {
             return Some(idx + distinct_count);
         }
@@ -16,19 +19,43 @@ fn day6(input: &str, distinct_count: usize) -> Option<usize> {
     None
 }
 
+/// Marker lengths default to 4 for part 1 and 14 for part 2
+pub struct Day6 {
+    pub marker_len: Option<usize>,
+}
+
+impl Solution for Day6 {
+    fn part1(&self, input: &Input) -> Result<Answer> {
+        let marker = day6(input, self.marker_len.unwrap_or(4))
+            .ok_or_else(|| eyre!("no start-of-packet marker found"))?;
+        Ok(Box::new(marker))
+    }
+
+    fn part2(&self, input: &Input) -> Result<Answer> {
+        let marker = day6(input, self.marker_len.unwrap_or(14))
+            .ok_or_else(|| eyre!("no start-of-message marker found"))?;
+        Ok(Box::new(marker))
+    }
+
+    fn parse(&self, input: &Input) -> Result<()> {
+        black_box(datastream(input));
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn day6_test() {
-        let input = include_str!("../testdata/day6");
-        assert_eq!(day6(input, 4), Some(1723));
+        let input = Input::from_path("./testdata/day6").unwrap();
+        assert_eq!(day6(&input, 4), Some(1723));
     }
 
     #[test]
     fn day6_part2_test() {
-        let input = include_str!("../testdata/day6");
-        assert_eq!(day6(input, 14), Some(3708));
+        let input = Input::from_path("./testdata/day6").unwrap();
+        assert_eq!(day6(&input, 14), Some(3708));
     }
 }
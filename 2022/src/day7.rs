@@ -1,11 +1,10 @@
-use std::{
-    collections::HashMap,
-    io::prelude::*,
-    io::{BufReader, Lines},
-};
+use std::collections::HashMap;
 
+use eyre::{eyre, Result, WrapErr};
 use itertools::Itertools;
 
+use crate::{parse::ParseError, trace, Answer, Input, Solution};
+
 #[derive(Debug, Clone)]
 enum Tree {
     Directory { children: HashMap<String, Tree> },
@@ -13,19 +12,21 @@ enum Tree {
 }
 
 impl Tree {
-    fn traverse(&mut self, path: &[String]) -> &mut Tree {
+    fn traverse(&mut self, path: &[String]) -> Option<&mut Tree> {
         let mut cur = self;
         for p in path {
             if let Tree::Directory { children } = cur {
-                cur = children.get_mut(p).expect("could not find path");
+                cur = children.get_mut(p)?;
             }
         }
 
-        cur
+        Some(cur)
     }
 
-    fn add(&mut self, path: &[String], ls_output: LsOutput) {
-        let tree = self.traverse(path);
+    fn add(&mut self, path: &[String], ls_output: LsOutput) -> Result<()> {
+        let tree = self
+            .traverse(path)
+            .ok_or_else(|| eyre!("directory /{} was never listed", path.join("/")))?;
         if let Tree::Directory { children } = tree {
             match ls_output {
                 LsOutput::File(name, size) => {
@@ -36,14 +37,13 @@ impl Tree {
                 }
             }
         }
+
+        Ok(())
     }
 
     fn get_size(&self) -> u32 {
         match self {
-            Tree::Directory { children } => children
-                .iter()
-                .map(|(_dirname, tree)| tree.get_size())
-                .sum(),
+            Tree::Directory { children } => children.values().map(|tree| tree.get_size()).sum(),
             Tree::File(size) => *size,
         }
     }
@@ -70,16 +70,18 @@ impl Tree {
     }
 }
 
-impl<T: std::io::BufRead> From<Lines<T>> for Tree {
-    fn from(lines: Lines<T>) -> Tree {
+impl TryFrom<&Input> for Tree {
+    type Error = eyre::Report;
+
+    fn try_from(input: &Input) -> Result<Tree> {
         let mut current_path: Vec<String> = vec![];
         let mut tree = Tree::new_dir();
-        for line in lines {
-            match parse_line(line.unwrap()) {
+        for (idx, line) in input.lines().enumerate() {
+            match parse_line(line).map_err(|e| e.offset_lines(idx))? {
                 ParsedLine::Command(cmd, arg) => match (cmd.as_ref(), arg.as_deref()) {
                     ("cd", Some("/")) => continue,
                     ("cd", Some(path)) => {
-                        println!("cding to {path:?}");
+                        trace!(Debug, "cding to {path:?}");
                         match path {
                             ".." => {
                                 current_path.pop();
@@ -89,17 +91,18 @@ impl<T: std::io::BufRead> From<Lines<T>> for Tree {
                             }
                         }
                     }
-                    ("ls", _) => println!("listing files"),
-                    (_, _) => panic!("unexpected command"),
+                    ("ls", _) => trace!(Trace, "listing files"),
+                    (_, _) => unreachable!("unexpected command"),
                 },
                 ParsedLine::LsOutput(ls_output) => {
-                    println!("got file {ls_output:?}");
-                    tree.add(&current_path, ls_output);
+                    trace!(Trace, "got file {ls_output:?}");
+                    tree.add(&current_path, ls_output)
+                        .wrap_err_with(|| format!("line {}", idx + 1))?;
                 }
             }
         }
 
-        tree
+        Ok(tree)
     }
 }
 
@@ -115,29 +118,26 @@ enum ParsedLine {
     LsOutput(LsOutput),
 }
 
-fn day7<T>(reader: BufReader<T>) -> u32
-where
-    T: std::io::Read,
-{
-    let tree = Tree::from(reader.lines());
-    tree.get_directories()
+fn day7(input: &Input) -> Result<u32> {
+    let tree = Tree::try_from(input)?;
+    Ok(tree
+        .get_directories()
         .iter()
         .filter_map(|dir| match dir.get_size() {
             size if size <= 100_000 => Some(size),
             _ => None,
         })
-        .sum()
+        .sum())
 }
 
 const TOTAL_SPACE: u32 = 70_000_000;
 const REQUIRED_SPACE: u32 = 30_000_000;
 
-fn day7_part2<T>(reader: BufReader<T>) -> u32
-where
-    T: std::io::Read,
-{
-    let tree = Tree::from(reader.lines());
-    let unused_space = TOTAL_SPACE - tree.get_size();
+fn day7_part2(input: &Input) -> Result<u32> {
+    let tree = Tree::try_from(input)?;
+    let unused_space = TOTAL_SPACE
+        .checked_sub(tree.get_size())
+        .ok_or_else(|| eyre!("files take up more than the total disk space"))?;
     let directories = tree.get_directories();
 
     directories
@@ -148,39 +148,73 @@ where
         })
         .sorted()
         .next()
-        .unwrap()
+        .ok_or_else(|| eyre!("no directory is large enough to free up the required space"))
+}
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    fn part1(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day7(input)?))
+    }
+
+    fn part2(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day7_part2(input)?))
+    }
+
+    fn parse(&self, input: &Input) -> Result<()> {
+        Tree::try_from(input)?;
+        Ok(())
+    }
 }
 
-fn parse_line(line: String) -> ParsedLine {
+fn parse_line(line: &str) -> Result<ParsedLine, ParseError> {
     match line.chars().next() {
-        Some('$') => parse_command(&line),
-        Some(_) => parse_ls_output(&line),
-        None => panic!("unexpected empty line"),
+        Some('$') => parse_command(line),
+        Some(_) => parse_ls_output(line),
+        None => Err(ParseError::at(line, line, "command or ls output")),
     }
 }
 
-fn parse_ls_output(line: &str) -> ParsedLine {
-    let mut split = line.split(' ');
-    let (first_col, name) = (split.next().unwrap(), split.next().unwrap());
+fn parse_ls_output(line: &str) -> Result<ParsedLine, ParseError> {
+    let (first_col, name) = line
+        .split_once(' ')
+        .ok_or_else(|| ParseError::at(line, &line[line.len()..], "' '"))?;
+    if name.is_empty() {
+        return Err(ParseError::at(line, name, "file name"));
+    }
+
     match first_col {
-        "dir" => ParsedLine::LsOutput(LsOutput::Directory(name.to_string())),
-        size => ParsedLine::LsOutput(LsOutput::File(
-            name.to_string(),
-            size.parse::<u32>().unwrap(),
-        )),
+        "dir" => Ok(ParsedLine::LsOutput(LsOutput::Directory(name.to_string()))),
+        size => {
+            let size = size
+                .parse::<u32>()
+                .map_err(|_| ParseError::at(line, line, "dir or file size"))?;
+            Ok(ParsedLine::LsOutput(LsOutput::File(name.to_string(), size)))
+        }
     }
 }
 
-fn parse_command(line: &str) -> ParsedLine {
-    let mut split = line.split(' ').skip(1);
-    let (cmd, arg) = (split.next().unwrap(), split.next());
-    ParsedLine::Command(cmd.to_string(), arg.map(|s| s.to_string()))
+fn parse_command(line: &str) -> Result<ParsedLine, ParseError> {
+    let rest = line
+        .strip_prefix("$ ")
+        .ok_or_else(|| ParseError::at(line, line, "\"$ \""))?;
+    let mut split = rest.split(' ');
+    let (cmd, arg) = (split.next().unwrap_or(""), split.next());
+    match (cmd, arg) {
+        ("cd", Some(_)) | ("ls", None) => Ok(ParsedLine::Command(
+            cmd.to_string(),
+            arg.map(|s| s.to_string()),
+        )),
+        ("cd", None) => Err(ParseError::at(line, &line[line.len()..], "directory")),
+        ("ls", Some(arg)) => Err(ParseError::at(line, arg, "end of line")),
+        _ => Err(ParseError::at(line, rest, "cd or ls")),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::{fs::File, io::Cursor};
 
     #[test]
     fn test_day7_simple() {
@@ -208,24 +242,20 @@ $ ls
 8033020 d.log
 5626152 d.ext
 7214296 k";
-
-        let cursor = Cursor::new(input.trim_start_matches('\n'));
-        let reader = BufReader::new(cursor);
-        assert_eq!(day7(reader), 95437);
+        let input = Input::from(input);
+        assert_eq!(day7(&input).unwrap(), 95437);
     }
 
     #[test]
     fn test_day7() {
-        let input = File::open("./testdata/day7").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day7(reader), 1770595);
+        let input = Input::from_path("./testdata/day7").unwrap();
+        assert_eq!(day7(&input).unwrap(), 1770595);
     }
 
     #[test]
     fn test_day7_part2() {
-        let input = File::open("./testdata/day7").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day7_part2(reader), 2195372);
+        let input = Input::from_path("./testdata/day7").unwrap();
+        assert_eq!(day7_part2(&input).unwrap(), 2195372);
     }
 
     #[test]
@@ -233,13 +263,28 @@ $ ls
         let input = "$ cd asdf";
         assert_eq!(
             parse_command(input),
-            ParsedLine::Command("cd".to_string(), Some("asdf".to_string()))
+            Ok(ParsedLine::Command(
+                "cd".to_string(),
+                Some("asdf".to_string())
+            ))
         );
 
         let input = "$ ls";
         assert_eq!(
             parse_command(input),
-            ParsedLine::Command("ls".to_string(), None)
+            Ok(ParsedLine::Command("ls".to_string(), None))
+        );
+    }
+
+    #[test]
+    fn parse_command_error_test() {
+        assert_eq!(
+            parse_command("$ rm -rf"),
+            Err(ParseError::new(1, 3, "cd or ls", "rm"))
+        );
+        assert_eq!(
+            parse_command("$ cd"),
+            Err(ParseError::new(1, 5, "directory", ""))
         );
     }
 
@@ -248,12 +293,16 @@ $ ls
         let input = "dir a";
         assert_eq!(
             parse_ls_output(input),
-            ParsedLine::LsOutput(LsOutput::Directory("a".to_string()))
+            Ok(ParsedLine::LsOutput(LsOutput::Directory("a".to_string())))
         );
         let input = "123 a";
         assert_eq!(
             parse_ls_output(input),
-            ParsedLine::LsOutput(LsOutput::File("a".to_string(), 123))
+            Ok(ParsedLine::LsOutput(LsOutput::File("a".to_string(), 123)))
+        );
+        assert_eq!(
+            parse_ls_output("12x a"),
+            Err(ParseError::new(1, 1, "dir or file size", "12x"))
         );
     }
 
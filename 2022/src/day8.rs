@@ -1,82 +1,54 @@
+use eyre::Result;
 use itertools::FoldWhile::{Continue, Done};
 use itertools::Itertools;
 
-fn day8(input: &str) -> i32 {
-    let size = input.lines().count();
-    let input = &input.replace('\n', "");
-    let grid = input
-        .chars()
-        .map(|c| c.to_string().parse::<u32>().unwrap())
-        .collect::<Vec<u32>>();
-    let get_coord = |x: usize, y: usize| grid[x + (size * y)];
-    let mut visible_trees = 0;
-
-    for x in 0..size {
-        for y in 0..size {
-            let is_at_edge = x == 0 || y == 0 || x == size - 1 || y == size - 1;
-            if is_at_edge {
-                visible_trees += 1;
-                continue;
-            }
-
-            let tree_height = get_coord(x, y);
+use crate::{
+    grid::Grid,
+    image::{Gradient, Image},
+    parse::ParseError,
+    point::Point,
+    Answer, Input, Solution,
+};
+
+/// Parses the grid of tree heights
+fn parse_grid(input: &str) -> Result<Grid<u32>, ParseError> {
+    Grid::parse(input, "tree height", |c| c.to_digit(10))
+}
 
-            let visible_to_left = (0..x).map(|x| get_coord(x, y)).all(|h| h < tree_height);
-            let visible_to_right = (x + 1..size)
-                .map(|x| get_coord(x, y))
-                .all(|h| h < tree_height);
-            let visible_to_top = (0..y).map(|y| get_coord(x, y)).all(|h| h < tree_height);
-            let visible_to_bottom = (y + 1..size)
-                .map(|y| get_coord(x, y))
-                .all(|h| h < tree_height);
+fn day8(input: &Input) -> Result<i32> {
+    let grid = parse_grid(input)?;
+    let mut visible_trees = 0;
 
-            let visible =
-                visible_to_left || visible_to_top || visible_to_right || visible_to_bottom;
+    for (pos, &tree_height) in grid.iter() {
+        // Trees at the edge have no trees in one direction, so are always visible
+        let visible = Point::ORTHOGONAL
+            .into_iter()
+            .any(|dir| grid.ray(pos, dir).all(|(_, &h)| h < tree_height));
 
-            if visible {
-                visible_trees += 1;
-            }
+        if visible {
+            visible_trees += 1;
         }
     }
 
-    visible_trees
+    Ok(visible_trees)
 }
 
-fn day8_part2(input: &str) -> u32 {
-    let size = input.lines().count();
-    let input = &input.replace('\n', "");
-    let grid = input
-        .chars()
-        .map(|c| c.to_string().parse::<u32>().unwrap())
-        .collect::<Vec<u32>>();
-    let get_coord = |x: usize, y: usize| grid[x + (size * y)];
+fn day8_part2(input: &Input) -> Result<u32> {
+    let grid = parse_grid(input)?;
     let mut max_scenic_score = 0;
 
-    for x in 0..size {
-        for y in 0..size {
-            let tree_height = get_coord(x, y);
-            let trees_to_left = (0..x).rev().map(|x| get_coord(x, y));
-            let trees_to_right = (x + 1..size).map(|x| get_coord(x, y));
-            let trees_to_top = (0..y).rev().map(|y| get_coord(x, y));
-            let trees_to_bottom = (y + 1..size).map(|y| get_coord(x, y));
-
-            let trees_visible_to_left = visible_trees(trees_to_left, tree_height);
-            let trees_visible_to_right = visible_trees(trees_to_right, tree_height);
-            let trees_visible_to_top = visible_trees(trees_to_top, tree_height);
-            let trees_visible_to_bottom = visible_trees(trees_to_bottom, tree_height);
-
-            let scenic_score = trees_visible_to_left
-                * trees_visible_to_right
-                * trees_visible_to_top
-                * trees_visible_to_bottom;
-
-            if scenic_score > max_scenic_score {
-                max_scenic_score = scenic_score
-            }
+    for (pos, &tree_height) in grid.iter() {
+        let scenic_score = Point::ORTHOGONAL
+            .into_iter()
+            .map(|dir| visible_trees(grid.ray(pos, dir).map(|(_, &h)| h), tree_height))
+            .product();
+
+        if scenic_score > max_scenic_score {
+            max_scenic_score = scenic_score
         }
     }
 
-    max_scenic_score
+    Ok(max_scenic_score)
 }
 
 fn visible_trees<I>(mut dir_trees: I, pov_tree_height: u32) -> u32
@@ -94,6 +66,36 @@ where
         .into_inner()
 }
 
+/// The forest with taller trees in brighter green
+pub fn image(input: &Input) -> Result<Image> {
+    let gradient = Gradient {
+        from: [10, 40, 10],
+        to: [140, 240, 100],
+        min: 0,
+        max: 9,
+    };
+    Ok(Image::from_grid(&parse_grid(input)?, |&h| {
+        gradient.colour(h)
+    }))
+}
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    fn part1(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day8(input)?))
+    }
+
+    fn part2(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day8_part2(input)?))
+    }
+
+    fn parse(&self, input: &Input) -> Result<()> {
+        parse_grid(input)?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -106,19 +108,31 @@ mod tests {
 65332
 33549
 35390";
-
-        assert_eq!(day8(input.trim()), 21);
+        let input = Input::from(input);
+        assert_eq!(day8(&input).unwrap(), 21);
     }
 
     #[test]
     fn test_day8() {
-        let input = include_str!("../testdata/day8");
-        assert_eq!(day8(input.trim()), 1843);
+        let input = Input::from_path("./testdata/day8").unwrap();
+        assert_eq!(day8(&input).unwrap(), 1843);
     }
 
     #[test]
     fn test_day8_part2() {
-        let input = include_str!("../testdata/day8");
-        assert_eq!(day8_part2(input.trim()), 180000);
+        let input = Input::from_path("./testdata/day8").unwrap();
+        assert_eq!(day8_part2(&input).unwrap(), 180000);
+    }
+
+    #[test]
+    fn parse_grid_error_test() {
+        assert_eq!(
+            parse_grid("303\n2x5\n653"),
+            Err(ParseError::new(2, 2, "tree height", "x5"))
+        );
+        assert_eq!(
+            parse_grid("303\n25\n653"),
+            Err(ParseError::new(2, 3, "3 cells per row", ""))
+        );
     }
 }
@@ -1,13 +1,20 @@
-use std::{
-    collections::HashSet,
-    io::{BufRead, BufReader},
+use std::collections::HashSet;
+
+use eyre::Result;
+use nom::{
+    character::complete::{self as cc, char, one_of},
+    combinator::map,
+    error::context,
+    sequence::separated_pair,
 };
 
-#[derive(Debug, Default, Hash, Eq, PartialEq, Clone)]
-struct Point {
-    x: i32,
-    y: i32,
-}
+use crate::{
+    animate::{Animate, Frame},
+    grid::Pos,
+    parse::{parse_all, ParseError},
+    point::Point,
+    trace, Answer, Input, Solution,
+};
 
 #[derive(Debug)]
 struct Grid {
@@ -23,57 +30,132 @@ impl Grid {
         }
     }
 
-    fn move_head(&mut self, motion: &Motion) {
+    fn move_head(&mut self, direction: &Direction) {
         let head = self.knots.first_mut().unwrap();
-
-        match motion.direction {
-            Direction::Up => head.y += 1,
-            Direction::Down => head.y -= 1,
-            Direction::Left => head.x -= 1,
-            Direction::Right => head.x += 1,
-        };
+        *head += direction.step();
     }
 
     fn move_knot_if_needed(&mut self, n: usize) {
-        let head = self.knots.get(n - 1).unwrap().clone();
-        let tail = self.knots.get_mut(n).unwrap();
-
-        let dx = head.x.abs_diff(tail.x);
-        let dy = head.y.abs_diff(tail.y);
-
-        // Tail needs to move
-        if dx > 1 || dy > 1 {
-            if head.x > tail.x {
-                tail.x += 1
-            }
+        let head = self.knots[n - 1];
+        let tail = &mut self.knots[n];
 
-            if head.y > tail.y {
-                tail.y += 1
-            }
+        // Tail needs to move, one step in each axis towards the head
+        if tail.chebyshev(head) > 1 {
+            *tail += (head - *tail).signum();
+        }
+    }
 
-            if head.x < tail.x {
-                tail.x -= 1
-            }
+    /// Moves the head one step and the rest of the rope after it
+    fn step(&mut self, direction: &Direction) {
+        self.move_head(direction);
+        trace!(Trace, "moved head {:?} to {}", direction, self.knots[0]);
 
-            if head.y < tail.y {
-                tail.y -= 1
-            }
+        for idx in 1..self.knots.len() {
+            self.move_knot_if_needed(idx);
         }
+
+        let tail = self.knots.last().unwrap();
+        self.tail_visited_positions.insert(*tail);
     }
 
     fn apply_motion(&mut self, motion: Motion) {
         for _ in 0..motion.steps {
-            self.move_head(&motion);
-            println!("moved head {:?} to {:?}", motion.direction, self.knots[0]);
+            self.step(&motion.direction);
+        }
+    }
+}
 
-            for idx in 1..self.knots.len() {
-                self.move_knot_if_needed(idx);
-            }
+/// The rope pulled through the motions one step of the head at a time
+pub struct Rope {
+    grid: Grid,
+    motions: Vec<Motion>,
+    /// Index of the motion being made
+    motion: usize,
+    /// Steps made so far of that motion
+    steps_made: i32,
+    /// Corners of the area the rope has been in
+    min: Point,
+    max: Point,
+}
+
+impl Rope {
+    pub fn new(input: &Input, knots: usize) -> Result<Rope, ParseError> {
+        let motions = input
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| parse_move(line).map_err(|e| e.offset_lines(idx)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut rope = Rope {
+            grid: Grid::new(knots),
+            motions,
+            motion: 0,
+            steps_made: 0,
+            min: Point::ORIGIN,
+            max: Point::ORIGIN,
+        };
+        rope.skip_made_motions();
+        Ok(rope)
+    }
+
+    /// Moves on to the first motion that has steps left to make
+    fn skip_made_motions(&mut self) {
+        while self
+            .motions
+            .get(self.motion)
+            .is_some_and(|m| self.steps_made >= m.steps)
+        {
+            self.motion += 1;
+            self.steps_made = 0;
+        }
+    }
+
+    fn char_at(&self, p: Point) -> char {
+        let knots = &self.grid.knots;
+        // The knot nearest the head is drawn over the ones behind it
+        match knots.iter().position(|&k| k == p) {
+            Some(0) => 'H',
+            Some(_) if knots.len() == 2 => 'T',
+            Some(idx) => char::from_digit(idx as u32, 36).unwrap_or('*'),
+            None if p == Point::ORIGIN => 's',
+            None if self.grid.tail_visited_positions.contains(&p) => '#',
+            None => '.',
+        }
+    }
+}
+
+impl Animate for Rope {
+    fn step(&mut self) {
+        let Some(motion) = self.motions.get(self.motion) else {
+            return;
+        };
+        self.grid.step(&motion.direction);
+        self.steps_made += 1;
+        for &knot in &self.grid.knots {
+            self.min = Point::new(self.min.x.min(knot.x), self.min.y.min(knot.y));
+            self.max = Point::new(self.max.x.max(knot.x), self.max.y.max(knot.y));
+        }
+        self.skip_made_motions();
+    }
 
-            let tail = self.knots.last().unwrap();
-            self.tail_visited_positions.insert(tail.clone());
+    fn render(&self, frame: &mut Frame) {
+        let size = self.max - self.min;
+        frame.resize(size.x as usize + 1, size.y as usize + 1);
+        for y in self.min.y..=self.max.y {
+            for x in self.min.x..=self.max.x {
+                let pos = ((x - self.min.x) as usize, (y - self.min.y) as usize);
+                frame.set(pos, self.char_at(Point::new(x, y)));
+            }
         }
     }
+
+    fn finished(&self) -> bool {
+        self.motion >= self.motions.len()
+    }
+
+    fn focus(&self) -> Option<Pos> {
+        let head = self.grid.knots[0] - self.min;
+        Some((head.x as usize, head.y as usize))
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -84,6 +166,17 @@ enum Direction {
     Right,
 }
 
+impl Direction {
+    fn step(&self) -> Point {
+        match self {
+            Direction::Up => Point::UP,
+            Direction::Down => Point::DOWN,
+            Direction::Left => Point::LEFT,
+            Direction::Right => Point::RIGHT,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 struct Motion {
     direction: Direction,
@@ -96,43 +189,63 @@ impl Motion {
     }
 }
 
-fn parse_move(input: &str) -> Motion {
-    let mut split = input.split(' ');
-    let (dir, steps) = (
-        split.next().unwrap().chars().next().unwrap(),
-        split.next().unwrap().parse::<i32>().unwrap(),
-    );
-
-    match dir {
-        'U' => Motion::new(Direction::Up, steps),
-        'D' => Motion::new(Direction::Down, steps),
-        'L' => Motion::new(Direction::Left, steps),
-        'R' => Motion::new(Direction::Right, steps),
-        _ => panic!("unexpected direction"),
-    }
+fn parse_move(input: &str) -> Result<Motion, ParseError> {
+    parse_all(
+        input,
+        map(
+            separated_pair(
+                context("U, D, L or R", one_of("UDLR")),
+                char(' '),
+                context("step count", cc::i32),
+            ),
+            |(dir, steps)| match dir {
+                'U' => Motion::new(Direction::Up, steps),
+                'D' => Motion::new(Direction::Down, steps),
+                'L' => Motion::new(Direction::Left, steps),
+                'R' => Motion::new(Direction::Right, steps),
+                _ => unreachable!("unexpected direction"),
+            },
+        ),
+    )
 }
 
-fn day9<T>(reader: BufReader<T>, knots: usize) -> usize
-where
-    T: std::io::Read,
-{
+fn day9(input: &Input, knots: usize) -> Result<usize> {
     let mut grid = Grid::new(knots);
 
-    for line in reader.lines() {
-        let m = parse_move(&line.unwrap());
+    for (idx, line) in input.lines().enumerate() {
+        let m = parse_move(line).map_err(|e| e.offset_lines(idx))?;
         grid.apply_motion(m);
     }
 
-    grid.tail_visited_positions.len()
+    Ok(grid.tail_visited_positions.len())
+}
+
+/// Rope length defaults to 2 knots for part 1 and 10 knots for part 2
+pub struct Day9 {
+    pub knots: Option<usize>,
+}
+
+impl Solution for Day9 {
+    fn part1(&self, input: &Input) -> Result<Answer> {
+        let knots = self.knots.unwrap_or(2);
+        Ok(Box::new(day9(input, knots)?))
+    }
+
+    fn part2(&self, input: &Input) -> Result<Answer> {
+        let knots = self.knots.unwrap_or(10);
+        Ok(Box::new(day9(input, knots)?))
+    }
+
+    fn parse(&self, input: &Input) -> Result<()> {
+        for (idx, line) in input.lines().enumerate() {
+            parse_move(line).map_err(|e| e.offset_lines(idx))?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{
-        fs::File,
-        io::{BufReader, Cursor},
-    };
-
     use super::*;
 
     #[test]
@@ -146,16 +259,14 @@ R 4
 D 1
 L 5
 R 2";
-        let cursor = Cursor::new(input.trim_start_matches('\n'));
-        let reader = BufReader::new(cursor);
-        assert_eq!(day9(reader, 2), 13);
+        let input = Input::from(input);
+        assert_eq!(day9(&input, 2).unwrap(), 13);
     }
 
     #[test]
     fn day9_test() {
-        let input = File::open("./testdata/day9").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day9(reader, 2), 6256);
+        let input = Input::from_path("./testdata/day9").unwrap();
+        assert_eq!(day9(&input, 2).unwrap(), 6256);
     }
 
     #[test]
@@ -169,9 +280,8 @@ R 4
 D 1
 L 5
 R 2";
-        let cursor = Cursor::new(input.trim_start_matches('\n'));
-        let reader = BufReader::new(cursor);
-        assert_eq!(day9(reader, 9), 1);
+        let input = Input::from(input);
+        assert_eq!(day9(&input, 9).unwrap(), 1);
     }
 
     #[test]
@@ -185,21 +295,49 @@ R 17
 D 10
 L 25
 U 20";
-        let cursor = Cursor::new(input.trim_start_matches('\n'));
-        let reader = BufReader::new(cursor);
-        assert_eq!(day9(reader, 10), 36);
+        let input = Input::from(input);
+        assert_eq!(day9(&input, 10).unwrap(), 36);
     }
 
     #[test]
     fn day9_part2_test() {
-        let input = File::open("./testdata/day9").unwrap();
-        let reader = BufReader::new(input);
-        assert_eq!(day9(reader, 10), 2665);
+        let input = Input::from_path("./testdata/day9").unwrap();
+        assert_eq!(day9(&input, 10).unwrap(), 2665);
+    }
+
+    #[test]
+    fn rope_animate_test() {
+        let input = Input::from("R 4\nU 4\nL 3\nD 1\nR 4\nD 1\nL 5\nR 2");
+        let mut rope = Rope::new(&input, 2).unwrap();
+        let mut frame = Frame::default();
+        for _ in 0..8 {
+            rope.step();
+        }
+        rope.render(&mut frame);
+        assert_eq!(frame.to_string(), "....H\n....T\n....#\n....#\ns###.\n");
+        assert_eq!(rope.focus(), Some((4, 0)));
+
+        while !rope.finished() {
+            rope.step();
+        }
+        assert_eq!(rope.grid.tail_visited_positions.len(), 13);
     }
 
     #[test]
     fn parse_move_test() {
         let input = "U 5";
-        assert_eq!(parse_move(input), Motion::new(Direction::Up, 5));
+        assert_eq!(parse_move(input), Ok(Motion::new(Direction::Up, 5)));
+    }
+
+    #[test]
+    fn parse_move_error_test() {
+        assert_eq!(
+            parse_move("X 5"),
+            Err(ParseError::new(1, 1, "U, D, L or R", "X"))
+        );
+        assert_eq!(
+            parse_move("U five"),
+            Err(ParseError::new(1, 3, "step count", "five"))
+        );
     }
 }
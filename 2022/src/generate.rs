@@ -0,0 +1,529 @@
+//! Random puzzle inputs for stress and property testing
+//!
+//! Each generator builds a valid input from a seed and a size, and records the answers that
+//! follow from how the input was built, so the solvers can be checked on inputs no one has
+//! solved by hand. The same seed and size give the same input for a given build.
+
+use std::{collections::HashSet, fmt::Write};
+
+use eyre::{eyre, Result};
+use itertools::Itertools;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{point::Point, Params, Part};
+
+/// Days that have an input generator
+pub const GENERATED_DAYS: [u8; 5] = [5, 7, 11, 14, 15];
+
+/// A generated puzzle input, with the answers known from its construction
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generated {
+    pub input: String,
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+    /// Parameters the input must be solved with
+    pub params: Params,
+}
+
+impl Generated {
+    fn new(input: String) -> Generated {
+        Generated {
+            input,
+            part1: None,
+            part2: None,
+            params: Params::default(),
+        }
+    }
+
+    /// The part's answer, if it follows from how the input was built
+    pub fn answer(&self, part: Part) -> Option<&str> {
+        match part {
+            Part::One => self.part1.as_deref(),
+            Part::Two => self.part2.as_deref(),
+        }
+    }
+
+    /// Answers manifest entries for the input saved as `file`, one for each known answer
+    pub fn manifest_entries(&self, file: &str) -> Vec<String> {
+        Part::BOTH
+            .into_iter()
+            .filter_map(|part| {
+                let answer = self.answer(part)?;
+                let entry = [file.to_string(), part.to_string(), answer.to_string()]
+                    .into_iter()
+                    .chain(self.params.to_args())
+                    .join(" ");
+                Some(entry)
+            })
+            .collect()
+    }
+}
+
+/// Generates an input for the day from the seed. `size` scales the input: it is the number of
+/// moves for day 5, directories for day 7, monkeys for day 11, rock paths for day 14, and extra
+/// sensors for day 15.
+pub fn generate(day: u8, seed: u64, size: usize) -> Result<Generated> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let size = size.max(1);
+    match day {
+        5 => Ok(crates(&mut rng, size)),
+        7 => Ok(filesystem(&mut rng, size)),
+        11 => Ok(monkeys(&mut rng, size)),
+        14 => Ok(rock_paths(&mut rng, size)),
+        15 => Ok(sensors(&mut rng, size)),
+        _ => Err(eyre!(
+            "day {day} has no input generator, expected one of {GENERATED_DAYS:?}"
+        )),
+    }
+}
+
+/// Day 5: a crate diagram and a procedure of `size` moves that never take more crates than a
+/// stack holds. Both answers come from carrying out the moves while they are generated.
+fn crates(rng: &mut StdRng, size: usize) -> Generated {
+    let stack_count = rng.gen_range(2..=9);
+    let mut stacks = vec![vec![]; stack_count];
+    for _ in 0..stack_count + size {
+        stacks[rng.gen_range(0..stack_count)].push(rng.gen_range('A'..='Z'));
+    }
+
+    let mut input = String::new();
+    let height = stacks.iter().map(Vec::len).max().unwrap_or(0);
+    for level in (0..height).rev() {
+        let row = stacks
+            .iter()
+            .map(|s| s.get(level).map_or("   ".to_string(), |c| format!("[{c}]")))
+            .join(" ");
+        writeln!(input, "{row}").unwrap();
+    }
+    let labels = (1..=stack_count).map(|n| format!(" {n} ")).join(" ");
+    writeln!(input, "{labels}\n").unwrap();
+
+    // Both cranes move the same number of crates, so their stacks always have the same heights
+    let mut one_at_a_time = stacks.clone();
+    let mut all_at_once = stacks;
+    for _ in 0..size {
+        let from = *(0..stack_count)
+            .filter(|&n| !all_at_once[n].is_empty())
+            .collect::<Vec<_>>()
+            .choose(rng)
+            .expect("there is always a crate to move");
+        let to = (from + rng.gen_range(1..stack_count)) % stack_count;
+        let count = rng.gen_range(1..=all_at_once[from].len());
+        writeln!(input, "move {count} from {} to {}", from + 1, to + 1).unwrap();
+
+        for _ in 0..count {
+            let c = one_at_a_time[from].pop().unwrap();
+            one_at_a_time[to].push(c);
+        }
+        let start = all_at_once[from].len() - count;
+        let moved = all_at_once[from].split_off(start);
+        all_at_once[to].extend(moved);
+    }
+
+    let tops = |stacks: &[Vec<char>]| stacks.iter().filter_map(|s| s.last()).collect();
+    Generated {
+        part1: Some(tops(&one_at_a_time)),
+        part2: Some(tops(&all_at_once)),
+        ..Generated::new(input)
+    }
+}
+
+/// Names made of a few lowercase letters, some with an extension
+fn name(rng: &mut StdRng, extension: bool) -> String {
+    let len = rng.gen_range(1..=8);
+    let mut name = (0..len)
+        .map(|_| rng.gen_range('a'..='z'))
+        .collect::<String>();
+    if extension && rng.gen_bool(0.5) {
+        name.push('.');
+        name.push_str(["txt", "dat", "log", "bin"].choose(rng).unwrap());
+    }
+    name
+}
+
+/// Day 7: a terminal session that lists each of `size` directories once, at least two, walking
+/// the tree depth first. One directory holds a file large enough that space has to be freed,
+/// and deleting that directory frees enough. Both answers come from the directory sizes of the
+/// tree the session was made from.
+fn filesystem(rng: &mut StdRng, size: usize) -> Generated {
+    let size = size.max(2);
+    struct Dir {
+        name: String,
+        children: Vec<usize>,
+        files: Vec<(String, u32)>,
+        size: u32,
+    }
+
+    // Each directory's parent comes before it, so the tree has no cycles and the directories
+    // can be summed from the last to the first
+    let mut dirs = vec![Dir {
+        name: "/".to_string(),
+        children: vec![],
+        files: vec![],
+        size: 0,
+    }];
+    let max_file_size = (30_000_000 / (4 * size as u32)).clamp(1, 300_000);
+    for n in 0..size {
+        if n > 0 {
+            let parent = rng.gen_range(0..n);
+            let taken = dirs[parent]
+                .children
+                .iter()
+                .map(|&c| dirs[c].name.clone())
+                .chain(dirs[parent].files.iter().map(|(f, _)| f.clone()))
+                .collect::<HashSet<_>>();
+            let name = loop {
+                let name = name(rng, false);
+                if !taken.contains(&name) {
+                    break name;
+                }
+            };
+            dirs[parent].children.push(n);
+            dirs.push(Dir {
+                name,
+                children: vec![],
+                files: vec![],
+                size: 0,
+            });
+        }
+        // Its files are made before any children, which are then named to not clash with them
+        let mut taken = HashSet::new();
+        for _ in 0..rng.gen_range(0..=4) {
+            let file = name(rng, true);
+            if taken.insert(file.clone()) {
+                dirs[n].files.push((file, rng.gen_range(1..=max_file_size)));
+            }
+        }
+    }
+
+    // Fill the disk past the 40,000,000 that leaves 30,000,000 unused. The file's extension is
+    // one the other files never have, so it cannot clash with them.
+    let small_files = dirs
+        .iter()
+        .flat_map(|d| &d.files)
+        .map(|(_, s)| s)
+        .sum::<u32>();
+    let total = rng.gen_range(40_000_001..=70_000_000);
+    let big = rng.gen_range(1..dirs.len());
+    dirs[big]
+        .files
+        .push(("disk.img".to_string(), total - small_files));
+
+    for n in (0..dirs.len()).rev() {
+        let files = dirs[n].files.iter().map(|(_, s)| s).sum::<u32>();
+        let children = dirs[n].children.iter().map(|&c| dirs[c].size).sum::<u32>();
+        dirs[n].size = files + children;
+    }
+
+    fn walk(dirs: &[Dir], n: usize, rng: &mut StdRng, out: &mut String) {
+        let dir = &dirs[n];
+        writeln!(out, "$ cd {}", dir.name).unwrap();
+        writeln!(out, "$ ls").unwrap();
+        let mut entries = dir
+            .children
+            .iter()
+            .map(|&c| format!("dir {}", dirs[c].name))
+            .chain(
+                dir.files
+                    .iter()
+                    .map(|(name, size)| format!("{size} {name}")),
+            )
+            .collect::<Vec<_>>();
+        entries.shuffle(rng);
+        for entry in entries {
+            writeln!(out, "{entry}").unwrap();
+        }
+
+        let mut children = dir.children.clone();
+        children.shuffle(rng);
+        for child in children {
+            walk(dirs, child, rng, out);
+            writeln!(out, "$ cd ..").unwrap();
+        }
+    }
+
+    let mut input = String::new();
+    walk(&dirs, 0, rng, &mut input);
+
+    let sizes = dirs.iter().map(|d| d.size).collect::<Vec<_>>();
+    let part1 = sizes.iter().filter(|&&s| s <= 100_000).sum::<u32>();
+    // Space to free so that 30,000,000 of the 70,000,000 are unused
+    let needed = sizes[0] - 40_000_000;
+    let part2 = sizes[1..].iter().filter(|&&s| s >= needed).min().unwrap();
+    Generated {
+        part1: Some(part1.to_string()),
+        part2: Some(part2.to_string()),
+        ..Generated::new(input)
+    }
+}
+
+/// A monkey's operation on worry levels
+#[derive(Debug, Clone, Copy)]
+enum Operation {
+    Square,
+    Times(u64),
+    Plus(u64),
+}
+
+impl Operation {
+    fn apply(self, old: u64) -> Option<u64> {
+        match self {
+            Operation::Square => old.checked_mul(old),
+            Operation::Times(n) => old.checked_mul(n),
+            Operation::Plus(n) => old.checked_add(n),
+        }
+    }
+}
+
+struct Monkey {
+    items: Vec<u64>,
+    operation: Operation,
+    divisor: u64,
+    if_true: usize,
+    if_false: usize,
+}
+
+/// Whether a worry level outgrows a `u64` in the 20 rounds of part 1, where worry levels are
+/// only divided by three rather than kept below the product of the divisors
+fn overflows(monkeys: &[Monkey]) -> bool {
+    let mut items = monkeys.iter().map(|m| m.items.clone()).collect::<Vec<_>>();
+    for _ in 0..20 {
+        for (n, monkey) in monkeys.iter().enumerate() {
+            for item in std::mem::take(&mut items[n]) {
+                let Some(worry) = monkey.operation.apply(item) else {
+                    return true;
+                };
+                let worry = worry / 3;
+                let to = if worry % monkey.divisor == 0 {
+                    monkey.if_true
+                } else {
+                    monkey.if_false
+                };
+                items[to].push(worry);
+            }
+        }
+    }
+    false
+}
+
+/// Day 11: `size` monkeys, at most nine, each testing for a different prime and throwing to two
+/// other monkeys. Monkeys that would overflow worry levels in part 1 are made again. Nothing
+/// about the answers follows from this, so none are given.
+fn monkeys(rng: &mut StdRng, size: usize) -> Generated {
+    const PRIMES: [u64; 9] = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+
+    let count = size.clamp(2, PRIMES.len());
+    let monkeys = loop {
+        let mut primes = PRIMES;
+        primes.shuffle(rng);
+        let squarer = rng.gen_range(0..count);
+        let monkeys = (0..count)
+            .map(|n| {
+                let items = (0..rng.gen_range(1..=8))
+                    .map(|_| rng.gen_range(50..100))
+                    .collect();
+                let operation = match rng.gen_range(0..2) {
+                    _ if n == squarer => Operation::Square,
+                    0 => Operation::Times(rng.gen_range(2..=19)),
+                    _ => Operation::Plus(rng.gen_range(1..=8)),
+                };
+                let others = (0..count).filter(|&m| m != n).collect::<Vec<_>>();
+                let targets = others.choose_multiple(rng, 2).copied().collect::<Vec<_>>();
+                Monkey {
+                    items,
+                    operation,
+                    divisor: primes[n],
+                    if_true: targets[0],
+                    if_false: *targets.last().unwrap(),
+                }
+            })
+            .collect::<Vec<_>>();
+        if !overflows(&monkeys) {
+            break monkeys;
+        }
+    };
+
+    let input = monkeys
+        .iter()
+        .enumerate()
+        .map(|(n, m)| {
+            let operation = match m.operation {
+                Operation::Square => "old * old".to_string(),
+                Operation::Times(k) => format!("old * {k}"),
+                Operation::Plus(k) => format!("old + {k}"),
+            };
+            format!(
+                "Monkey {n}:\n  Starting items: {}\n  Operation: new = {operation}\n  \
+             Test: divisible by {}\n    If true: throw to monkey {}\n    \
+             If false: throw to monkey {}\n",
+                m.items.iter().join(", "),
+                m.divisor,
+                m.if_true,
+                m.if_false
+            )
+        })
+        .join("\n");
+    Generated::new(input)
+}
+
+/// Day 14: `size` rock paths of horizontal and vertical lines below and around the sand source.
+/// Nothing about the answers follows from this, so none are given.
+fn rock_paths(rng: &mut StdRng, size: usize) -> Generated {
+    let spread = 5 * size as i64 + 10;
+    let depth = 3 * size as i64 + 10;
+
+    let mut input = String::new();
+    for _ in 0..size {
+        let mut point = Point {
+            x: 500 + rng.gen_range(-spread..=spread),
+            y: rng.gen_range(1..=depth),
+        };
+        let mut points = vec![point];
+        let mut horizontal = rng.gen_bool(0.5);
+        for _ in 0..rng.gen_range(1..=4) {
+            let len = rng.gen_range(1..=6) * if rng.gen_bool(0.5) { 1 } else { -1 };
+            if horizontal {
+                point.x += len;
+            } else {
+                point.y = (point.y + len).max(1);
+            }
+            horizontal = !horizontal;
+            points.push(point);
+        }
+        let path = points
+            .iter()
+            .map(|p| format!("{},{}", p.x, p.y))
+            .join(" -> ");
+        writeln!(input, "{path}").unwrap();
+    }
+    Generated::new(input)
+}
+
+/// Day 15: sensors that cover every position within the search bound but one. Four sensors
+/// sit diagonally from the gap, just outside the bound, each reaching up to the gap; `size`
+/// more are scattered around without reaching it. The bound is 100 times the size, and the
+/// part 2 answer is the gap's tuning frequency.
+fn sensors(rng: &mut StdRng, size: usize) -> Generated {
+    let bound = 100 * size as i64;
+    let gap = Point {
+        x: rng.gen_range(0..=bound),
+        y: rng.gen_range(0..=bound),
+    };
+
+    let mut sensors = vec![];
+    // A sensor `offset` away on both axes reaches every position of its quadrant that is at
+    // most `bound` away from the gap on either axis, except the gap itself
+    let offset = bound + 1;
+    for (dx, dy) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+        let sensor = gap
+            + Point {
+                x: dx * offset,
+                y: dy * offset,
+            };
+        let beacon = sensor
+            - Point {
+                x: dx * (2 * offset - 1),
+                y: 0,
+            };
+        sensors.push((sensor, beacon));
+    }
+    while sensors.len() < size + 4 {
+        let sensor = Point {
+            x: rng.gen_range(-bound / 2..=bound * 3 / 2),
+            y: rng.gen_range(-bound / 2..=bound * 3 / 2),
+        };
+        let to_gap = sensor.manhattan(gap);
+        if to_gap < 2 {
+            continue;
+        }
+        let radius = rng.gen_range(1..to_gap);
+        let dx = rng.gen_range(-radius..=radius);
+        let dy = (radius - dx.abs()) * if rng.gen_bool(0.5) { 1 } else { -1 };
+        sensors.push((sensor, sensor + Point { x: dx, y: dy }));
+    }
+    sensors.shuffle(rng);
+
+    let mut input = String::new();
+    for (s, b) in sensors {
+        writeln!(
+            input,
+            "Sensor at x={}, y={}: closest beacon is at x={}, y={}",
+            s.x, s.y, b.x, b.y
+        )
+        .unwrap();
+    }
+    Generated {
+        part2: Some((gap.x * 4000000 + gap.y).to_string()),
+        params: Params {
+            row: gap.y,
+            bound,
+            ..Params::default()
+        },
+        ..Generated::new(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{solution, Input};
+
+    fn solve(generated: &Generated, day: u8, part: Part) -> String {
+        let input = Input::new(&generated.input);
+        let solution = solution(day, &generated.params).unwrap();
+        solution.solve(part, &input).unwrap().to_string()
+    }
+
+    #[test]
+    fn known_answers_test() {
+        for day in [5, 7, 15] {
+            for seed in 0..20 {
+                let generated = generate(day, seed, 1 + seed as usize % 7).unwrap();
+                for part in Part::BOTH {
+                    if let Some(answer) = generated.answer(part) {
+                        assert_eq!(
+                            solve(&generated, day, part),
+                            answer,
+                            "day {day} part {part} with seed {seed}\n{}",
+                            generated.input
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn solvable_test() {
+        for day in GENERATED_DAYS {
+            for seed in 0..5 {
+                let generated = generate(day, seed, 8).unwrap();
+                for part in Part::BOTH {
+                    solve(&generated, day, part);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn seed_test() {
+        assert_eq!(generate(14, 7, 3).unwrap(), generate(14, 7, 3).unwrap());
+        assert_ne!(generate(14, 7, 3).unwrap(), generate(14, 8, 3).unwrap());
+        assert!(generate(1, 0, 3).is_err());
+    }
+
+    #[test]
+    fn manifest_entries_test() {
+        let generated = generate(15, 0, 1).unwrap();
+        let entries = generated.manifest_entries("day15_gen");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0],
+            format!(
+                "day15_gen 2 {} row={} bound=100",
+                generated.part2.as_ref().unwrap(),
+                generated.params.row
+            )
+        );
+    }
+}
@@ -1,10 +1,15 @@
 #![allow(dead_code)]
+use std::{collections::BTreeMap, num::NonZeroUsize, ops::RangeInclusive, str::FromStr};
+
+use eyre::{eyre, Result};
+
+pub mod answers;
 mod day1;
 mod day10;
 mod day11;
 mod day12;
 mod day13;
-pub mod day14;
+mod day14;
 mod day15;
 mod day2;
 mod day3;
@@ -14,3 +19,350 @@ mod day6;
 mod day7;
 mod day8;
 mod day9;
+pub mod generate;
+pub mod report;
+
+pub use aoc_common::{
+    animate, asciicast, bench, cache, grid, image, input, ocr, parse, point, range_set, scaffold,
+    search, submit, trace, Answer, Input, Part, PuzzleParams, Puzzles, Solution,
+};
+
+pub use day10::{asm, debugger, device};
+
+use animate::Animate;
+use device::{Display, Schedule};
+use image::Image;
+
+/// Puzzle parameters that the examples and real inputs disagree on. `None` means the puzzle's
+/// own value for the part being solved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Params {
+    /// Day 6 marker length, 4 for part 1 and 14 for part 2
+    pub marker_len: Option<usize>,
+    /// Day 9 rope length, 2 knots for part 1 and 10 for part 2
+    pub knots: Option<usize>,
+    /// Day 10 part 2 reads the CRT's letters, or gives the screen as drawn when false
+    pub ocr: bool,
+    /// Day 10 CRT that part 2 draws on
+    pub display: Display,
+    /// Day 10 cycles that part 1 samples the signal strength on
+    pub schedule: Schedule,
+    /// Day 14 floor, absent for part 1 and present for part 2
+    pub floor: Option<bool>,
+    /// Day 15 row for part 1
+    pub row: i64,
+    /// Day 15 coordinate bound for part 2
+    pub bound: i64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            marker_len: None,
+            knots: None,
+            ocr: true,
+            display: Display::DEVICE,
+            schedule: Schedule::DEVICE,
+            floor: None,
+            row: 2000000,
+            bound: 4000000,
+        }
+    }
+}
+
+impl Params {
+    /// Sets the parameter named as its command line flag, e.g. `marker-len` or `row`
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        fn parse<T: FromStr>(key: &str, value: &str) -> Result<T> {
+            value
+                .parse()
+                .map_err(|_| eyre!("invalid value {value:?} for parameter {key}"))
+        }
+
+        match key {
+            // Both count characters or knots, so there must be at least one
+            "marker-len" => self.marker_len = Some(parse::<NonZeroUsize>(key, value)?.get()),
+            "knots" => self.knots = Some(parse::<NonZeroUsize>(key, value)?.get()),
+            "ocr" => self.ocr = parse(key, value)?,
+            "crt-width" => self.display.width = parse(key, value)?,
+            "crt-height" => self.display.height = parse(key, value)?,
+            "sprite-width" => self.display.sprite_width = parse(key, value)?,
+            "samples" => self.schedule = parse(key, value)?,
+            "floor" => self.floor = Some(parse(key, value)?),
+            "row" => self.row = parse(key, value)?,
+            "bound" => self.bound = parse(key, value)?,
+            _ => return Err(eyre!("unknown parameter {key}")),
+        }
+        Ok(())
+    }
+
+    /// Parameters that differ from their defaults, as `key=value` in the form `set` takes
+    pub fn to_args(&self) -> Vec<String> {
+        let default = Params::default();
+        let mut args = vec![];
+        if let Some(marker_len) = self.marker_len {
+            args.push(format!("marker-len={marker_len}"));
+        }
+        if let Some(knots) = self.knots {
+            args.push(format!("knots={knots}"));
+        }
+        if self.ocr != default.ocr {
+            args.push(format!("ocr={}", self.ocr));
+        }
+        if self.display.width != default.display.width {
+            args.push(format!("crt-width={}", self.display.width));
+        }
+        if self.display.height != default.display.height {
+            args.push(format!("crt-height={}", self.display.height));
+        }
+        if self.display.sprite_width != default.display.sprite_width {
+            args.push(format!("sprite-width={}", self.display.sprite_width));
+        }
+        if self.schedule != default.schedule {
+            args.push(format!("samples={}", self.schedule));
+        }
+        if let Some(floor) = self.floor {
+            args.push(format!("floor={floor}"));
+        }
+        if self.row != default.row {
+            args.push(format!("row={}", self.row));
+        }
+        if self.bound != default.bound {
+            args.push(format!("bound={}", self.bound));
+        }
+        args
+    }
+}
+
+impl PuzzleParams for Params {
+    fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        Params::set(self, key, value)
+    }
+}
+
+/// Year of the puzzles solved here
+pub const YEAR: u16 = 2022;
+
+/// Days that have a solution
+pub const DAYS: RangeInclusive<u8> = 1..=15;
+
+/// This year's solutions, as the runner finds them
+pub const PUZZLES: Puzzles<Params> = Puzzles {
+    year: YEAR,
+    days: DAYS,
+    solution,
+};
+
+/// Returns the solution for the given day, if there is one
+pub fn solution(day: u8, params: &Params) -> Option<Box<dyn Solution>> {
+    let solution: Box<dyn Solution> = match day {
+        1 => Box::new(day1::Day1),
+        2 => Box::new(day2::Day2),
+        3 => Box::new(day3::Day3),
+        4 => Box::new(day4::Day4),
+        5 => Box::new(day5::Day5),
+        6 => Box::new(day6::Day6 {
+            marker_len: params.marker_len,
+        }),
+        7 => Box::new(day7::Day7),
+        8 => Box::new(day8::Day8),
+        9 => Box::new(day9::Day9 {
+            knots: params.knots,
+        }),
+        10 => Box::new(day10::Day10 {
+            ocr: params.ocr,
+            display: params.display,
+            schedule: params.schedule.clone(),
+        }),
+        11 => Box::new(day11::Day11),
+        12 => Box::new(day12::Day12),
+        13 => Box::new(day13::Day13),
+        14 => Box::new(day14::Day14 {
+            floor: params.floor,
+        }),
+        15 => Box::new(day15::Day15 {
+            row: params.row,
+            bound: params.bound,
+        }),
+        _ => return None,
+    };
+
+    Some(solution)
+}
+
+/// Days that have an animation
+pub const ANIMATED_DAYS: [u8; 3] = [5, 9, 14];
+
+/// Returns the day's simulation of the part for the input, for days in [`ANIMATED_DAYS`]
+pub fn animation(day: u8, part: Part, params: &Params, input: &Input) -> Result<Box<dyn Animate>> {
+    let animation: Box<dyn Animate> = match day {
+        5 => day5::animation(input, part)?,
+        9 => {
+            let knots = params.knots.unwrap_or(match part {
+                Part::One => 2,
+                Part::Two => 10,
+            });
+            Box::new(day9::Rope::new(input, knots)?)
+        }
+        14 => {
+            let floor = params.floor.unwrap_or(part == Part::Two);
+            Box::new(day14::Cave::from_rock_paths(input, floor)?)
+        }
+        _ => return Err(eyre!("day {day} has no animation, try day 5, 9 or 14")),
+    };
+    Ok(animation)
+}
+
+/// Days that can draw an image
+pub const IMAGE_DAYS: [u8; 4] = [8, 10, 12, 14];
+
+/// Returns the day's picture of the puzzle, with a pixel per cell, for days in [`IMAGE_DAYS`]
+pub fn image(day: u8, part: Part, params: &Params, input: &Input) -> Result<Image> {
+    match day {
+        8 => day8::image(input),
+        10 => day10::image(input, &params.display),
+        12 => day12::image(input),
+        14 => day14::image(input, params.floor.unwrap_or(part == Part::Two)),
+        _ => Err(eyre!("day {day} has no image, try day 8, 10, 12 or 14")),
+    }
+}
+
+/// All solutions, keyed by day number
+pub fn registry(params: &Params) -> BTreeMap<u8, Box<dyn Solution>> {
+    PUZZLES.registry(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_test() {
+        let registry = registry(&Params::default());
+        assert_eq!(registry.len(), DAYS.count());
+        assert_eq!(
+            registry.keys().copied().collect::<Vec<_>>(),
+            DAYS.collect::<Vec<_>>()
+        );
+        assert!(solution(26, &Params::default()).is_none());
+    }
+
+    #[test]
+    fn solution_test() {
+        let input = Input::from_path("./testdata/day1").unwrap();
+        let day1 = solution(1, &Params::default()).unwrap();
+        assert_eq!(day1.part1(&input).unwrap().to_string(), "69836");
+        assert_eq!(day1.part2(&input).unwrap().to_string(), "207968");
+    }
+
+    #[test]
+    fn bench_test() {
+        let input = Input::from_path("./testdata/day1").unwrap();
+        let day1 = solution(1, &Params::default()).unwrap();
+        let timing = bench::bench(1, day1.as_ref(), Part::Two, &input, 3).unwrap();
+        assert_eq!((timing.day, timing.part, timing.runs), (1, Part::Two, 3));
+        assert!(timing.total.min <= timing.total.median && timing.total.median <= timing.total.max);
+        assert!(timing.solve.min <= timing.total.min && timing.solve.max <= timing.total.max);
+        assert!(bench::bench(1, day1.as_ref(), Part::Two, &input, 0).is_err());
+    }
+
+    #[test]
+    fn solution_params_test() {
+        let input = Input::from_path("./testdata/day15_simple").unwrap();
+        let params = Params {
+            row: 10,
+            bound: 20,
+            ..Params::default()
+        };
+        let day15 = solution(15, &params).unwrap();
+        assert_eq!(day15.solve(Part::One, &input).unwrap().to_string(), "26");
+        assert_eq!(
+            day15.solve(Part::Two, &input).unwrap().to_string(),
+            "56000011"
+        );
+    }
+
+    #[test]
+    fn solution_day10_params_test() {
+        let input = Input::from_path("./testdata/day10_less_simple").unwrap();
+        let params = Params {
+            ocr: false,
+            display: Display::new(5, 2, 3).unwrap(),
+            schedule: Schedule::Cycles(vec![1, 2]),
+            ..Params::default()
+        };
+        let day10 = solution(10, &params).unwrap();
+        // X is 1 for the first cycles, which start the program with `addx 15`
+        assert_eq!(day10.solve(Part::One, &input).unwrap().to_string(), "3");
+        assert_eq!(
+            day10.solve(Part::Two, &input).unwrap().to_string(),
+            "##..#\n.....\n"
+        );
+    }
+
+    #[test]
+    fn params_set_test() {
+        let mut params = Params::default();
+        params.set("marker-len", "4").unwrap();
+        params.set("floor", "true").unwrap();
+        params.set("ocr", "false").unwrap();
+        params.set("row", "10").unwrap();
+        assert_eq!(
+            params,
+            Params {
+                marker_len: Some(4),
+                ocr: false,
+                floor: Some(true),
+                row: 10,
+                ..Params::default()
+            }
+        );
+        assert_eq!(
+            params.to_args(),
+            ["marker-len=4", "ocr=false", "floor=true", "row=10"]
+        );
+        let mut params = Params::default();
+        params.set("crt-width", "5").unwrap();
+        params.set("samples", "1,4,9").unwrap();
+        assert_eq!(params.display, Display::new(5, 6, 3).unwrap());
+        assert_eq!(params.schedule, Schedule::Cycles(vec![1, 4, 9]));
+        assert_eq!(params.to_args(), ["crt-width=5", "samples=1,4,9"]);
+        assert!(params.set("crt-height", "0").is_err());
+        assert!(params.set("samples", "20+0").is_err());
+        assert!(params.set("knots", "many").is_err());
+        assert!(params.set("knots", "0").is_err());
+        assert!(params.set("marker-len", "0").is_err());
+        assert!(params.set("y", "10").is_err());
+    }
+
+    #[test]
+    fn animation_test() {
+        let input = Input::from_path("./testdata/day14").unwrap();
+        let params = Params::default();
+        for part in Part::BOTH {
+            assert!(!animation(14, part, &params, &input).unwrap().finished());
+        }
+        assert!(animation(1, Part::One, &params, &input).is_err());
+    }
+
+    #[test]
+    fn image_test() {
+        let input = Input::from_path("./testdata/day10").unwrap();
+        let crt = image(10, Part::Two, &Params::default(), &input).unwrap();
+        assert_eq!((crt.width(), crt.height()), (40, 6));
+
+        let input = Input::from_path("./testdata/day14").unwrap();
+        let params = Params::default();
+        let part1 = image(14, Part::One, &params, &input).unwrap();
+        let part2 = image(14, Part::Two, &params, &input).unwrap();
+        assert!(part2.width() > part1.width());
+        assert!(image(1, Part::One, &params, &input).is_err());
+    }
+
+    #[test]
+    fn part_from_str_test() {
+        assert_eq!("1".parse::<Part>().unwrap(), Part::One);
+        assert_eq!("2".parse::<Part>().unwrap(), Part::Two);
+        assert!("3".parse::<Part>().is_err());
+    }
+}
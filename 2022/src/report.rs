@@ -0,0 +1,50 @@
+//! Solving every day at once, for a picture of which parts work and how long they take
+
+use eyre::Result;
+
+pub use aoc_common::report::{write_json, write_table, Outcome, Report};
+
+use crate::{Input, Params, Part, PUZZLES};
+
+/// Solves the parts of this year's days, each on its own thread, with each day's input read
+/// once by `read_input`. A part that panics is reported as an error.
+pub fn run_all(
+    days: &[u8],
+    parts: &[Part],
+    read_input: impl Fn(u8) -> Result<Input>,
+    params: &Params,
+) -> Report {
+    aoc_common::report::run_all(&PUZZLES, days, parts, read_input, params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_all_test() {
+        let report = run_all(
+            &[1, 10, 26],
+            &Part::BOTH,
+            |day| Input::from_path(format!("./testdata/day{day}")),
+            &Params::default(),
+        );
+        let parts = report
+            .outcomes
+            .iter()
+            .map(|o| (o.day, o.part))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parts,
+            [1, 10, 26]
+                .into_iter()
+                .flat_map(|day| Part::BOTH.map(|part| (day, part)))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(report.outcomes[0].answer.as_ref().unwrap(), "69836");
+        assert_eq!(report.outcomes[1].answer.as_ref().unwrap(), "207968");
+        assert_eq!(report.outcomes[3].answer.as_ref().unwrap(), "FECZELHE");
+        assert!(report.outcomes[4].answer.is_err());
+        assert_eq!(report.failures(), 2);
+    }
+}
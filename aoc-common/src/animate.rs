@@ -0,0 +1,463 @@
+//! Step-by-step simulations drawn as characters, and a terminal player for them
+
+use std::{
+    fmt,
+    io::{self, Write},
+    thread,
+    time::Duration,
+};
+
+use eyre::Result;
+use termion::{
+    clear, cursor, event::Key, input::TermRead, raw::IntoRawMode, screen::IntoAlternateScreen,
+};
+
+use crate::grid::{Grid, Pos};
+
+/// A simulation that advances a step at a time and can draw its state
+pub trait Animate {
+    /// Advances the simulation by one step. Does nothing once it has finished.
+    fn step(&mut self);
+
+    /// Draws the current state, resizing the frame to fit it
+    fn render(&self, frame: &mut Frame);
+
+    fn finished(&self) -> bool;
+
+    /// Position in the frame to keep in view when the frame is bigger than the screen
+    fn focus(&self) -> Option<Pos> {
+        None
+    }
+}
+
+/// A rectangle of characters that an animation draws its state on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    cells: Grid<char>,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize) -> Frame {
+        Frame {
+            cells: Grid::new(width, height, ' '),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.height()
+    }
+
+    /// Blanks the frame and changes its size
+    pub fn resize(&mut self, width: usize, height: usize) {
+        if (width, height) == (self.width(), self.height()) {
+            for pos in self.cells.positions() {
+                self.cells[pos] = ' ';
+            }
+        } else {
+            *self = Frame::new(width, height);
+        }
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<char> {
+        self.cells.get(pos).copied()
+    }
+
+    /// Sets the character at `pos`, ignoring positions outside the frame
+    pub fn set(&mut self, pos: Pos, c: char) {
+        if let Some(cell) = self.cells.get_mut(pos) {
+            *cell = c;
+        }
+    }
+
+    /// Writes the text rightwards from `pos`, cutting it off at the frame's edge
+    pub fn text(&mut self, (x, y): Pos, text: &str) {
+        for (i, c) in text.chars().enumerate() {
+            self.set((x + i, y), c);
+        }
+    }
+
+    /// The part of the frame `width` by `height` from `origin`, padded with blanks where it
+    /// extends past the frame
+    pub fn crop(&self, (left, top): Pos, width: usize, height: usize) -> Frame {
+        let mut cropped = Frame::new(width, height);
+        for (x, y) in cropped.cells.positions() {
+            if let Some(c) = self.get((left + x, top + y)) {
+                cropped.cells[(x, y)] = c;
+            }
+        }
+        cropped
+    }
+
+    /// Runs of characters that differ from `previous`, each with the position of its first
+    /// character. Frames of different sizes differ everywhere.
+    pub fn changes(&self, previous: &Frame) -> Vec<(Pos, String)> {
+        let same_size = (self.width(), self.height()) == (previous.width(), previous.height());
+        let mut runs: Vec<(Pos, String)> = vec![];
+        for (pos, &c) in self.cells.iter() {
+            if same_size && previous.cells[pos] == c {
+                continue;
+            }
+            match runs.last_mut() {
+                Some(((x, y), run)) if *y == pos.1 && *x + run.chars().count() == pos.0 => {
+                    run.push(c)
+                }
+                _ => runs.push((pos, c.to_string())),
+            }
+        }
+        runs
+    }
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Frame::new(0, 0)
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.cells)
+    }
+}
+
+const MIN_DELAY: Duration = Duration::from_millis(1);
+const MAX_DELAY: Duration = Duration::from_secs(2);
+const MAX_STEPS_PER_FRAME: usize = 1 << 16;
+/// How often keys are checked for while paused or finished
+const IDLE_DELAY: Duration = Duration::from_millis(50);
+
+/// Playback state, changed by the keys the player reads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Controls {
+    pub paused: bool,
+    /// Time between frames
+    pub delay: Duration,
+    /// Steps the animation takes between frames, so long simulations can be skipped through
+    pub steps_per_frame: usize,
+    /// Whether to advance by one frame although paused
+    single_step: bool,
+    pub quit: bool,
+}
+
+impl Controls {
+    pub const HELP: &'static str =
+        "space pause  . step  +/- speed  ]/[ skip more/fewer frames  q quit";
+
+    pub fn new(delay: Duration, steps_per_frame: usize) -> Controls {
+        Controls {
+            paused: false,
+            delay: delay.clamp(MIN_DELAY, MAX_DELAY),
+            steps_per_frame: steps_per_frame.clamp(1, MAX_STEPS_PER_FRAME),
+            single_step: false,
+            quit: false,
+        }
+    }
+
+    pub fn handle(&mut self, key: Key) {
+        match key {
+            Key::Char(' ') => self.paused = !self.paused,
+            Key::Char('.') | Key::Right => {
+                self.paused = true;
+                self.single_step = true;
+            }
+            Key::Char('+') | Key::Char('=') => self.delay = (self.delay / 2).max(MIN_DELAY),
+            Key::Char('-') => self.delay = (self.delay * 2).min(MAX_DELAY),
+            Key::Char(']') => {
+                self.steps_per_frame = (self.steps_per_frame * 2).min(MAX_STEPS_PER_FRAME)
+            }
+            Key::Char('[') => self.steps_per_frame = (self.steps_per_frame / 2).max(1),
+            Key::Char('q') | Key::Esc | Key::Ctrl('c') => self.quit = true,
+            _ => {}
+        }
+    }
+
+    /// Steps to take before drawing the next frame
+    fn steps_due(&mut self) -> usize {
+        if self.single_step {
+            self.single_step = false;
+            self.steps_per_frame
+        } else if self.paused {
+            0
+        } else {
+            self.steps_per_frame
+        }
+    }
+
+    fn status(&self, steps: usize, finished: bool) -> String {
+        let state = if finished {
+            "finished"
+        } else if self.paused {
+            "paused"
+        } else {
+            "playing"
+        };
+        format!(
+            "step {steps}  {state}  {}ms/frame  {} steps/frame  |  {}",
+            self.delay.as_millis(),
+            self.steps_per_frame,
+            Controls::HELP
+        )
+    }
+}
+
+/// Somewhere to show an animation's frames, like a terminal or a recording
+pub trait FrameSink {
+    /// Shows the next frame with a status line under it. `focus` is a position in the frame to
+    /// keep in view if the whole frame does not fit.
+    fn show(&mut self, frame: &Frame, focus: Option<Pos>, status: &str) -> io::Result<()>;
+}
+
+/// Draws frames on a terminal, rewriting only the characters that changed since the last one
+pub struct Screen<W: Write> {
+    out: W,
+    /// Terminal width and height
+    size: (usize, usize),
+    /// Position in the frame of the top left character on the terminal
+    origin: Pos,
+    /// What the terminal shows above the status line
+    shown: Frame,
+}
+
+impl<W: Write> Screen<W> {
+    pub fn new(out: W, size: (usize, usize)) -> Screen<W> {
+        Screen {
+            out,
+            size,
+            origin: (0, 0),
+            shown: Frame::default(),
+        }
+    }
+
+    pub fn resize(&mut self, size: (usize, usize)) {
+        self.size = size;
+    }
+
+    /// The terminal output, for taking what has been drawn when it is a buffer
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.out
+    }
+
+    /// Moves the view the least it can to bring the focus into it, and keeps it within the frame
+    fn scroll(&mut self, frame: &Frame, focus: Option<Pos>, (width, height): (usize, usize)) {
+        let fit = |origin: usize, view: usize, size: usize, focus: Option<usize>| {
+            let origin = match focus {
+                Some(f) if f < origin => f,
+                Some(f) if f >= origin + view => f + 1 - view,
+                _ => origin,
+            };
+            origin.min(size.saturating_sub(view))
+        };
+        self.origin = (
+            fit(self.origin.0, width, frame.width(), focus.map(|f| f.0)),
+            fit(self.origin.1, height, frame.height(), focus.map(|f| f.1)),
+        );
+    }
+}
+
+impl<W: Write> FrameSink for Screen<W> {
+    /// Shows the part of the frame that fits on the terminal
+    fn show(&mut self, frame: &Frame, focus: Option<Pos>, status: &str) -> io::Result<()> {
+        let (width, height) = (self.size.0, self.size.1.saturating_sub(1));
+        self.scroll(frame, focus, (width, height));
+        let view = frame.crop(
+            self.origin,
+            width.min(frame.width()),
+            height.min(frame.height()),
+        );
+
+        if (view.width(), view.height()) != (self.shown.width(), self.shown.height()) {
+            write!(self.out, "{}", clear::All)?;
+        }
+        for ((x, y), run) in view.changes(&self.shown) {
+            write!(self.out, "{}{run}", goto((x, y)))?;
+        }
+        let status = status.chars().take(width).collect::<String>();
+        write!(
+            self.out,
+            "{}{}{status}",
+            goto((0, view.height())),
+            clear::CurrentLine
+        )?;
+        self.shown = view;
+        self.out.flush()
+    }
+}
+
+/// Cursor movement to a position counting from 0, where the terminal counts from 1
+fn goto((x, y): Pos) -> cursor::Goto {
+    cursor::Goto(x as u16 + 1, y as u16 + 1)
+}
+
+fn terminal_size() -> io::Result<(usize, usize)> {
+    let (width, height) = termion::terminal_size()?;
+    Ok((width as usize, height as usize))
+}
+
+/// Plays the animation on the terminal until it is quit with one of the keys in
+/// [`Controls::HELP`]. The animation is left on screen once it finishes.
+pub fn play(animation: &mut dyn Animate, mut controls: Controls) -> Result<()> {
+    let out = io::stdout().into_raw_mode()?.into_alternate_screen()?;
+    let mut screen = Screen::new(cursor::HideCursor::from(out), terminal_size()?);
+    // Keys are read from the terminal, so the puzzle input can be piped in
+    let mut keys = termion::async_stdin().keys();
+    let mut frame = Frame::default();
+    let mut steps = 0;
+
+    loop {
+        for key in keys.by_ref() {
+            controls.handle(key?);
+        }
+        if controls.quit {
+            return Ok(());
+        }
+
+        for _ in 0..controls.steps_due() {
+            if animation.finished() {
+                break;
+            }
+            animation.step();
+            steps += 1;
+        }
+
+        animation.render(&mut frame);
+        screen.resize(terminal_size()?);
+        let finished = animation.finished();
+        screen.show(&frame, animation.focus(), &controls.status(steps, finished))?;
+
+        thread::sleep(if controls.paused || finished {
+            IDLE_DELAY
+        } else {
+            controls.delay
+        });
+    }
+}
+
+/// Shows every frame of the animation, with `steps_per_frame` steps between them, until it
+/// finishes. Returns the number of frames shown.
+pub fn record(
+    animation: &mut dyn Animate,
+    sink: &mut dyn FrameSink,
+    steps_per_frame: usize,
+) -> io::Result<usize> {
+    let mut frame = Frame::default();
+    let (mut steps, mut frames) = (0, 0);
+    loop {
+        animation.render(&mut frame);
+        sink.show(&frame, animation.focus(), &format!("step {steps}"))?;
+        frames += 1;
+        if animation.finished() {
+            return Ok(frames);
+        }
+
+        for _ in 0..steps_per_frame.max(1) {
+            if animation.finished() {
+                break;
+            }
+            animation.step();
+            steps += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(rows: &[&str]) -> Frame {
+        let mut frame = Frame::new(rows[0].len(), rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            frame.text((0, y), row);
+        }
+        frame
+    }
+
+    #[test]
+    fn frame_test() {
+        let mut f = Frame::new(4, 2);
+        f.text((1, 0), "abcdef");
+        f.set((3, 1), 'x');
+        f.set((9, 9), 'y');
+        assert_eq!(f.to_string(), " abc\n   x\n");
+        assert_eq!(f.crop((2, 1), 3, 2).to_string(), " x \n   \n");
+
+        f.resize(4, 2);
+        assert_eq!(f.to_string(), "    \n    \n");
+        f.resize(1, 1);
+        assert_eq!((f.width(), f.height()), (1, 1));
+    }
+
+    #[test]
+    fn changes_test() {
+        let before = frame(&["....", "...."]);
+        let after = frame(&[".ab.", "c..d"]);
+        assert_eq!(
+            after.changes(&before),
+            vec![
+                ((1, 0), "ab".to_string()),
+                ((0, 1), "c".to_string()),
+                ((3, 1), "d".to_string())
+            ]
+        );
+        assert!(after.changes(&after).is_empty());
+        assert_eq!(
+            after.changes(&Frame::default()),
+            vec![((0, 0), ".ab.".to_string()), ((0, 1), "c..d".to_string())]
+        );
+    }
+
+    #[test]
+    fn controls_test() {
+        let mut controls = Controls::new(Duration::from_millis(40), 1);
+        assert_eq!(controls.steps_due(), 1);
+
+        controls.handle(Key::Char(' '));
+        assert_eq!(controls.steps_due(), 0);
+        controls.handle(Key::Char('.'));
+        assert_eq!(controls.steps_due(), 1);
+        assert_eq!(controls.steps_due(), 0);
+
+        controls.handle(Key::Char('+'));
+        assert_eq!(controls.delay, Duration::from_millis(20));
+        controls.handle(Key::Char('-'));
+        controls.handle(Key::Char('-'));
+        assert_eq!(controls.delay, Duration::from_millis(80));
+
+        controls.handle(Key::Char(']'));
+        controls.handle(Key::Char(']'));
+        controls.handle(Key::Char(' '));
+        assert_eq!(controls.steps_due(), 4);
+        controls.handle(Key::Char('['));
+        assert_eq!(controls.steps_per_frame, 2);
+
+        assert!(!controls.quit);
+        controls.handle(Key::Char('q'));
+        assert!(controls.quit);
+    }
+
+    #[test]
+    fn screen_draw_test() {
+        let mut screen = Screen::new(vec![], (3, 3));
+        screen.show(&frame(&["abcd", "efgh"]), None, "s").unwrap();
+        let first = String::from_utf8(std::mem::take(&mut screen.out)).unwrap();
+        assert!(first.starts_with(&clear::All.to_string()));
+        assert!(first.contains(&format!("{}abc", goto((0, 0)))));
+        assert!(first.contains(&format!("{}efg", goto((0, 1)))));
+
+        // Only the changed character is rewritten
+        screen.show(&frame(&["abcd", "eXgh"]), None, "s").unwrap();
+        let second = String::from_utf8(std::mem::take(&mut screen.out)).unwrap();
+        assert!(!second.contains(&clear::All.to_string()));
+        assert!(second.starts_with(&format!("{}X", goto((1, 1)))));
+
+        // Focusing on a column out of view scrolls right
+        screen
+            .show(&frame(&["abcd", "eXgh"]), Some((3, 0)), "s")
+            .unwrap();
+        assert_eq!(screen.origin, (1, 0));
+        assert_eq!(screen.shown.to_string(), "bcd\nXgh\n");
+    }
+}
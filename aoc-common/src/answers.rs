@@ -0,0 +1,158 @@
+use std::path::Path;
+
+use eyre::{eyre, Result};
+
+use crate::{parse::ParseError, Input, Part, PuzzleParams, Puzzles};
+
+/// The expected answer to one part of a puzzle for one input file, read from the answers manifest
+///
+/// Each entry in the manifest is a line of the form `<file> <part> <answer> [<key>=<value>]...`,
+/// where the day is taken from the file name, e.g. `day15_simple 1 26 row=10`. An answer of `|`
+/// is followed by the lines of a multi-line answer, each indented by two spaces. Blank lines
+/// and lines starting with `#` are ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expected<P> {
+    /// Input file, relative to the inputs directory
+    pub file: String,
+    pub day: u8,
+    pub part: Part,
+    pub answer: String,
+    pub params: P,
+}
+
+/// Day number that a file name such as `day15_simple` starts with
+fn day_of(file: &str) -> Option<u8> {
+    let digits = file.strip_prefix("day")?;
+    let end = digits
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(digits.len());
+    digits[..end].parse().ok()
+}
+
+fn parse_entry<P: PuzzleParams>(line: &str) -> Result<Expected<P>, ParseError> {
+    let mut tokens = line.split_whitespace();
+    let mut next = |expected| {
+        tokens
+            .next()
+            .ok_or_else(|| ParseError::at(line, &line[line.len()..], expected))
+    };
+
+    let file = next("input file")?;
+    let day = day_of(file).ok_or_else(|| ParseError::at(line, file, "file named day<N>"))?;
+    let part = next("part")?;
+    let part = part
+        .parse()
+        .map_err(|_| ParseError::at(line, part, "part 1 or 2"))?;
+    let answer = next("answer")?.to_string();
+
+    let mut params = P::default();
+    for param in tokens {
+        let (key, value) = param
+            .split_once('=')
+            .ok_or_else(|| ParseError::at(line, param, "parameter as <key>=<value>"))?;
+        params.set(key, value).map_err(|e| {
+            ParseError::at(line, param, format!("parameter as <key>=<value> ({e})"))
+        })?;
+    }
+
+    Ok(Expected {
+        file: file.to_string(),
+        day,
+        part,
+        answer,
+        params,
+    })
+}
+
+/// Parses the answers manifest, with parameters of the type the year's puzzles take
+pub fn parse_manifest<P: PuzzleParams>(input: &Input) -> Result<Vec<Expected<P>>, ParseError> {
+    let mut lines = input.lines().enumerate().peekable();
+    let mut entries = vec![];
+    while let Some((idx, line)) = lines.next() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut entry = parse_entry(line).map_err(|e| e.offset_lines(idx))?;
+        if entry.answer == "|" {
+            let block = std::iter::from_fn(|| lines.next_if(|(_, l)| l.starts_with("  ")))
+                .map(|(_, l)| &l[2..])
+                .collect::<Vec<_>>();
+            if block.is_empty() {
+                return Err(ParseError::new(idx + 2, 1, "indented answer line", ""));
+            }
+            entry.answer = block.join("\n");
+        }
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+/// Solves the expected entry's part of its input file, returning the answer without any
+/// trailing newlines
+pub fn solve<P>(puzzles: &Puzzles<P>, expected: &Expected<P>, inputs: &Path) -> Result<String> {
+    let solution = puzzles
+        .solution(expected.day, &expected.params)
+        .ok_or_else(|| eyre!("no solution for day {}", expected.day))?;
+    let input = Input::from_path(inputs.join(&expected.file))?;
+    let answer = solution.solve(expected.part, &input)?.to_string();
+    Ok(answer.trim_end_matches('\n').to_string())
+}
+
+/// An expected answer and the answer that was actually given
+#[derive(Debug)]
+pub struct Check<'a, P> {
+    pub expected: &'a Expected<P>,
+    pub actual: Result<String>,
+}
+
+impl<P> Check<'_, P> {
+    pub fn passed(&self) -> bool {
+        matches!(&self.actual, Ok(answer) if *answer == self.expected.answer)
+    }
+}
+
+/// Solves every entry, with input files read from the `inputs` directory
+pub fn verify<'a, P>(
+    puzzles: &Puzzles<P>,
+    entries: &'a [Expected<P>],
+    inputs: &Path,
+) -> Vec<Check<'a, P>> {
+    entries
+        .iter()
+        .map(|expected| Check {
+            expected,
+            actual: solve(puzzles, expected, inputs),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NoParams;
+
+    #[test]
+    fn day_of_test() {
+        assert_eq!(day_of("day1"), Some(1));
+        assert_eq!(day_of("day15_simple"), Some(15));
+        assert_eq!(day_of("input15"), None);
+        assert_eq!(day_of("day_simple"), None);
+    }
+
+    #[test]
+    fn no_params_test() {
+        let entries = parse_manifest::<NoParams>(&Input::from("day2 2 |\n  ab\n  c")).unwrap();
+        assert_eq!((entries[0].day, entries[0].answer.as_str()), (2, "ab\nc"));
+        assert_eq!(
+            parse_manifest::<NoParams>(&Input::from("day15 1 26 row=10")).unwrap_err(),
+            ParseError::new(
+                1,
+                12,
+                "parameter as <key>=<value> (unknown parameter row)",
+                "row=10"
+            )
+        );
+    }
+}
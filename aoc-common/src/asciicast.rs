@@ -0,0 +1,125 @@
+//! Recordings in asciinema's asciicast v2 format, which `asciinema play` and its web player can
+//! replay without the code that produced them
+
+use std::{
+    io::{self, Write},
+    mem,
+    time::Duration,
+};
+
+use serde_json::json;
+use termion::cursor;
+
+use crate::{
+    animate::{Frame, FrameSink, Screen},
+    grid::Pos,
+};
+
+/// Writes each frame it is shown as an output event a fixed delay after the previous one
+pub struct Recorder<W: Write> {
+    out: W,
+    /// Draws the frames into a buffer, so a recording holds only what changed between frames
+    screen: Screen<Vec<u8>>,
+    delay: Duration,
+    frames: u32,
+}
+
+impl<W: Write> Recorder<W> {
+    /// Starts a recording of a terminal `size` columns by rows, writing its header
+    pub fn new(
+        mut out: W,
+        size: (usize, usize),
+        delay: Duration,
+        title: &str,
+    ) -> io::Result<Recorder<W>> {
+        let header = json!({
+            "version": 2,
+            "width": size.0,
+            "height": size.1,
+            "title": title,
+        });
+        writeln!(out, "{header}")?;
+
+        let mut screen = Screen::new(vec![], size);
+        write!(screen.get_mut(), "{}", cursor::Hide)?;
+        Ok(Recorder {
+            out,
+            screen,
+            delay,
+            frames: 0,
+        })
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl<W: Write> FrameSink for Recorder<W> {
+    fn show(&mut self, frame: &Frame, focus: Option<Pos>, status: &str) -> io::Result<()> {
+        self.screen.show(frame, focus, status)?;
+        let output = String::from_utf8(mem::take(self.screen.get_mut()))
+            .expect("frames are drawn from chars");
+        // Counting in microseconds keeps times like 0.15 from becoming 0.15000000000000002
+        let time = (self.delay.as_micros() * self.frames as u128) as f64 / 1e6;
+        writeln!(self.out, "{}", json!([time, "o", output]))?;
+        self.frames += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+    use crate::animate::{record, Animate};
+
+    /// Counts up to three, a digit per step
+    struct Counter(usize);
+
+    impl Animate for Counter {
+        fn step(&mut self) {
+            self.0 += 1;
+        }
+
+        fn render(&self, frame: &mut Frame) {
+            frame.resize(3, 1);
+            frame.text((0, 0), &"123"[..self.0]);
+        }
+
+        fn finished(&self) -> bool {
+            self.0 == 3
+        }
+    }
+
+    #[test]
+    fn record_test() {
+        let mut recorder =
+            Recorder::new(vec![], (10, 2), Duration::from_millis(50), "count").unwrap();
+        let frames = record(&mut Counter(0), &mut recorder, 1).unwrap();
+        assert_eq!(frames, 4);
+
+        let cast = String::from_utf8(recorder.into_inner()).unwrap();
+        let lines = cast
+            .lines()
+            .map(|l| serde_json::from_str::<Value>(l).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines[0],
+            json!({"version": 2, "width": 10, "height": 2, "title": "count"})
+        );
+        assert_eq!(lines.len(), 5);
+
+        let times = lines[1..].iter().map(|e| e[0].as_f64().unwrap());
+        assert_eq!(times.collect::<Vec<_>>(), vec![0.0, 0.05, 0.1, 0.15]);
+        assert!(lines[1..].iter().all(|e| e[1] == "o"));
+
+        // Each frame after the first writes only the digit it added
+        let first = lines[1][2].as_str().unwrap();
+        assert!(first.starts_with(&cursor::Hide.to_string()));
+        let last = lines[4][2].as_str().unwrap();
+        assert!(last.starts_with(&format!("{}3", cursor::Goto(3, 1))));
+        assert!(last.ends_with("step 3"));
+    }
+}
@@ -0,0 +1,236 @@
+use std::{
+    fmt,
+    hint::black_box,
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+use eyre::{eyre, Result};
+
+use crate::{Input, Part, Solution};
+
+/// Fastest, median and slowest of a set of timed runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    /// Returns `None` if there are no samples
+    pub fn from_samples(samples: &[Duration]) -> Option<Stats> {
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        Some(Stats {
+            min: *sorted.first()?,
+            median: sorted[sorted.len() / 2],
+            max: *sorted.last()?,
+        })
+    }
+}
+
+/// Timings for one part of one day
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timing {
+    pub day: u8,
+    pub part: Part,
+    pub runs: usize,
+    /// Time the solution's `parse` takes on its own
+    pub parse: Stats,
+    /// Time solving takes beyond parsing, the total less the parse time of the same run
+    pub solve: Stats,
+    /// Time to solve the part from the input, which parses it again
+    pub total: Stats,
+}
+
+/// Parses and solves `part` of the input `runs` times, timing each run
+pub fn bench(
+    day: u8,
+    solution: &dyn Solution,
+    part: Part,
+    input: &Input,
+    runs: usize,
+) -> Result<Timing> {
+    let mut parse = Vec::with_capacity(runs);
+    let mut solve = Vec::with_capacity(runs);
+    let mut total = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let start = Instant::now();
+        solution.parse(black_box(input))?;
+        let parsed = start.elapsed();
+
+        let start = Instant::now();
+        black_box(solution.solve(part, black_box(input))?);
+        let solved = start.elapsed();
+
+        parse.push(parsed);
+        solve.push(solved.saturating_sub(parsed));
+        total.push(solved);
+    }
+
+    let stats = |samples: &[Duration]| {
+        Stats::from_samples(samples).ok_or_else(|| eyre!("benchmark needs at least one run"))
+    };
+    Ok(Timing {
+        day,
+        part,
+        runs,
+        parse: stats(&parse)?,
+        solve: stats(&solve)?,
+        total: stats(&total)?,
+    })
+}
+
+/// Duration formatted with a unit suited to its size, e.g. `1.25ms`
+pub(crate) struct Human(pub Duration);
+
+impl fmt::Display for Human {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let nanos = self.0.as_nanos();
+        let text = match nanos {
+            0..=999 => format!("{nanos}ns"),
+            1_000..=999_999 => format!("{:.2}µs", nanos as f64 / 1e3),
+            1_000_000..=999_999_999 => format!("{:.2}ms", nanos as f64 / 1e6),
+            _ => format!("{:.2}s", nanos as f64 / 1e9),
+        };
+        f.pad(&text)
+    }
+}
+
+/// Writes the timings as an aligned table for reading
+pub fn write_table(mut w: impl Write, timings: &[Timing]) -> io::Result<()> {
+    writeln!(
+        w,
+        "{:>3} {:>4} {:>5}  {:>29}  {:>29}  {:>29}",
+        "day", "part", "runs", "parse min/med/max", "solve min/med/max", "total min/med/max"
+    )?;
+    for t in timings {
+        write!(w, "{:>3} {:>4} {:>5}", t.day, t.part, t.runs)?;
+        for stats in [t.parse, t.solve, t.total] {
+            write!(
+                w,
+                "  {:>9}/{:>9}/{:>9}",
+                Human(stats.min),
+                Human(stats.median),
+                Human(stats.max)
+            )?;
+        }
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
+/// Writes the timings as tab separated values in nanoseconds, with a header row, for comparing
+/// runs with other tools
+pub fn write_tsv(mut w: impl Write, timings: &[Timing]) -> io::Result<()> {
+    let header = ["parse", "solve", "total"]
+        .iter()
+        .flat_map(|s| ["min", "median", "max"].map(|m| format!("{s}_{m}_ns")))
+        .collect::<Vec<_>>()
+        .join("\t");
+    writeln!(w, "day\tpart\truns\t{header}")?;
+    for t in timings {
+        write!(w, "{}\t{}\t{}", t.day, t.part, t.runs)?;
+        for stats in [t.parse, t.solve, t.total] {
+            for d in [stats.min, stats.median, stats.max] {
+                write!(w, "\t{}", d.as_nanos())?;
+            }
+        }
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn stats_from_samples_test() {
+        assert_eq!(
+            Stats::from_samples(&[ms(5), ms(1), ms(3)]),
+            Some(Stats {
+                min: ms(1),
+                median: ms(3),
+                max: ms(5)
+            })
+        );
+        assert_eq!(Stats::from_samples(&[]), None);
+    }
+
+    /// Sleeps for 2ms to parse, and for 1ms more to solve part 1 once it has parsed
+    struct Sleepy;
+
+    impl Solution for Sleepy {
+        fn part1(&self, input: &Input) -> Result<crate::Answer> {
+            self.parse(input)?;
+            std::thread::sleep(ms(1));
+            Ok(Box::new(0))
+        }
+
+        fn part2(&self, _input: &Input) -> Result<crate::Answer> {
+            Err(eyre!("no part 2"))
+        }
+
+        fn parse(&self, _input: &Input) -> Result<()> {
+            std::thread::sleep(ms(2));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn bench_test() {
+        let input = Input::from("");
+        let timing = bench(1, &Sleepy, Part::One, &input, 3).unwrap();
+        assert_eq!((timing.day, timing.part, timing.runs), (1, Part::One, 3));
+        // A sleep lasts at least as long as asked, so these hold however busy the machine is
+        assert!(timing.parse.min >= ms(2));
+        assert!(timing.total.min >= ms(3));
+        assert!(timing.total.min <= timing.total.median && timing.total.median <= timing.total.max);
+        // Each run's solve time is its total less its parse time
+        assert!(timing.solve.min <= timing.total.min - ms(2));
+        assert!(timing.solve.max <= timing.total.max - ms(2));
+
+        assert!(bench(1, &Sleepy, Part::Two, &input, 3).is_err());
+        assert!(bench(1, &Sleepy, Part::One, &input, 0).is_err());
+    }
+
+    #[test]
+    fn write_tsv_test() {
+        let stats = Stats {
+            min: ms(1),
+            median: ms(2),
+            max: ms(3),
+        };
+        let timing = Timing {
+            day: 15,
+            part: Part::Two,
+            runs: 10,
+            parse: stats,
+            solve: stats,
+            total: stats,
+        };
+        let mut out = vec![];
+        write_tsv(&mut out, &[timing]).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0].split('\t').count(), 12);
+        assert!(lines[0].starts_with("day\tpart\truns\tparse_min_ns\tparse_median_ns"));
+        assert_eq!(
+            lines[1],
+            "15\t2\t10\t1000000\t2000000\t3000000\t1000000\t2000000\t3000000\t1000000\t2000000\t3000000"
+        );
+    }
+
+    #[test]
+    fn human_test() {
+        assert_eq!(Human(Duration::from_nanos(999)).to_string(), "999ns");
+        assert_eq!(Human(Duration::from_micros(1500)).to_string(), "1.50ms");
+        assert_eq!(Human(Duration::from_secs(2)).to_string(), "2.00s");
+    }
+}
@@ -0,0 +1,304 @@
+//! Puzzle inputs kept on disk, fetched from the site the first time each is needed
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use eyre::{eyre, Result, WrapErr};
+
+use crate::Input;
+
+pub const BASE_URL: &str = "https://adventofcode.com";
+
+/// Sent with every request, as the site asks automated tools to say where they come from
+const USER_AGENT: &str = "github.com/raine/advent-of-code input cache";
+
+/// A way of making requests to the site
+pub trait Fetch {
+    /// Body of the response to a GET of `url` with the session cookie, or an error if the
+    /// response was not a success
+    fn fetch(&self, url: &str, session: &str) -> Result<String>;
+
+    /// Body of the response to POSTing the form to `url` with the session cookie, or an error
+    /// if the response was not a success
+    fn post(&self, url: &str, session: &str, form: &[(&str, &str)]) -> Result<String>;
+}
+
+/// Fetches over HTTP
+pub struct Http {
+    agent: ureq::Agent,
+}
+
+impl Http {
+    pub fn new() -> Http {
+        Http {
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(30))
+                .user_agent(USER_AGENT)
+                .build(),
+        }
+    }
+}
+
+impl Default for Http {
+    fn default() -> Self {
+        Http::new()
+    }
+}
+
+impl Fetch for Http {
+    fn fetch(&self, url: &str, session: &str) -> Result<String> {
+        let response = self
+            .agent
+            .get(url)
+            .set("Cookie", &format!("session={session}"))
+            .call();
+        body(url, response)
+    }
+
+    fn post(&self, url: &str, session: &str, form: &[(&str, &str)]) -> Result<String> {
+        let response = self
+            .agent
+            .post(url)
+            .set("Cookie", &format!("session={session}"))
+            .send_form(form);
+        body(url, response)
+    }
+}
+
+fn body(url: &str, response: Result<ureq::Response, ureq::Error>) -> Result<String> {
+    match response {
+        Ok(response) => Ok(response.into_string()?),
+        Err(ureq::Error::Status(status, response)) => {
+            let body = response.into_string().unwrap_or_default();
+            Err(eyre!(
+                "{url} responded with status {status}: {}",
+                body.lines().next().unwrap_or_default()
+            ))
+        }
+        Err(e) => Err(e).wrap_err_with(|| format!("could not reach {url}")),
+    }
+}
+
+/// Checks that fetched text is a puzzle input rather than an error page, returning it with
+/// `\n` line endings and a single trailing newline
+pub fn validate(text: &str) -> Result<String> {
+    let text = text.replace("\r\n", "\n");
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return Err(eyre!("the input is empty"));
+    }
+    if trimmed.contains("Puzzle inputs differ by user") {
+        return Err(eyre!("the session token was not accepted"));
+    }
+    if trimmed.contains("before it unlocks") {
+        return Err(eyre!("the puzzle has not unlocked yet"));
+    }
+    let lower = trimmed.to_ascii_lowercase();
+    if lower.starts_with("<!doctype") || lower.contains("<html") {
+        return Err(eyre!("got an HTML page instead of a puzzle input"));
+    }
+
+    // Leading whitespace is kept, as day 5's crate diagram starts with it
+    Ok(format!("{}\n", text.trim_end_matches('\n')))
+}
+
+/// Puzzle inputs cached as `<dir>/<year>/day<N>`
+pub struct InputCache {
+    dir: PathBuf,
+    base_url: String,
+    session: Option<String>,
+    fetcher: Box<dyn Fetch>,
+}
+
+impl InputCache {
+    /// Cache in `dir` that fetches from the site with `fetcher`, without a session token
+    pub fn new(dir: impl Into<PathBuf>, fetcher: Box<dyn Fetch>) -> InputCache {
+        InputCache {
+            dir: dir.into(),
+            base_url: BASE_URL.to_string(),
+            session: None,
+            fetcher,
+        }
+    }
+
+    /// Cache in [`cache_dir`] that fetches over HTTP with the session token in `$AOC_SESSION`
+    pub fn from_env() -> Result<InputCache> {
+        Ok(InputCache::new(cache_dir()?, Box::new(Http::new())).with_session(session()))
+    }
+
+    pub fn with_base_url(mut self, base_url: &str) -> InputCache {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_session(mut self, session: Option<String>) -> InputCache {
+        self.session = session;
+        self
+    }
+
+    pub fn path(&self, year: u16, day: u8) -> PathBuf {
+        self.dir.join(year.to_string()).join(format!("day{day}"))
+    }
+
+    /// The day's input, read from the cache or else fetched and cached
+    pub fn get(&self, year: u16, day: u8) -> Result<Input> {
+        let path = self.path(year, day);
+        if path.exists() {
+            return Input::from_path(&path);
+        }
+
+        let text = self
+            .fetch(year, day)
+            .wrap_err_with(|| format!("could not fetch the input of {year} day {day}"))?;
+        save(&path, &text)?;
+        Ok(Input::new(&text))
+    }
+
+    fn fetch(&self, year: u16, day: u8) -> Result<String> {
+        let session = self.session.as_deref().ok_or_else(|| {
+            eyre!("it is not cached and there is no session token, set AOC_SESSION to fetch it")
+        })?;
+        let url = format!("{}/{year}/day/{day}/input", self.base_url);
+        validate(&self.fetcher.fetch(&url, session)?)
+    }
+}
+
+/// `$AOC_CACHE_DIR`, or else `$XDG_CACHE_HOME/aoc` or `~/.cache/aoc`
+pub fn cache_dir() -> Result<PathBuf> {
+    if let Some(dir) = env::var_os("AOC_CACHE_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+    match env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => Ok(PathBuf::from(dir).join("aoc")),
+        _ => env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(".cache").join("aoc"))
+            .ok_or_else(|| eyre!("no cache directory, set AOC_CACHE_DIR")),
+    }
+}
+
+/// Session token in `$AOC_SESSION`, the value of the site's `session` cookie
+pub fn session() -> Option<String> {
+    env::var("AOC_SESSION").ok().filter(|s| !s.is_empty())
+}
+
+/// Writes the file through a temporary one, so an interrupted write leaves no partial input
+fn save(path: &Path, text: &str) -> Result<()> {
+    let write = || -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let partial = path.with_extension("partial");
+        fs::write(&partial, text)?;
+        fs::rename(&partial, path)?;
+        Ok(())
+    };
+    write().wrap_err_with(|| format!("could not cache the input in {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+    use crate::test_support::{serve_once, temp_dir};
+
+    /// Answers every request with the same text, counting them
+    struct Canned {
+        text: &'static str,
+        calls: Rc<Cell<usize>>,
+    }
+
+    impl Fetch for Canned {
+        fn fetch(&self, _url: &str, _session: &str) -> Result<String> {
+            self.calls.set(self.calls.get() + 1);
+            Ok(self.text.to_string())
+        }
+
+        fn post(&self, _url: &str, _session: &str, _form: &[(&str, &str)]) -> Result<String> {
+            Err(eyre!("the cache only fetches"))
+        }
+    }
+
+    #[test]
+    fn fetch_over_http_test() {
+        let (url, server) = serve_once(200, "1000\r\n2000\r\n\r\n");
+        let dir = temp_dir("http");
+        let cache = InputCache::new(&dir, Box::new(Http::new()))
+            .with_base_url(&url)
+            .with_session(Some("abc".to_string()));
+
+        let input = cache.get(2022, 1).unwrap();
+        assert_eq!(input.as_str(), "1000\n2000");
+        let request = server.join().unwrap();
+        assert_eq!(
+            (request.method.as_str(), request.url.as_str()),
+            ("GET", "/2022/day/1/input")
+        );
+        assert_eq!(request.cookie, "session=abc");
+        let cached = fs::read_to_string(dir.join("2022").join("day1")).unwrap();
+        assert_eq!(cached, "1000\n2000\n");
+
+        // The server is gone, so this can only come from the cache
+        assert_eq!(cache.get(2022, 1).unwrap(), input);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn fetch_error_test() {
+        let dir = temp_dir("error");
+        let (url, server) = serve_once(400, "Puzzle inputs differ by user.  Please log in.");
+        let cache = InputCache::new(&dir, Box::new(Http::new()))
+            .with_base_url(&url)
+            .with_session(Some("expired".to_string()));
+        let error = format!("{:#}", cache.get(2022, 3).unwrap_err());
+        assert!(
+            error.contains("status 400: Puzzle inputs differ by user"),
+            "{error}"
+        );
+        server.join().unwrap();
+
+        let (url, server) = serve_once(200, "<!DOCTYPE html>\n<html></html>");
+        let cache = cache.with_base_url(&url);
+        let error = format!("{:#}", cache.get(2022, 3).unwrap_err());
+        assert!(error.contains("HTML page"), "{error}");
+        server.join().unwrap();
+
+        assert!(!cache.path(2022, 3).exists());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn cache_test() {
+        let calls = Rc::new(Cell::new(0));
+        let canned = Canned {
+            text: "    [D]\n[N] [C]\n 1   2\n\nmove 1 from 2 to 1\n\n",
+            calls: calls.clone(),
+        };
+        let dir = temp_dir("canned");
+        let cache = InputCache::new(&dir, Box::new(canned));
+        assert!(cache.get(2022, 5).is_err());
+        assert_eq!(calls.get(), 0);
+
+        let cache = cache.with_session(Some("abc".to_string()));
+        let input = cache.get(2022, 5).unwrap();
+        assert!(input.starts_with("    [D]"));
+        assert_eq!(cache.get(2022, 5).unwrap(), input);
+        assert_eq!(calls.get(), 1);
+        assert_eq!(cache.path(2022, 5), dir.join("2022").join("day5"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn validate_test() {
+        assert_eq!(validate("1\r\n2").unwrap(), "1\n2\n");
+        assert_eq!(validate("  1\n2\n\n\n").unwrap(), "  1\n2\n");
+        assert!(validate("\n\n").is_err());
+        assert!(validate("<html><body>500</body></html>").is_err());
+        assert!(
+            validate("Please don't repeatedly request this endpoint before it unlocks!").is_err()
+        );
+    }
+}
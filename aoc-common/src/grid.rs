@@ -0,0 +1,270 @@
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+use crate::{parse::ParseError, point::Point};
+
+/// Position of a cell as `(x, y)`, where `(0, 0)` is the top left cell
+pub type Pos = (usize, usize);
+
+/// A rectangular grid of cells, stored row by row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            cells: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+
+    /// Parses a map with a character per cell and a line per row. Every row must be as wide as
+    /// the first; `expected` describes the characters `parse_cell` accepts.
+    pub fn parse(
+        input: &str,
+        expected: &str,
+        parse_cell: impl Fn(char) -> Option<T>,
+    ) -> Result<Grid<T>, ParseError> {
+        let width = input.lines().next().map_or(0, |l| l.chars().count());
+        let mut cells = vec![];
+        let mut height = 0;
+        for line in input.lines() {
+            let mut row_width = 0;
+            for (col, c) in line.char_indices() {
+                if row_width == width {
+                    return Err(ParseError::at(
+                        input,
+                        &line[col..],
+                        format!("{width} cells per row"),
+                    ));
+                }
+                let cell =
+                    parse_cell(c).ok_or_else(|| ParseError::at(input, &line[col..], expected))?;
+                cells.push(cell);
+                row_width += 1;
+            }
+
+            if row_width < width {
+                let at = &line[line.len()..];
+                return Err(ParseError::at(input, at, format!("{width} cells per row")));
+            }
+            height += 1;
+        }
+
+        Ok(Grid {
+            cells,
+            width,
+            height,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (x, y): Pos) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.contains(pos).then(|| &self.cells[self.index(pos)])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        let idx = self.index(pos);
+        self.contains(pos).then(|| &mut self.cells[idx])
+    }
+
+    fn index(&self, (x, y): Pos) -> usize {
+        x + y * self.width
+    }
+
+    /// Position `step` away from `pos`, or `None` if that is outside the grid
+    pub fn offset(&self, (x, y): Pos, step: Point) -> Option<Pos> {
+        let x = usize::try_from(x as i64 + step.x).ok()?;
+        let y = usize::try_from(y as i64 + step.y).ok()?;
+        self.contains((x, y)).then_some((x, y))
+    }
+
+    /// Every position, row by row
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Every cell with its position, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// Position of the first cell, row by row, that matches the predicate
+    pub fn find(&self, predicate: impl Fn(&T) -> bool) -> Option<Pos> {
+        self.iter().find(|(_, c)| predicate(c)).map(|(pos, _)| pos)
+    }
+
+    /// Positions of the cells above, right of, below and left of `pos` that are in the grid
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        Point::ORTHOGONAL
+            .into_iter()
+            .filter_map(move |d| self.offset(pos, d))
+    }
+
+    /// Positions of the cells around `pos`, including diagonally, that are in the grid
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        Point::ALL_DIRECTIONS
+            .into_iter()
+            .filter_map(move |d| self.offset(pos, d))
+    }
+
+    /// Cells of row `y`, left to right. Panics if the row is outside the grid.
+    pub fn row(&self, y: usize) -> &[T] {
+        assert!(
+            y < self.height,
+            "row {y} outside grid of height {}",
+            self.height
+        );
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on a zero width, and a grid without columns has no cells anyway
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// Cells of column `x`, top to bottom. Panics if the column is outside the grid.
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(
+            x < self.width,
+            "column {x} outside grid of width {}",
+            self.width
+        );
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    /// Cells from `pos` in steps of `step` until the edge of the grid, not including `pos`
+    pub fn ray(&self, pos: Pos, step: Point) -> impl Iterator<Item = (Pos, &T)> {
+        std::iter::successors(self.offset(pos, step), move |&p| self.offset(p, step))
+            .map(|p| (p, &self[p]))
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{pos:?} outside {}x{} grid", self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{pos:?} outside {width}x{height} grid"))
+    }
+}
+
+/// Draws the grid with a line per row
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(input: &str) -> Grid<u32> {
+        Grid::parse(input, "digit", |c| c.to_digit(10)).unwrap()
+    }
+
+    #[test]
+    fn parse_test() {
+        let grid = digits("123\n456");
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get((2, 1)), Some(&6));
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid[(0, 1)], 4);
+        assert_eq!(digits("").positions().count(), 0);
+    }
+
+    #[test]
+    fn parse_error_test() {
+        let parse = |s| Grid::parse(s, "digit", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!(parse("123\n4x6"), ParseError::new(2, 2, "digit", "x6"));
+        assert_eq!(
+            parse("123\n45"),
+            ParseError::new(2, 3, "3 cells per row", "")
+        );
+        assert_eq!(
+            parse("123\n4567"),
+            ParseError::new(2, 4, "3 cells per row", "7")
+        );
+    }
+
+    #[test]
+    fn neighbours_test() {
+        let grid = digits("123\n456\n789");
+        assert_eq!(
+            grid.neighbours4((0, 0)).collect::<Vec<_>>(),
+            vec![(1, 0), (0, 1)]
+        );
+        assert_eq!(grid.neighbours4((1, 1)).count(), 4);
+        assert_eq!(
+            grid.neighbours8((2, 0))
+                .map(|p| grid[p])
+                .collect::<Vec<_>>(),
+            vec![6, 5, 2]
+        );
+        assert_eq!(grid.neighbours8((1, 1)).count(), 8);
+    }
+
+    #[test]
+    fn rows_and_columns_test() {
+        let grid = digits("123\n456");
+        assert_eq!(grid.row(1), &[4, 5, 6]);
+        assert_eq!(grid.rows().count(), 2);
+        assert_eq!(grid.column(2).copied().collect::<Vec<_>>(), vec![3, 6]);
+    }
+
+    #[test]
+    fn ray_test() {
+        let grid = digits("123\n456\n789");
+        let ray = |pos, step| grid.ray(pos, step).map(|(_, c)| *c).collect::<Vec<_>>();
+        assert_eq!(ray((0, 0), Point::DOWN_RIGHT), vec![5, 9]);
+        assert_eq!(ray((1, 2), Point::UP), vec![5, 2]);
+        assert_eq!(ray((2, 1), Point::RIGHT), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn find_and_mutate_test() {
+        let mut grid = Grid::new(2, 2, '.');
+        grid[(1, 1)] = '#';
+        *grid.get_mut((0, 1)).unwrap() = 'o';
+        assert!(grid.get_mut((2, 0)).is_none());
+        assert_eq!(grid.find(|c| *c == '#'), Some((1, 1)));
+        assert_eq!(grid.to_string(), "..\no#\n");
+    }
+}
@@ -0,0 +1,337 @@
+//! Raster images of grids, written as PPM or PNG without an image library
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use eyre::{eyre, Result};
+
+use crate::grid::Grid;
+
+/// Red, green and blue
+pub type Rgb = [u8; 3];
+
+/// Colours for particular values, and a colour for every other value
+#[derive(Debug, Clone)]
+pub struct Palette<T> {
+    colours: Vec<(T, Rgb)>,
+    default: Rgb,
+}
+
+impl<T: PartialEq> Palette<T> {
+    pub fn new(default: Rgb) -> Palette<T> {
+        Palette {
+            colours: vec![],
+            default,
+        }
+    }
+
+    pub fn with(mut self, value: T, colour: Rgb) -> Palette<T> {
+        self.colours.push((value, colour));
+        self
+    }
+
+    pub fn colour(&self, value: &T) -> Rgb {
+        self.colours
+            .iter()
+            .find(|(v, _)| v == value)
+            .map_or(self.default, |(_, colour)| *colour)
+    }
+}
+
+/// Colours blending from one to another as a value goes from `min` to `max`
+#[derive(Debug, Clone, Copy)]
+pub struct Gradient {
+    pub from: Rgb,
+    pub to: Rgb,
+    pub min: u32,
+    pub max: u32,
+}
+
+impl Gradient {
+    /// Colour of the value, which is clamped to the gradient's range
+    pub fn colour(&self, value: u32) -> Rgb {
+        let t = if self.max > self.min {
+            (value.clamp(self.min, self.max) - self.min) as f64 / (self.max - self.min) as f64
+        } else {
+            0.0
+        };
+        let mut colour = [0; 3];
+        for (c, (from, to)) in colour.iter_mut().zip(self.from.iter().zip(self.to)) {
+            *c = (*from as f64 + (to as f64 - *from as f64) * t).round() as u8;
+        }
+        colour
+    }
+}
+
+/// File formats an image can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Ppm,
+    Png,
+}
+
+impl Format {
+    /// Format named by the path's extension
+    pub fn from_path(path: &Path) -> Result<Format> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("ppm") => Ok(Format::Ppm),
+            Some("png") => Ok(Format::Png),
+            _ => Err(eyre!(
+                "cannot tell the image format of {}, expected a .ppm or .png file",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// A rectangle of pixels
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pixels: Grid<Rgb>,
+}
+
+impl Image {
+    /// Image with a pixel per cell, coloured by `colour`
+    pub fn from_grid<T>(grid: &Grid<T>, colour: impl Fn(&T) -> Rgb) -> Image {
+        let mut pixels = Grid::new(grid.width(), grid.height(), [0; 3]);
+        for (pos, cell) in grid.iter() {
+            pixels[pos] = colour(cell);
+        }
+        Image { pixels }
+    }
+
+    pub fn width(&self) -> usize {
+        self.pixels.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.pixels.height()
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Rgb> {
+        self.pixels.get((x, y)).copied()
+    }
+
+    /// Sets the pixel, ignoring positions outside the image
+    pub fn set(&mut self, x: usize, y: usize, colour: Rgb) {
+        if let Some(pixel) = self.pixels.get_mut((x, y)) {
+            *pixel = colour;
+        }
+    }
+
+    /// Image with each pixel blown up to a `factor` by `factor` square
+    pub fn scale(&self, factor: usize) -> Image {
+        let mut pixels = Grid::new(self.width() * factor, self.height() * factor, [0; 3]);
+        for (x, y) in pixels.positions() {
+            pixels[(x, y)] = self.pixels[(x / factor, y / factor)];
+        }
+        Image { pixels }
+    }
+
+    /// Pixel bytes row by row, each row after a byte for its PNG filter type if `filter` is set
+    fn bytes(&self, filter: bool) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity((self.width() * 3 + 1) * self.height());
+        for row in self.pixels.rows() {
+            if filter {
+                // Filter type 0 leaves the row as it is
+                bytes.push(0);
+            }
+            bytes.extend(row.iter().flatten());
+        }
+        bytes
+    }
+
+    /// Writes the image as a binary PPM, which has a text header and the raw pixels
+    pub fn write_ppm(&self, mut w: impl Write) -> io::Result<()> {
+        write!(w, "P6\n{} {}\n255\n", self.width(), self.height())?;
+        w.write_all(&self.bytes(false))
+    }
+
+    /// Writes the image as an 8-bit RGB PNG. The pixels are stored without compression, which
+    /// keeps the encoder small at the cost of files about as large as a PPM.
+    pub fn write_png(&self, mut w: impl Write) -> io::Result<()> {
+        w.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+        let mut header = vec![];
+        header.extend((self.width() as u32).to_be_bytes());
+        header.extend((self.height() as u32).to_be_bytes());
+        // Bit depth 8, colour type 2 (RGB), and the only compression, filter and interlace
+        // methods there are
+        header.extend([8, 2, 0, 0, 0]);
+        write_chunk(&mut w, b"IHDR", &header)?;
+        write_chunk(&mut w, b"IDAT", &zlib_stored(&self.bytes(true)))?;
+        write_chunk(&mut w, b"IEND", &[])
+    }
+
+    /// Writes the image in the format named by the path's extension
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let format = Format::from_path(path)?;
+        let mut w = BufWriter::new(File::create(path)?);
+        match format {
+            Format::Ppm => self.write_ppm(&mut w)?,
+            Format::Png => self.write_png(&mut w)?,
+        }
+        w.flush()?;
+        Ok(())
+    }
+}
+
+fn write_chunk(mut w: impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    w.write_all(kind)?;
+    w.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data));
+    w.write_all(&crc.to_be_bytes())
+}
+
+/// A zlib stream holding the data in uncompressed deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = u16::MAX as usize;
+
+    // Deflate with a 32K window and no preset dictionary, at the fastest level
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1, 0);
+    // Sums of this many bytes cannot overflow before they are reduced
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 == 1 {
+                0xedb88320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+fn crc32<'a>(data: impl IntoIterator<Item = &'a u8>) -> u32 {
+    !data.into_iter().fold(!0, |crc, &byte| {
+        CRC_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: Rgb = [0, 0, 0];
+    const WHITE: Rgb = [255, 255, 255];
+
+    fn checkerboard() -> Image {
+        let grid = Grid::parse("#.\n.#\n#.", "# or .", |c| Some(c == '#')).unwrap();
+        Image::from_grid(&grid, |&on| if on { WHITE } else { BLACK })
+    }
+
+    #[test]
+    fn palette_and_gradient_test() {
+        let palette = Palette::new(BLACK).with('#', WHITE).with('o', [1, 2, 3]);
+        assert_eq!(palette.colour(&'o'), [1, 2, 3]);
+        assert_eq!(palette.colour(&'.'), BLACK);
+
+        let gradient = Gradient {
+            from: [0, 100, 200],
+            to: [200, 100, 0],
+            min: 0,
+            max: 4,
+        };
+        assert_eq!(gradient.colour(1), [50, 100, 150]);
+        assert_eq!(gradient.colour(9), [200, 100, 0]);
+    }
+
+    #[test]
+    fn scale_test() {
+        let image = checkerboard().scale(2);
+        assert_eq!((image.width(), image.height()), (4, 6));
+        assert_eq!(image.get(1, 1), Some(WHITE));
+        assert_eq!(image.get(2, 1), Some(BLACK));
+        assert_eq!(image.get(2, 3), Some(WHITE));
+    }
+
+    #[test]
+    fn ppm_test() {
+        let mut ppm = vec![];
+        checkerboard().write_ppm(&mut ppm).unwrap();
+        let (header, pixels) = ppm.split_at(11);
+        assert_eq!(header, b"P6\n2 3\n255\n");
+        assert_eq!(&pixels[..6], &[255, 255, 255, 0, 0, 0]);
+        assert_eq!(pixels.len(), 2 * 3 * 3);
+    }
+
+    #[test]
+    fn png_test() {
+        let image = checkerboard().scale(3);
+        let mut png = vec![];
+        image.write_png(&mut png).unwrap();
+
+        let decoder = ::png::Decoder::new(io::Cursor::new(png));
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (6, 9));
+        assert_eq!(info.color_type, ::png::ColorType::Rgb);
+        assert_eq!(pixels, image.bytes(false));
+    }
+
+    #[test]
+    fn zlib_stored_test() {
+        // Enough data for more than one block
+        let data = (0..70000).map(|i| i as u8).collect::<Vec<_>>();
+        let stream = zlib_stored(&data);
+        assert_eq!(stream.len(), 2 + 5 + 65535 + 5 + (70000 - 65535) + 4);
+        assert_eq!(stream[2], 0);
+        assert_eq!(stream[2 + 5 + 65535], 1);
+    }
+
+    #[test]
+    fn checksum_test() {
+        assert_eq!(crc32(b"IEND"), 0xae426082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+    }
+
+    #[test]
+    fn format_test() {
+        assert_eq!(Format::from_path(Path::new("a.png")).unwrap(), Format::Png);
+        assert_eq!(Format::from_path(Path::new("a.ppm")).unwrap(), Format::Ppm);
+        assert!(Format::from_path(Path::new("a.jpg")).is_err());
+    }
+}
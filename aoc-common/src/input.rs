@@ -0,0 +1,95 @@
+use std::{
+    fs,
+    io::{self, Read},
+    ops::Deref,
+    path::Path,
+};
+
+use eyre::{Result, WrapErr};
+
+/// Puzzle input with `\n` line endings and no blank lines before or after it, however it was
+/// read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Input {
+    text: String,
+}
+
+impl Input {
+    pub fn new(text: &str) -> Input {
+        let text = text.replace("\r\n", "\n");
+        Input {
+            text: text
+                .trim_start_matches('\n')
+                .trim_end_matches('\n')
+                .to_string(),
+        }
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Input> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .wrap_err_with(|| format!("could not read {}", path.display()))?;
+        Ok(Input::new(&text))
+    }
+
+    pub fn from_reader(mut reader: impl Read) -> Result<Input> {
+        let mut text = String::new();
+        reader
+            .read_to_string(&mut text)
+            .wrap_err("could not read input")?;
+        Ok(Input::new(&text))
+    }
+
+    pub fn from_stdin() -> Result<Input> {
+        Input::from_reader(io::stdin().lock()).wrap_err("could not read input from stdin")
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+impl From<&str> for Input {
+    fn from(text: &str) -> Self {
+        Input::new(text)
+    }
+}
+
+impl Deref for Input {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::test_support::temp_dir;
+
+    #[test]
+    fn new_test() {
+        assert_eq!(Input::new("\n\na\r\nb\r\n\r\n").as_str(), "a\nb");
+        assert_eq!(Input::new("  a\n b \n").as_str(), "  a\n b ");
+    }
+
+    #[test]
+    fn from_reader_test() {
+        let input = Input::from_reader(Cursor::new("R 4\r\nU 4\r\n")).unwrap();
+        assert_eq!(input.lines().collect::<Vec<_>>(), vec!["R 4", "U 4"]);
+    }
+
+    #[test]
+    fn from_path_test() {
+        let dir = temp_dir("input");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("day6"), "mjqjpqmgbljsphdztnvjfqwrcgsmlb\n\n").unwrap();
+        let input = Input::from_path(dir.join("day6")).unwrap();
+        assert!(!input.ends_with('\n'));
+        assert!(Input::from_path(dir.join("missing")).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
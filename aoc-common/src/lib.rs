@@ -0,0 +1,157 @@
+//! The parts of solving Advent of Code that every year shares: grids, geometry, parsing and
+//! the runner that each year's crate registers its days with
+#![allow(dead_code)]
+use std::{collections::BTreeMap, fmt, fmt::Display, ops::RangeInclusive, str::FromStr};
+
+use eyre::{eyre, Result};
+
+pub mod animate;
+pub mod answers;
+pub mod asciicast;
+pub mod bench;
+pub mod cache;
+pub mod grid;
+pub mod image;
+pub mod input;
+pub mod ocr;
+pub mod parse;
+pub mod point;
+pub mod range_set;
+pub mod report;
+pub mod scaffold;
+pub mod search;
+pub mod submit;
+#[cfg(test)]
+mod test_support;
+pub mod trace;
+
+pub use input::Input;
+
+/// Answer to one part of a puzzle, in the form it is entered on the site
+pub type Answer = Box<dyn Display>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const BOTH: [Part; 2] = [Part::One, Part::Two];
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Part::One => f.pad("1"),
+            Part::Two => f.pad("2"),
+        }
+    }
+}
+
+impl FromStr for Part {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            _ => Err(eyre!("part must be 1 or 2, got {s:?}")),
+        }
+    }
+}
+
+/// A day's puzzle, solvable from the puzzle input
+pub trait Solution {
+    fn part1(&self, input: &Input) -> Result<Answer>;
+    fn part2(&self, input: &Input) -> Result<Answer>;
+
+    /// Parses the input without solving, so parsing can be timed on its own. Both parts parse
+    /// the input again when solving.
+    fn parse(&self, _input: &Input) -> Result<()> {
+        Ok(())
+    }
+
+    fn solve(&self, part: Part, input: &Input) -> Result<Answer> {
+        match part {
+            Part::One => self.part1(input),
+            Part::Two => self.part2(input),
+        }
+    }
+}
+
+/// A year's puzzle parameters, which the answers manifest sets by name
+pub trait PuzzleParams: Default {
+    /// Sets the parameter named as its command line flag
+    fn set(&mut self, key: &str, value: &str) -> Result<()>;
+}
+
+/// Parameters of a year whose puzzles take none
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoParams;
+
+impl PuzzleParams for NoParams {
+    fn set(&mut self, key: &str, _value: &str) -> Result<()> {
+        Err(eyre!("unknown parameter {key}"))
+    }
+}
+
+/// A year's solutions, as its crate registers them with the runner
+pub struct Puzzles<P> {
+    pub year: u16,
+    /// Days that have a solution
+    pub days: RangeInclusive<u8>,
+    /// Returns the solution for the given day, if there is one
+    pub solution: fn(u8, &P) -> Option<Box<dyn Solution>>,
+}
+
+impl<P> Puzzles<P> {
+    pub fn solution(&self, day: u8, params: &P) -> Option<Box<dyn Solution>> {
+        (self.solution)(day, params)
+    }
+
+    /// All solutions, keyed by day number
+    pub fn registry(&self, params: &P) -> BTreeMap<u8, Box<dyn Solution>> {
+        self.days
+            .clone()
+            .filter_map(|day| self.solution(day, params).map(|s| (day, s)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Constant;
+
+    impl Solution for Constant {
+        fn part1(&self, _input: &Input) -> Result<Answer> {
+            Ok(Box::new(1))
+        }
+
+        fn part2(&self, _input: &Input) -> Result<Answer> {
+            Ok(Box::new(2))
+        }
+    }
+
+    const PUZZLES: Puzzles<NoParams> = Puzzles {
+        year: 2015,
+        days: 1..=3,
+        solution: |day, _| matches!(day, 1 | 3).then(|| Box::new(Constant) as Box<dyn Solution>),
+    };
+
+    #[test]
+    fn registry_test() {
+        let registry = PUZZLES.registry(&NoParams);
+        assert_eq!(registry.keys().copied().collect::<Vec<_>>(), [1, 3]);
+        let answer = registry[&3].solve(Part::Two, &Input::new("")).unwrap();
+        assert_eq!(answer.to_string(), "2");
+        assert!(PUZZLES.solution(4, &NoParams).is_none());
+    }
+
+    #[test]
+    fn no_params_test() {
+        assert!(NoParams.set("row", "10").is_err());
+    }
+}
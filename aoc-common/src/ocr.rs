@@ -0,0 +1,144 @@
+//! Reading the letters that puzzles draw on screens, in the 4x6 font they all use
+
+use std::fmt;
+
+/// Height of a letter in pixels
+pub const HEIGHT: usize = 6;
+
+/// Width of a letter in pixels, not counting the blank column between letters
+pub const WIDTH: usize = 4;
+
+/// Letters of the font, with `#` for a lit pixel
+const FONT: [(char, [&str; HEIGHT]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// Text on a screen that has shapes which are not letters of the font
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OcrError {
+    /// 1-based column where each unknown shape starts
+    pub columns: Vec<usize>,
+    /// The text with `?` for each unknown shape
+    pub text: String,
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let columns = self
+            .columns
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>();
+        let s = if columns.len() == 1 { "" } else { "s" };
+        write!(
+            f,
+            "unknown letter{s} at column{s} {}, read {:?}",
+            columns.join(", "),
+            self.text
+        )
+    }
+}
+
+impl std::error::Error for OcrError {}
+
+/// Reads the letters on a screen of six lines, where `#` is a lit pixel and anything else is
+/// dark. Letters start every five columns.
+pub fn read(screen: &str) -> Result<String, OcrError> {
+    let rows = screen
+        .lines()
+        .map(|l| l.chars().map(|c| c == '#').collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let pixel = |row: usize, col: usize| rows.get(row).and_then(|r| r.get(col)) == Some(&true);
+
+    let mut text = String::new();
+    let mut columns = vec![];
+    for start in (0..width).step_by(WIDTH + 1) {
+        let letter = FONT.iter().find(|(_, glyph)| {
+            rows.len() == HEIGHT
+                && glyph.iter().enumerate().all(|(row, line)| {
+                    line.chars()
+                        .enumerate()
+                        .all(|(col, c)| (c == '#') == pixel(row, start + col))
+                })
+        });
+        match letter {
+            Some(&(letter, _)) => text.push(letter),
+            None => {
+                text.push('?');
+                columns.push(start + 1);
+            }
+        }
+    }
+
+    if columns.is_empty() {
+        Ok(text)
+    } else {
+        Err(OcrError { columns, text })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The font's letters side by side, the way a puzzle draws them
+    fn screen(letters: &str) -> String {
+        (0..HEIGHT)
+            .map(|row| {
+                letters
+                    .chars()
+                    .map(|letter| FONT.iter().find(|(l, _)| *l == letter).unwrap().1[row])
+                    .collect::<Vec<_>>()
+                    .join(".")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn read_test() {
+        let letters = FONT.iter().map(|(letter, _)| letter).collect::<String>();
+        assert_eq!(read(&screen(&letters)).unwrap(), letters);
+        assert_eq!(read(&format!("{}.\n", screen("HI"))).unwrap(), "HI");
+    }
+
+    #[test]
+    fn unknown_letter_test() {
+        let mut screen = screen("ABCE");
+        // Light the dark corner of B's first row
+        screen.replace_range(8..9, "#");
+        let error = read(&screen).unwrap_err();
+        assert_eq!(error.columns, [6]);
+        assert_eq!(error.text, "A?CE");
+        assert_eq!(
+            error.to_string(),
+            "unknown letter at column 6, read \"A?CE\""
+        );
+
+        let error = read("#....#\n#").unwrap_err();
+        assert_eq!(
+            (error.columns.as_slice(), error.text.as_str()),
+            (&[1, 6][..], "??")
+        );
+        assert!(error
+            .to_string()
+            .starts_with("unknown letters at columns 1, 6"));
+    }
+}
@@ -0,0 +1,208 @@
+use std::fmt;
+
+use nom::{
+    character::complete::line_ending,
+    combinator::all_consuming,
+    error::{ErrorKind, VerboseError, VerboseErrorKind},
+    Finish,
+};
+
+/// nom result type used by the puzzle parsers, keeping enough context to build a [`ParseError`]
+pub type IResult<'a, O> = nom::IResult<&'a str, O, VerboseError<&'a str>>;
+
+/// Puzzle input that does not have the expected format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column number, counted in characters
+    pub column: usize,
+    /// Description of the token that was expected
+    pub expected: String,
+    /// The token that was found instead, empty at the end of a line
+    pub found: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, expected: impl Into<String>, found: &str) -> Self {
+        ParseError {
+            line,
+            column,
+            expected: expected.into(),
+            found: first_token(found).to_string(),
+        }
+    }
+
+    /// Error at the start of `at`, which must be a suffix or other subslice of `input`
+    pub fn at(input: &str, at: &str, expected: impl Into<String>) -> Self {
+        let offset = (at.as_ptr() as usize)
+            .checked_sub(input.as_ptr() as usize)
+            .filter(|offset| *offset <= input.len())
+            .expect("`at` must be a subslice of `input`");
+        let before = &input[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+        ParseError::new(line, column, expected, at)
+    }
+
+    /// Moves the error down by `lines`, for errors found in a block that starts partway through
+    /// the input
+    pub fn offset_lines(mut self, lines: usize) -> Self {
+        self.line += lines;
+        self
+    }
+
+    fn from_verbose(input: &str, err: VerboseError<&str>) -> Self {
+        let Some((at, kind)) = err.errors.first() else {
+            return ParseError::new(1, 1, "valid input", input);
+        };
+        let context = |same_position: bool| {
+            err.errors.iter().find_map(|(i, kind)| match kind {
+                VerboseErrorKind::Context(ctx) if !same_position || i == at => {
+                    Some(ctx.to_string())
+                }
+                _ => None,
+            })
+        };
+        // A context naming the token at the error position describes it better than the
+        // innermost parser, e.g. "number or list" rather than the '[' of the last alternative
+        let expected = match kind {
+            VerboseErrorKind::Char(c) => context(true).unwrap_or_else(|| format!("{c:?}")),
+            VerboseErrorKind::Context(ctx) => ctx.to_string(),
+            VerboseErrorKind::Nom(kind) => context(false).unwrap_or_else(|| describe_kind(*kind)),
+        };
+        ParseError::at(input, at, expected)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: expected {}, found ",
+            self.line, self.column, self.expected
+        )?;
+        if self.found.is_empty() {
+            write!(f, "end of line")
+        } else {
+            write!(f, "{:?}", self.found)
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn first_token(s: &str) -> &str {
+    let line = s.lines().next().unwrap_or("");
+    line.split_whitespace().next().unwrap_or("")
+}
+
+fn describe_kind(kind: ErrorKind) -> String {
+    match kind {
+        ErrorKind::Digit => "digits".to_string(),
+        ErrorKind::Eof => "end of input".to_string(),
+        ErrorKind::CrLf => "line break".to_string(),
+        kind => kind.description().to_lowercase(),
+    }
+}
+
+/// Runs `parser` over the whole of `input`
+pub fn parse_all<'a, O, P>(input: &'a str, parser: P) -> Result<O, ParseError>
+where
+    P: FnMut(&'a str) -> IResult<'a, O>,
+{
+    all_consuming(parser)(input)
+        .finish()
+        .map(|(_, o)| o)
+        .map_err(|e| ParseError::from_verbose(input, e))
+}
+
+/// Parses one item per line, allowing a trailing line break. Once a line has started, an error
+/// in it is reported where it happens instead of backtracking to the line break.
+pub fn lines_of<'a, O, P>(mut parser: P) -> impl FnMut(&'a str) -> IResult<'a, Vec<O>>
+where
+    P: FnMut(&'a str) -> IResult<'a, O>,
+{
+    move |i| {
+        let (mut i, first) = parser(i)?;
+        let mut items = vec![first];
+        while let Ok((rest, _)) = line_ending::<_, VerboseError<&str>>(i) {
+            if rest.is_empty() {
+                return Ok((rest, items));
+            }
+            let (rest, item) = parser(rest).map_err(|e| match e {
+                nom::Err::Error(e) => nom::Err::Failure(e),
+                e => e,
+            })?;
+            items.push(item);
+            i = rest;
+        }
+
+        Ok((i, items))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::{
+        character::complete::{char, digit1},
+        error::context,
+        sequence::separated_pair,
+    };
+
+    use super::*;
+
+    fn pair(i: &str) -> IResult<'_, (&str, &str)> {
+        separated_pair(
+            context("number", digit1),
+            char(','),
+            context("number", digit1),
+        )(i)
+    }
+
+    #[test]
+    fn at_test() {
+        let input = "abc\ndef ghi\n";
+        let err = ParseError::at(input, &input[8..], "number");
+        assert_eq!(err, ParseError::new(2, 5, "number", "ghi"));
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 5: expected number, found \"ghi\""
+        );
+    }
+
+    #[test]
+    fn offset_lines_test() {
+        let err = ParseError::new(1, 3, "digit", "x").offset_lines(4);
+        assert_eq!(err.line, 5);
+    }
+
+    #[test]
+    fn parse_all_test() {
+        assert_eq!(parse_all("12,34", pair), Ok(("12", "34")));
+        assert_eq!(
+            parse_all("12;34", pair),
+            Err(ParseError::new(1, 3, "','", ";34"))
+        );
+        assert_eq!(
+            parse_all("12,x", pair),
+            Err(ParseError::new(1, 4, "number", "x"))
+        );
+        assert_eq!(
+            parse_all("12,34\n", pair).unwrap_err().to_string(),
+            "line 1, column 6: expected end of input, found end of line"
+        );
+    }
+
+    #[test]
+    fn lines_of_test() {
+        assert_eq!(
+            parse_all("1,2\n3,4\n", lines_of(pair)),
+            Ok(vec![("1", "2"), ("3", "4")])
+        );
+        assert_eq!(
+            parse_all("1,2\n3;4\n", lines_of(pair)),
+            Err(ParseError::new(2, 2, "','", ";4"))
+        );
+    }
+}
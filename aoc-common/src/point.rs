@@ -0,0 +1,213 @@
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+/// Signed integer type that a [`Point`]'s coordinates, or a `RangeSet`'s values, can have
+pub trait Coordinate:
+    Copy
+    + Ord
+    + fmt::Debug
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const MINUS_ONE: Self;
+
+    fn abs(self) -> Self;
+    fn signum(self) -> Self;
+}
+
+macro_rules! impl_coordinate {
+    ($($t:ty),*) => {
+        $(
+            impl Coordinate for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const MINUS_ONE: Self = -1;
+
+                fn abs(self) -> Self {
+                    <$t>::abs(self)
+                }
+
+                fn signum(self) -> Self {
+                    <$t>::signum(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_coordinate!(i8, i16, i32, i64, i128, isize);
+
+/// A point, or the vector between two points, on an integer plane. `y` grows downwards, as it
+/// does down the lines of a puzzle's map.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point<T = i64> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T: Coordinate> Point<T> {
+    pub const ORIGIN: Point<T> = Point::new(T::ZERO, T::ZERO);
+    pub const UP: Point<T> = Point::new(T::ZERO, T::MINUS_ONE);
+    pub const DOWN: Point<T> = Point::new(T::ZERO, T::ONE);
+    pub const LEFT: Point<T> = Point::new(T::MINUS_ONE, T::ZERO);
+    pub const RIGHT: Point<T> = Point::new(T::ONE, T::ZERO);
+    pub const UP_LEFT: Point<T> = Point::new(T::MINUS_ONE, T::MINUS_ONE);
+    pub const UP_RIGHT: Point<T> = Point::new(T::ONE, T::MINUS_ONE);
+    pub const DOWN_LEFT: Point<T> = Point::new(T::MINUS_ONE, T::ONE);
+    pub const DOWN_RIGHT: Point<T> = Point::new(T::ONE, T::ONE);
+
+    /// Steps up, right, down and left
+    pub const ORTHOGONAL: [Point<T>; 4] = [Self::UP, Self::RIGHT, Self::DOWN, Self::LEFT];
+
+    /// Steps to every surrounding point, clockwise from up
+    pub const ALL_DIRECTIONS: [Point<T>; 8] = [
+        Self::UP,
+        Self::UP_RIGHT,
+        Self::RIGHT,
+        Self::DOWN_RIGHT,
+        Self::DOWN,
+        Self::DOWN_LEFT,
+        Self::LEFT,
+        Self::UP_LEFT,
+    ];
+
+    /// Distance moving only horizontally and vertically
+    pub fn manhattan(self, other: Point<T>) -> T {
+        let d = other - self;
+        d.x.abs() + d.y.abs()
+    }
+
+    /// Distance moving in any of the eight directions, like a chess king
+    pub fn chebyshev(self, other: Point<T>) -> T {
+        let d = other - self;
+        d.x.abs().max(d.y.abs())
+    }
+
+    /// Step of at most one in each axis in the direction of the vector
+    pub fn signum(self) -> Point<T> {
+        Point::new(self.x.signum(), self.y.signum())
+    }
+}
+
+impl<T> Point<T> {
+    pub const fn new(x: T, y: T) -> Point<T> {
+        Point { x, y }
+    }
+}
+
+impl<T> From<(T, T)> for Point<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Point { x, y }
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point<T> {
+    type Output = Point<T>;
+
+    fn add(self, other: Point<T>) -> Point<T> {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point<T> {
+    type Output = Point<T>;
+
+    fn sub(self, other: Point<T>) -> Point<T> {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<T: Add<Output = T> + Copy> AddAssign for Point<T> {
+    fn add_assign(&mut self, other: Point<T>) {
+        *self = *self + other;
+    }
+}
+
+impl<T: Sub<Output = T> + Copy> SubAssign for Point<T> {
+    fn sub_assign(&mut self, other: Point<T>) {
+        *self = *self - other;
+    }
+}
+
+impl<T: Mul<Output = T> + Copy> Mul<T> for Point<T> {
+    type Output = Point<T>;
+
+    fn mul(self, scale: T) -> Point<T> {
+        Point::new(self.x * scale, self.y * scale)
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Point<T> {
+    type Output = Point<T>;
+
+    fn neg(self) -> Point<T> {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ops_test() {
+        let p = Point::new(2, -3);
+        assert_eq!(p + Point::new(1, 1), Point::new(3, -2));
+        assert_eq!(p - Point::new(1, 1), Point::new(1, -4));
+        assert_eq!(p * 3, Point::new(6, -9));
+        assert_eq!(-p, Point::new(-2, 3));
+
+        let mut q = p;
+        q += Point::RIGHT;
+        q -= Point::UP;
+        assert_eq!(q, Point::new(3, -2));
+    }
+
+    #[test]
+    fn distance_test() {
+        let a: Point = Point::new(0, 0);
+        let b = Point::new(-3, 4);
+        assert_eq!(a.manhattan(b), 7);
+        assert_eq!(b.manhattan(a), 7);
+        assert_eq!(a.chebyshev(b), 4);
+        assert_eq!(a.chebyshev(a), 0);
+    }
+
+    #[test]
+    fn directions_test() {
+        assert_eq!(Point::<i32>::UP, Point::new(0, -1));
+        assert_eq!(Point::<i32>::DOWN_LEFT, Point::new(-1, 1));
+        assert_eq!(
+            Point::<i64>::ORTHOGONAL
+                .iter()
+                .fold(Point::ORIGIN, |a, &d| a + d),
+            Point::ORIGIN
+        );
+        for d in Point::<i64>::ALL_DIRECTIONS {
+            assert_eq!(Point::ORIGIN.chebyshev(d), 1);
+        }
+    }
+
+    #[test]
+    fn signum_test() {
+        assert_eq!(Point::new(5, -2).signum(), Point::new(1, -1));
+        assert_eq!(Point::new(0, 7).signum(), Point::DOWN);
+    }
+
+    #[test]
+    fn display_test() {
+        assert_eq!(Point::new(498, 4).to_string(), "498,4");
+    }
+}
@@ -0,0 +1,243 @@
+use std::{iter::once, ops::RangeInclusive};
+
+use crate::point::Coordinate;
+
+/// A set of integers stored as sorted, disjoint inclusive ranges. Ranges that overlap or touch
+/// are merged, so `1..=3` and `4..=6` are stored as `1..=6`. Values must lie strictly between
+/// the type's minimum and maximum, as the ranges either side of a value are found by adding
+/// or subtracting one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangeSet<T = i64> {
+    ranges: Vec<RangeInclusive<T>>,
+}
+
+impl<T: Coordinate> RangeSet<T> {
+    pub fn new() -> RangeSet<T> {
+        RangeSet { ranges: vec![] }
+    }
+
+    /// The set's ranges, in order
+    pub fn ranges(&self) -> &[RangeInclusive<T>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Number of values in the set
+    pub fn len(&self) -> T {
+        self.ranges
+            .iter()
+            .fold(T::ZERO, |len, r| len + (*r.end() - *r.start() + T::ONE))
+    }
+
+    /// Adds the range's values, merging it with the ranges it overlaps or touches
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        let (mut start, mut end) = range.into_inner();
+        if start > end {
+            return;
+        }
+
+        let first = self.ranges.partition_point(|r| *r.end() + T::ONE < start);
+        let last = self.ranges.partition_point(|r| *r.start() <= end + T::ONE);
+        if first < last {
+            start = start.min(*self.ranges[first].start());
+            end = end.max(*self.ranges[last - 1].end());
+        }
+        self.ranges.splice(first..last, once(start..=end));
+    }
+
+    /// Takes the range's values out of the set, splitting any range it falls inside of
+    pub fn remove(&mut self, range: RangeInclusive<T>) {
+        let (start, end) = range.into_inner();
+        if start > end {
+            return;
+        }
+
+        let first = self.ranges.partition_point(|r| *r.end() < start);
+        let last = self.ranges.partition_point(|r| *r.start() <= end);
+        let mut remaining = vec![];
+        if first < last {
+            let (first_start, last_end) =
+                (*self.ranges[first].start(), *self.ranges[last - 1].end());
+            if first_start < start {
+                remaining.push(first_start..=start - T::ONE);
+            }
+            if last_end > end {
+                remaining.push(end + T::ONE..=last_end);
+            }
+        }
+        self.ranges.splice(first..last, remaining);
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let idx = self.ranges.partition_point(|r| *r.end() < value);
+        self.ranges.get(idx).is_some_and(|r| *r.start() <= value)
+    }
+
+    /// Whether every value of the range is in the set
+    pub fn contains_range(&self, range: &RangeInclusive<T>) -> bool {
+        if range.is_empty() {
+            return true;
+        }
+        let idx = self.ranges.partition_point(|r| r.end() < range.start());
+        self.ranges
+            .get(idx)
+            .is_some_and(|r| r.start() <= range.start() && range.end() <= r.end())
+    }
+
+    /// Whether any value of the range is in the set
+    pub fn overlaps(&self, range: &RangeInclusive<T>) -> bool {
+        let idx = self.ranges.partition_point(|r| r.end() < range.start());
+        !range.is_empty()
+            && self
+                .ranges
+                .get(idx)
+                .is_some_and(|r| r.start() <= range.end())
+    }
+
+    pub fn union(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut union = self.clone();
+        union.extend(other.ranges.iter().cloned());
+        union
+    }
+
+    pub fn intersection(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let (mut a, mut b) = (
+            self.ranges.iter().peekable(),
+            other.ranges.iter().peekable(),
+        );
+        let mut ranges = vec![];
+        while let (Some(ra), Some(rb)) = (a.peek(), b.peek()) {
+            let start = *ra.start().max(rb.start());
+            let end = *ra.end().min(rb.end());
+            if start <= end {
+                ranges.push(start..=end);
+            }
+            if ra.end() < rb.end() {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+        RangeSet { ranges }
+    }
+
+    pub fn difference(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut difference = self.clone();
+        for r in &other.ranges {
+            difference.remove(r.clone());
+        }
+        difference
+    }
+
+    /// Ranges of the values in `within` that are not in the set
+    pub fn gaps(&self, within: RangeInclusive<T>) -> RangeSet<T> {
+        RangeSet::from(within).difference(self)
+    }
+}
+
+impl<T: Coordinate> From<RangeInclusive<T>> for RangeSet<T> {
+    fn from(range: RangeInclusive<T>) -> Self {
+        let mut set = RangeSet::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl<T: Coordinate> Extend<RangeInclusive<T>> for RangeSet<T> {
+    fn extend<I: IntoIterator<Item = RangeInclusive<T>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+impl<T: Coordinate> FromIterator<RangeInclusive<T>> for RangeSet<T> {
+    /// Sorts the ranges and merges them in one pass, which is quicker than inserting each
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        let mut sorted = iter
+            .into_iter()
+            .filter(|r| !r.is_empty())
+            .collect::<Vec<_>>();
+        sorted.sort_unstable_by_key(|r| *r.start());
+
+        let mut ranges: Vec<RangeInclusive<T>> = Vec::with_capacity(sorted.len());
+        for range in sorted {
+            match ranges.last_mut() {
+                Some(last) if *range.start() <= *last.end() + T::ONE => {
+                    if range.end() > last.end() {
+                        *last = *last.start()..=*range.end();
+                    }
+                }
+                _ => ranges.push(range),
+            }
+        }
+        RangeSet { ranges }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[RangeInclusive<i64>]) -> RangeSet {
+        ranges.iter().cloned().collect()
+    }
+
+    #[test]
+    fn insert_test() {
+        let mut s = set(&[10..=12, 1..=3]);
+        assert_eq!(s.ranges(), &[1..=3, 10..=12]);
+        s.insert(4..=5);
+        assert_eq!(s.ranges(), &[1..=5, 10..=12]);
+        s.insert(7..=8);
+        assert_eq!(s.ranges(), &[1..=5, 7..=8, 10..=12]);
+        s.insert(0..=11);
+        assert_eq!(s.ranges(), &[0..=12]);
+        #[allow(clippy::reversed_empty_ranges)]
+        s.insert(20..=19);
+        assert_eq!(s.ranges(), &[0..=12]);
+    }
+
+    #[test]
+    fn remove_test() {
+        let mut s = set(&[1..=10, 20..=30]);
+        s.remove(4..=6);
+        assert_eq!(s.ranges(), &[1..=3, 7..=10, 20..=30]);
+        s.remove(8..=25);
+        assert_eq!(s.ranges(), &[1..=3, 7..=7, 26..=30]);
+        s.remove(0..=100);
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn contains_test() {
+        let s = set(&[1..=3, 7..=9]);
+        assert!(s.contains(1) && s.contains(8));
+        assert!(!s.contains(0) && !s.contains(5) && !s.contains(10));
+        assert!(s.contains_range(&(7..=9)));
+        assert!(!s.contains_range(&(3..=7)));
+        assert!(s.overlaps(&(3..=7)));
+        assert!(!s.overlaps(&(4..=6)));
+    }
+
+    #[test]
+    fn set_operations_test() {
+        let a = set(&[1..=5, 10..=15]);
+        let b = set(&[4..=11, 20..=20]);
+        assert_eq!(a.union(&b).ranges(), &[1..=15, 20..=20]);
+        assert_eq!(a.intersection(&b).ranges(), &[4..=5, 10..=11]);
+        assert_eq!(a.difference(&b).ranges(), &[1..=3, 12..=15]);
+        assert_eq!(b.difference(&a).ranges(), &[6..=9, 20..=20]);
+    }
+
+    #[test]
+    fn len_and_gaps_test() {
+        let s = set(&[-2..=2, 5..=6]);
+        assert_eq!(s.len(), 7);
+        assert_eq!(s.gaps(0..=10).ranges(), &[3..=4, 7..=10]);
+        assert_eq!(RangeSet::<i32>::new().len(), 0);
+    }
+}
@@ -0,0 +1,287 @@
+//! Solving every day at once, for a picture of which parts work and how long they take
+
+use std::{
+    io::{self, Write},
+    thread,
+    time::{Duration, Instant},
+};
+
+use eyre::{eyre, Result};
+use serde_json::json;
+
+use crate::{bench::Human, Input, Part, Puzzles};
+
+/// What solving one part of one day gave
+#[derive(Debug)]
+pub struct Outcome {
+    pub day: u8,
+    pub part: Part,
+    /// The answer, without trailing newlines, or why there is none
+    pub answer: Result<String>,
+    /// Time spent solving, not counting reading the input
+    pub time: Duration,
+}
+
+/// Outcomes of every part that was solved, in order of day and part
+#[derive(Debug)]
+pub struct Report {
+    pub outcomes: Vec<Outcome>,
+    /// Time from starting the first part to finishing the last
+    pub wall: Duration,
+}
+
+impl Report {
+    pub fn failures(&self) -> usize {
+        self.outcomes.iter().filter(|o| o.answer.is_err()).count()
+    }
+}
+
+fn solve<P>(
+    puzzles: &Puzzles<P>,
+    day: u8,
+    part: Part,
+    input: &Result<Input>,
+    params: &P,
+) -> Outcome {
+    let mut time = Duration::ZERO;
+    let mut answer = || {
+        let solution = puzzles
+            .solution(day, params)
+            .ok_or_else(|| eyre!("no solution for day {day}"))?;
+        let input = input.as_ref().map_err(|e| eyre!("{e:#}"))?;
+        let start = Instant::now();
+        let answer = solution.solve(part, input);
+        time = start.elapsed();
+        Ok(answer?.to_string().trim_end_matches('\n').to_string())
+    };
+    Outcome {
+        day,
+        part,
+        answer: answer(),
+        time,
+    }
+}
+
+/// Solves the parts of the days, each on its own thread, with each day's input read once by
+/// `read_input` before any of them start. A part that panics is reported as an error.
+pub fn run_all<P: Sync>(
+    puzzles: &Puzzles<P>,
+    days: &[u8],
+    parts: &[Part],
+    read_input: impl Fn(u8) -> Result<Input>,
+    params: &P,
+) -> Report {
+    let inputs = days.iter().map(|&day| read_input(day)).collect::<Vec<_>>();
+    let start = Instant::now();
+    let outcomes = thread::scope(|s| {
+        let handles = days
+            .iter()
+            .zip(&inputs)
+            .flat_map(|(&day, input)| parts.iter().map(move |&part| (day, part, input)))
+            .map(|(day, part, input)| {
+                (
+                    day,
+                    part,
+                    s.spawn(move || solve(puzzles, day, part, input, params)),
+                )
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|(day, part, handle)| {
+                handle.join().unwrap_or_else(|_| Outcome {
+                    day,
+                    part,
+                    answer: Err(eyre!("panicked")),
+                    time: Duration::ZERO,
+                })
+            })
+            .collect()
+    });
+    Report {
+        outcomes,
+        wall: start.elapsed(),
+    }
+}
+
+/// Writes the report as an aligned table for reading. Answers spanning several lines start on
+/// the line after their part's.
+pub fn write_table(mut w: impl Write, report: &Report) -> io::Result<()> {
+    writeln!(w, "{:>3} {:>4} {:>9}  answer", "day", "part", "time")?;
+    for o in &report.outcomes {
+        write!(w, "{:>3} {:>4} {:>9}", o.day, o.part, Human(o.time))?;
+        match &o.answer {
+            Ok(answer) if answer.contains('\n') => {
+                writeln!(w)?;
+                for line in answer.lines() {
+                    writeln!(w, "    {line}")?;
+                }
+            }
+            Ok(answer) => writeln!(w, "  {answer}")?,
+            Err(e) => writeln!(w, "  error: {e:#}")?,
+        }
+    }
+    writeln!(
+        w,
+        "{} parts in {}, {} failed",
+        report.outcomes.len(),
+        Human(report.wall),
+        report.failures()
+    )
+}
+
+/// Writes the report as a JSON document, with times in nanoseconds, for comparing runs with
+/// other tools
+pub fn write_json(mut w: impl Write, report: &Report) -> io::Result<()> {
+    let outcomes = report
+        .outcomes
+        .iter()
+        .map(|o| {
+            let (answer, error) = match &o.answer {
+                Ok(answer) => (Some(answer.clone()), None),
+                Err(e) => (None, Some(format!("{e:#}"))),
+            };
+            let part = match o.part {
+                Part::One => 1,
+                Part::Two => 2,
+            };
+            json!({
+                "day": o.day,
+                "part": part,
+                "answer": answer,
+                "error": error,
+                "time_ns": o.time.as_nanos() as u64,
+            })
+        })
+        .collect::<Vec<_>>();
+    let document = json!({
+        "wall_ns": report.wall.as_nanos() as u64,
+        "failures": report.failures(),
+        "outcomes": outcomes,
+    });
+    writeln!(w, "{document:#}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{test_support::temp_dir, Answer, NoParams, Solution};
+
+    /// Answers part 1 with the input's length and panics on part 2
+    struct Length;
+
+    impl Solution for Length {
+        fn part1(&self, input: &Input) -> Result<Answer> {
+            Ok(Box::new(input.len()))
+        }
+
+        fn part2(&self, _input: &Input) -> Result<Answer> {
+            panic!("no part 2")
+        }
+    }
+
+    const PUZZLES: Puzzles<NoParams> = Puzzles {
+        year: 2015,
+        days: 1..=1,
+        solution: |day, _| (day == 1).then(|| Box::new(Length) as Box<dyn Solution>),
+    };
+
+    #[test]
+    fn run_all_test() {
+        let inputs = temp_dir("run-all");
+        fs::create_dir_all(&inputs).unwrap();
+        fs::write(inputs.join("day1"), "abc\n").unwrap();
+
+        let read_input = |day| Input::from_path(inputs.join(format!("day{day}")));
+        let report = run_all(&PUZZLES, &[1, 2], &Part::BOTH, read_input, &NoParams);
+        let answers = report
+            .outcomes
+            .iter()
+            .map(|o| match &o.answer {
+                Ok(answer) => answer.clone(),
+                Err(e) => format!("error: {e}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            answers,
+            [
+                "3",
+                "error: panicked",
+                "error: no solution for day 2",
+                "error: no solution for day 2"
+            ]
+        );
+        assert_eq!(report.failures(), 3);
+        fs::remove_dir_all(inputs).unwrap();
+    }
+
+    #[test]
+    fn write_table_test() {
+        let outcome = |part, answer: &str| Outcome {
+            day: 10,
+            part,
+            answer: Ok(answer.to_string()),
+            time: Duration::from_micros(1500),
+        };
+        let report = Report {
+            outcomes: vec![
+                outcome(Part::One, "13140"),
+                outcome(Part::Two, "##..\n..##"),
+            ],
+            wall: Duration::from_millis(2),
+        };
+        let mut out = vec![];
+        write_table(&mut out, &report).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\
+day part      time  answer
+ 10    1    1.50ms  13140
+ 10    2    1.50ms
+    ##..
+    ..##
+2 parts in 2.00ms, 0 failed
+"
+        );
+    }
+
+    #[test]
+    fn write_json_test() {
+        let report = Report {
+            outcomes: vec![
+                Outcome {
+                    day: 1,
+                    part: Part::Two,
+                    answer: Ok("207968".to_string()),
+                    time: Duration::from_micros(1500),
+                },
+                Outcome {
+                    day: 26,
+                    part: Part::One,
+                    answer: Err(eyre!("no solution for day 26")),
+                    time: Duration::ZERO,
+                },
+            ],
+            wall: Duration::from_millis(2),
+        };
+        let mut out = vec![];
+        write_json(&mut out, &report).unwrap();
+        let json = serde_json::from_slice::<Value>(&out).unwrap();
+        assert_eq!(json["failures"], 1);
+        assert_eq!(json["wall_ns"], 2_000_000);
+
+        let outcomes = json["outcomes"].as_array().unwrap();
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(outcomes[0]["day"], 1);
+        assert_eq!(outcomes[0]["part"], 2);
+        assert_eq!(outcomes[0]["answer"], "207968");
+        assert_eq!(outcomes[0]["error"], Value::Null);
+        assert_eq!(outcomes[0]["time_ns"], 1_500_000);
+        assert_eq!(outcomes[1]["answer"], Value::Null);
+        assert_eq!(outcomes[1]["error"], "no solution for day 26");
+    }
+}
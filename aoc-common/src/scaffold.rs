@@ -0,0 +1,439 @@
+//! Starting points for new days and years, laid out the way the existing ones are
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use eyre::{eyre, Result, WrapErr};
+
+/// Module for day `{day}`, with a parser, a function for each part and a test that is ignored
+/// until the day is solved
+const DAY_TEMPLATE: &str = r#"use eyre::{eyre, Result};
+use nom::character::complete as cc;
+
+use crate::{
+    parse::{lines_of, parse_all, IResult, ParseError},
+    Answer, Input, Solution,
+};
+
+fn parse_line(i: &str) -> IResult<'_, i64> {
+    cc::i64(i)
+}
+
+fn parse_input(input: &Input) -> Result<Vec<i64>, ParseError> {
+    parse_all(input, lines_of(parse_line))
+}
+
+fn day{day}(input: &Input) -> Result<i64> {
+    parse_input(input)?;
+    Err(eyre!("part 1 is not solved yet"))
+}
+
+fn day{day}_part2(input: &Input) -> Result<i64> {
+    parse_input(input)?;
+    Err(eyre!("part 2 is not solved yet"))
+}
+
+pub struct Day{day};
+
+impl Solution for Day{day} {
+    fn part1(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day{day}(input)?))
+    }
+
+    fn part2(&self, input: &Input) -> Result<Answer> {
+        Ok(Box::new(day{day}_part2(input)?))
+    }
+
+    fn parse(&self, input: &Input) -> Result<()> {
+        parse_input(input)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[ignore = "day {day} is not solved yet"]
+    fn day{day}_test() {
+        let input = Input::from_path("./testdata/day{day}").unwrap();
+        assert_eq!(day{day}(&input).unwrap(), 0);
+    }
+
+    #[test]
+    fn parse_input_test() {
+        assert_eq!(parse_input(&Input::new("1\n-2\n")), Ok(vec![1, -2]));
+    }
+}
+"#;
+
+/// Library of year `{year}`, which registers its days with the shared runner
+const LIB_TEMPLATE: &str = r#"#![allow(dead_code)]
+use std::ops::RangeInclusive;
+
+pub use aoc_common::{parse, Answer, Input, NoParams, Part, Puzzles, Solution};
+
+/// Year of the puzzles solved here
+pub const YEAR: u16 = {year};
+
+/// Days that have a solution
+pub const DAYS: RangeInclusive<u8> = 1..=0;
+
+/// This year's solutions, as the runner finds them
+pub const PUZZLES: Puzzles<NoParams> = Puzzles {
+    year: YEAR,
+    days: DAYS,
+    solution,
+};
+
+/// Returns the solution for the given day, if there is one
+pub fn solution(day: u8, _params: &NoParams) -> Option<Box<dyn Solution>> {
+    let solution: Box<dyn Solution> = match day {
+        _ => return None,
+    };
+
+    Some(solution)
+}
+"#;
+
+const CARGO_TEMPLATE: &str = r#"[package]
+name = "adventofcode_{year}"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+aoc-common = { path = "{shared}" }
+eyre = "0.6.8"
+itertools = "0.10.5"
+nom = "7.1.1"
+
+[lib]
+doctest = false
+"#;
+
+const ANSWERS_TEMPLATE: &str =
+    "# Expected answers for the inputs in testdata/, checked with `aoc verify`.
+#
+# <file> <part> <answer> [<key>=<value>]...
+";
+
+/// Answer in a new manifest entry, which no solution gives, so the entry fails until it is
+/// replaced with the real answer
+pub const PLACEHOLDER_ANSWER: &str = "TODO";
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).wrap_err_with(|| format!("could not read {}", path.display()))
+}
+
+fn write(path: &Path, text: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, text).wrap_err_with(|| format!("could not write {}", path.display()))
+}
+
+/// Adds day `day` to a library's module declarations, `solution` match and `DAYS` range
+pub fn register(lib: &str, day: u8) -> Result<String> {
+    let module = format!("mod day{day};");
+    if lib.lines().any(|l| l == module) {
+        return Err(eyre!("day {day} is already registered"));
+    }
+
+    let mut lines = lib.lines().map(str::to_string).collect::<Vec<_>>();
+
+    // Modules are declared in the order rustfmt keeps them in, which sorts day10 before day2
+    let name = format!("day{day}");
+    let modules = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, l)| Some((idx, l.strip_prefix("mod ")?.strip_suffix(';')?)))
+        .filter(|(_, m)| m.starts_with("day"))
+        .map(|(idx, m)| (idx, m.to_string()))
+        .collect::<Vec<_>>();
+    let at = match modules.iter().find(|(_, m)| *m > name) {
+        Some(&(idx, _)) => idx,
+        None => match modules.last() {
+            Some(&(idx, _)) => idx + 1,
+            // No days yet, so the first goes in a paragraph of its own after the imports
+            None => {
+                let imports = lines
+                    .iter()
+                    .rposition(|l| l.starts_with("use ") || l.starts_with("pub use "))
+                    .ok_or_else(|| eyre!("no place for module declarations"))?;
+                lines.insert(imports + 1, String::new());
+                imports + 2
+            }
+        },
+    };
+    lines.insert(at, module);
+
+    let start = lines
+        .iter()
+        .position(|l| l.starts_with("pub fn solution("))
+        .ok_or_else(|| eyre!("no `solution` function"))?;
+    let fallback = lines[start..]
+        .iter()
+        .position(|l| l.trim() == "_ => return None,")
+        .ok_or_else(|| eyre!("no `_ => return None` arm in `solution`"))?;
+    let indent = &lines[start + fallback][..lines[start + fallback].find('_').unwrap()];
+    let arm = format!("{indent}{day} => Box::new(day{day}::Day{day}),");
+    lines.insert(start + fallback, arm);
+
+    let days = lines
+        .iter_mut()
+        .find(|l| l.starts_with("pub const DAYS: RangeInclusive<u8> = "))
+        .ok_or_else(|| eyre!("no `DAYS` range"))?;
+    let (start, end) = days
+        .trim_start_matches("pub const DAYS: RangeInclusive<u8> = ")
+        .trim_end_matches(';')
+        .split_once("..=")
+        .and_then(|(start, end)| Some((start.parse::<u8>().ok()?, end.parse::<u8>().ok()?)))
+        .ok_or_else(|| eyre!("`DAYS` is not a range like 1..=15"))?;
+    *days = format!(
+        "pub const DAYS: RangeInclusive<u8> = {}..={};",
+        start.min(day),
+        end.max(day)
+    );
+
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Adds day `day` to the crate at `root`: its module, an empty input file and a manifest entry
+/// with a placeholder answer. Returns the files that were created or changed.
+pub fn new_day(root: &Path, day: u8) -> Result<Vec<PathBuf>> {
+    if !(1..=25).contains(&day) {
+        return Err(eyre!("day must be from 1 to 25, got {day}"));
+    }
+    let module = root.join("src").join(format!("day{day}.rs"));
+    if module.exists() {
+        return Err(eyre!("{} already exists", module.display()));
+    }
+
+    let lib = root.join("src").join("lib.rs");
+    let registered = register(&read(&lib)?, day)?;
+    write(&module, &DAY_TEMPLATE.replace("{day}", &day.to_string()))?;
+    write(&lib, &registered)?;
+    let mut changed = vec![module, lib];
+
+    // An input that is already there is kept
+    let input = root.join("testdata").join(format!("day{day}"));
+    if !input.exists() {
+        write(&input, "")?;
+        changed.push(input);
+    }
+
+    let answers = root.join("answers.txt");
+    let mut manifest = if answers.exists() {
+        read(&answers)?
+    } else {
+        ANSWERS_TEMPLATE.to_string()
+    };
+    if !manifest.ends_with('\n') {
+        manifest.push('\n');
+    }
+    manifest.push_str(&format!("\nday{day} 1 {PLACEHOLDER_ANSWER}\n"));
+    write(&answers, &manifest)?;
+    changed.push(answers);
+
+    Ok(changed)
+}
+
+/// Path from `dir` to `target`, relative when they share a parent directory
+fn path_from(dir: &Path, target: &Path) -> Result<PathBuf> {
+    let dir = dir.canonicalize()?;
+    let target = target.canonicalize()?;
+    match (dir.parent(), target.parent(), target.file_name()) {
+        (Some(a), Some(b), Some(name)) if a == b => Ok(Path::new("..").join(name)),
+        _ => Ok(target),
+    }
+}
+
+/// Adds `member` to the one-line `members` list of a workspace manifest, keeping it sorted
+pub fn add_member(manifest: &str, member: &str) -> Result<String> {
+    let mut lines = manifest.lines().map(str::to_string).collect::<Vec<_>>();
+    let line = lines
+        .iter_mut()
+        .find(|l| l.starts_with("members = ["))
+        .ok_or_else(|| eyre!("no `members` list in the workspace"))?;
+    let mut members = line
+        .trim_start_matches("members = [")
+        .strip_suffix(']')
+        .ok_or_else(|| eyre!("`members` is not a list on one line"))?
+        .split(',')
+        .map(|m| m.trim().trim_matches('"'))
+        .filter(|m| !m.is_empty())
+        .collect::<Vec<_>>();
+    if members.contains(&member) {
+        return Err(eyre!("{member} is already a member of the workspace"));
+    }
+    members.push(member);
+    members.sort_unstable();
+
+    let members = members
+        .iter()
+        .map(|m| format!("\"{m}\""))
+        .collect::<Vec<_>>();
+    *line = format!("members = [{}]", members.join(", "));
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Creates a crate for `year` at `root`, with its first day, that uses the shared crate at
+/// `shared`. If the directory above `root` is a workspace, the crate is added to it. Returns the
+/// files that were created or changed.
+pub fn new_year(root: &Path, year: u16, shared: &Path) -> Result<Vec<PathBuf>> {
+    if root.exists() && fs::read_dir(root)?.next().is_some() {
+        return Err(eyre!("{} already exists and is not empty", root.display()));
+    }
+    fs::create_dir_all(root)?;
+    let shared = path_from(root, shared)?;
+
+    let year = year.to_string();
+    let cargo = root.join("Cargo.toml");
+    let shared = shared.to_str().ok_or_else(|| eyre!("path is not UTF-8"))?;
+    write(
+        &cargo,
+        &CARGO_TEMPLATE
+            .replace("{year}", &year)
+            .replace("{shared}", shared),
+    )?;
+    let lib = root.join("src").join("lib.rs");
+    write(&lib, &LIB_TEMPLATE.replace("{year}", &year))?;
+    let gitignore = root.join(".gitignore");
+    write(&gitignore, "/target\n")?;
+
+    let mut created = vec![cargo, gitignore];
+    created.extend(new_day(root, 1)?.into_iter().filter(|p| *p != lib));
+    created.push(lib);
+
+    if let (Some(parent), Some(name)) = (root.parent(), root.file_name()) {
+        let workspace = parent.join("Cargo.toml");
+        if workspace.exists() && read(&workspace)?.lines().any(|l| l == "[workspace]") {
+            let name = name.to_str().ok_or_else(|| eyre!("path is not UTF-8"))?;
+            write(&workspace, &add_member(&read(&workspace)?, name)?)?;
+            created.push(workspace);
+        }
+    }
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_dir;
+
+    const LIB: &str = "\
+use std::ops::RangeInclusive;
+
+mod day1;
+mod day10;
+mod day2;
+pub mod grid;
+
+pub const DAYS: RangeInclusive<u8> = 1..=10;
+
+pub fn solution(day: u8) -> Option<Box<dyn Solution>> {
+    let solution: Box<dyn Solution> = match day {
+        1 => Box::new(day1::Day1),
+        _ => return None,
+    };
+}
+";
+
+    #[test]
+    fn register_test() {
+        let lib = register(LIB, 3).unwrap();
+        assert!(lib.contains("mod day2;\nmod day3;\npub mod grid;"));
+        assert!(lib.contains(
+            "        1 => Box::new(day1::Day1),\n        3 => Box::new(day3::Day3),\n        _ => return None,"
+        ));
+        assert!(lib.contains("= 1..=10;"));
+
+        let lib = register(&lib, 11).unwrap();
+        assert!(lib.contains("mod day10;\nmod day11;\nmod day2;"));
+        assert!(lib.contains("= 1..=11;"));
+        assert!(register(&lib, 11).is_err());
+    }
+
+    #[test]
+    fn register_first_day_test() {
+        let lib = LIB_TEMPLATE.replace("{year}", "2023");
+        let lib = register(&lib, 1).unwrap();
+        assert!(lib.contains(
+            "pub use aoc_common::{parse, Answer, Input, NoParams, Part, Puzzles, Solution};\n\nmod day1;\n"
+        ));
+        assert!(lib.contains("= 1..=1;"));
+        assert!(lib.contains("        1 => Box::new(day1::Day1),\n        _ => return None,"));
+    }
+
+    #[test]
+    fn new_day_test() {
+        let root = temp_dir("new-day");
+        write(&root.join("src").join("lib.rs"), LIB).unwrap();
+        write(&root.join("testdata").join("day4"), "1\n2\n").unwrap();
+        write(&root.join("answers.txt"), "day1 1 5").unwrap();
+
+        let changed = new_day(&root, 4).unwrap();
+        assert_eq!(changed.len(), 3);
+        let module = read(&root.join("src").join("day4.rs")).unwrap();
+        assert!(module.contains("fn day4_part2(input: &Input) -> Result<i64> {"));
+        assert!(module.contains("impl Solution for Day4 {"));
+        assert!(!module.contains("{day}"));
+        assert_eq!(read(&root.join("testdata").join("day4")).unwrap(), "1\n2\n");
+        assert_eq!(
+            read(&root.join("answers.txt")).unwrap(),
+            "day1 1 5\n\nday4 1 TODO\n"
+        );
+        assert!(new_day(&root, 4).is_err());
+
+        new_day(&root, 12).unwrap();
+        assert_eq!(read(&root.join("testdata").join("day12")).unwrap(), "");
+        assert!(read(&root.join("src").join("lib.rs"))
+            .unwrap()
+            .contains("= 1..=12;"));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn new_year_test() {
+        let parent = temp_dir("new-year");
+        fs::create_dir_all(parent.join("aoc-common")).unwrap();
+        let workspace = "[workspace]\nmembers = [\"2022\", \"aoc-common\"]\n";
+        write(&parent.join("Cargo.toml"), workspace).unwrap();
+        let root = parent.join("2023");
+        let created = new_year(&root, 2023, &parent.join("aoc-common")).unwrap();
+        assert_eq!(created.last(), Some(&parent.join("Cargo.toml")));
+        assert_eq!(
+            read(&parent.join("Cargo.toml")).unwrap(),
+            "[workspace]\nmembers = [\"2022\", \"2023\", \"aoc-common\"]\n"
+        );
+
+        let cargo = read(&root.join("Cargo.toml")).unwrap();
+        assert!(cargo.contains("name = \"adventofcode_2023\""));
+        assert!(cargo.contains("aoc-common = { path = \"../aoc-common\" }"));
+        let lib = read(&root.join("src").join("lib.rs")).unwrap();
+        assert!(lib.contains("pub const YEAR: u16 = 2023;"));
+        assert!(lib.contains("mod day1;"));
+        assert!(root.join("src").join("day1.rs").exists());
+        assert!(root.join("testdata").join("day1").exists());
+
+        assert!(new_year(&root, 2023, &parent.join("aoc-common")).is_err());
+        fs::remove_dir_all(parent).unwrap();
+    }
+
+    #[test]
+    fn add_member_test() {
+        let manifest = "[workspace]\nmembers = [\"2022\"]\nresolver = \"2\"\n";
+        let manifest = add_member(manifest, "aoc-common").unwrap();
+        let manifest = add_member(&manifest, "2023").unwrap();
+        assert_eq!(
+            manifest,
+            "[workspace]\nmembers = [\"2022\", \"2023\", \"aoc-common\"]\nresolver = \"2\"\n"
+        );
+        assert!(add_member(&manifest, "2023").is_err());
+        assert!(add_member("[workspace]\nmembers = [\n  \"2022\",\n]\n", "2023").is_err());
+        assert!(add_member("[package]\n", "2023").is_err());
+    }
+}
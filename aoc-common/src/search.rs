@@ -0,0 +1,271 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+/// What a search found: the cost of reaching each node it reached, and the path there
+#[derive(Debug, Clone)]
+pub struct Search<N, C> {
+    /// Cost of the cheapest path found to each reached node
+    pub distances: HashMap<N, C>,
+    /// Node before each reached node on the cheapest path found to it. Start nodes have none.
+    pub predecessors: HashMap<N, N>,
+    /// Target the search stopped at, if it reached one
+    pub target: Option<N>,
+}
+
+impl<N: Clone + Eq + Hash, C: Copy> Search<N, C> {
+    pub fn distance(&self, node: &N) -> Option<C> {
+        self.distances.get(node).copied()
+    }
+
+    /// Path from a start node to the node, including both
+    pub fn path_to(&self, node: &N) -> Option<Vec<N>> {
+        if !self.distances.contains_key(node) {
+            return None;
+        }
+
+        let mut path = vec![node.clone()];
+        while let Some(prev) = self.predecessors.get(path.last().unwrap()) {
+            path.push(prev.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Path from a start node to the target the search stopped at
+    pub fn path(&self) -> Option<Vec<N>> {
+        self.path_to(self.target.as_ref()?)
+    }
+}
+
+/// Breadth-first search from every start node at once, where each step costs one. Stops at the
+/// first target reached, which is the closest, or once every reachable node is reached.
+pub fn bfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut is_target: impl FnMut(&N) -> bool,
+) -> Search<N, usize>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut search = Search {
+        distances: HashMap::new(),
+        predecessors: HashMap::new(),
+        target: None,
+    };
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if !search.distances.contains_key(&start) {
+            search.distances.insert(start.clone(), 0);
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(node) = queue.pop_front() {
+        if is_target(&node) {
+            search.target = Some(node);
+            break;
+        }
+
+        let distance = search.distances[&node] + 1;
+        for next in neighbours(&node) {
+            if !search.distances.contains_key(&next) {
+                search.distances.insert(next.clone(), distance);
+                search.predecessors.insert(next.clone(), node.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+
+    search
+}
+
+/// Dijkstra's search from every start node at once, where `neighbours` gives each neighbour
+/// with the cost of stepping to it. Stops at the first target reached, which is the cheapest to
+/// reach, or once every reachable node is reached.
+pub fn dijkstra<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+    is_target: impl FnMut(&N) -> bool,
+) -> Search<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar(starts, neighbours, |_| C::default(), is_target)
+}
+
+/// A* search from every start node at once, where `neighbours` gives each neighbour with the
+/// cost of stepping to it and `heuristic` estimates the cost from a node to the nearest target.
+/// The heuristic must never overestimate that cost for the path found to be the cheapest.
+pub fn astar<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut is_target: impl FnMut(&N) -> bool,
+) -> Search<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut search = Search {
+        distances: HashMap::new(),
+        predecessors: HashMap::new(),
+        target: None,
+    };
+    let mut queue = BinaryHeap::new();
+    for start in starts {
+        let cost = C::default();
+        search.distances.insert(start.clone(), cost);
+        queue.push(Queued {
+            priority: cost + heuristic(&start),
+            cost,
+            node: start,
+        });
+    }
+
+    while let Some(Queued { cost, node, .. }) = queue.pop() {
+        // The node was queued again with a lower cost and has already been visited
+        if cost > search.distances[&node] {
+            continue;
+        }
+        if is_target(&node) {
+            search.target = Some(node);
+            break;
+        }
+
+        for (next, step_cost) in neighbours(&node) {
+            let next_cost = cost + step_cost;
+            if search.distances.get(&next).is_some_and(|&c| c <= next_cost) {
+                continue;
+            }
+            search.distances.insert(next.clone(), next_cost);
+            search.predecessors.insert(next.clone(), node.clone());
+            queue.push(Queued {
+                priority: next_cost + heuristic(&next),
+                cost: next_cost,
+                node: next,
+            });
+        }
+    }
+
+    search
+}
+
+/// Node in the priority queue, ordered so the lowest priority is popped first
+struct Queued<N, C> {
+    priority: C,
+    cost: C,
+    node: N,
+}
+
+impl<N, C: Ord> Ord for Queued<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+impl<N, C: Ord> PartialOrd for Queued<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C: Ord> PartialEq for Queued<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<N, C: Ord> Eq for Queued<N, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{grid::Grid, point::Point};
+
+    /// Neighbours on a line of nodes 0 to 9
+    fn line(n: &i32) -> Vec<i32> {
+        [n - 1, n + 1]
+            .into_iter()
+            .filter(|m| (0..10).contains(m))
+            .collect()
+    }
+
+    #[test]
+    fn bfs_test() {
+        let search = bfs([2], line, |&n| n == 5);
+        assert_eq!(search.target, Some(5));
+        assert_eq!(search.distance(&5), Some(3));
+        assert_eq!(search.path(), Some(vec![2, 3, 4, 5]));
+        assert_eq!(search.path_to(&2), Some(vec![2]));
+    }
+
+    #[test]
+    fn bfs_multi_source_test() {
+        let search = bfs([0, 9], line, |_| false);
+        assert_eq!(search.target, None);
+        assert_eq!(search.distances.len(), 10);
+        assert_eq!(search.distance(&3), Some(3));
+        assert_eq!(search.distance(&7), Some(2));
+        assert_eq!(search.path_to(&7), Some(vec![9, 8, 7]));
+    }
+
+    #[test]
+    fn bfs_multi_target_test() {
+        let search = bfs([4], line, |&n| n == 0 || n == 6);
+        assert_eq!(search.target, Some(6));
+    }
+
+    #[test]
+    fn bfs_unreachable_test() {
+        let search = bfs(
+            [0],
+            |&n: &i32| if n < 3 { vec![n + 1] } else { vec![] },
+            |&n| n == 5,
+        );
+        assert_eq!(search.target, None);
+        assert_eq!(search.path_to(&5), None);
+        assert_eq!(search.distance(&3), Some(3));
+    }
+
+    #[test]
+    fn dijkstra_test() {
+        // The direct edge from a to c costs more than going through b
+        let edges = HashMap::from([
+            ('a', vec![('b', 1), ('c', 5)]),
+            ('b', vec![('c', 2)]),
+            ('c', vec![('d', 1)]),
+            ('d', vec![]),
+        ]);
+        let search = dijkstra(['a'], |n| edges[n].clone(), |&n| n == 'd');
+        assert_eq!(search.distance(&'d'), Some(4));
+        assert_eq!(search.path(), Some(vec!['a', 'b', 'c', 'd']));
+    }
+
+    #[test]
+    fn astar_test() {
+        let grid = Grid::parse(".....\n.###.\n...#.\n.#...", "# or .", |c| Some(c == '#')).unwrap();
+        let to_point = |(x, y)| Point::new(x as i64, y as i64);
+        let end = (4, 3);
+        let search = astar(
+            [(0, 0)],
+            |&pos| {
+                grid.neighbours4(pos)
+                    .filter(|&p| !grid[p])
+                    .map(|p| (p, 1))
+                    .collect::<Vec<_>>()
+            },
+            |&pos| to_point(pos).manhattan(to_point(end)),
+            |&pos| pos == end,
+        );
+        assert_eq!(search.distance(&end), Some(7));
+        assert_eq!(search.path().unwrap().len(), 8);
+    }
+}
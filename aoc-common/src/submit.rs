@@ -0,0 +1,644 @@
+//! Submitting answers to the site, keeping a history of every attempt so that known wrong
+//! answers are not sent again and the site's rate limit is respected
+
+use std::{
+    fmt,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use eyre::{eyre, Result, WrapErr};
+
+use crate::{
+    cache::{Fetch, BASE_URL},
+    parse::ParseError,
+    Input, Part,
+};
+
+/// Which way a wrong answer was off, when the site says
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hint {
+    TooHigh,
+    TooLow,
+}
+
+/// What the site made of an answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    /// Wrong, and no other answer is accepted until `wait` has passed
+    Incorrect {
+        hint: Option<Hint>,
+        wait: Option<Duration>,
+    },
+    /// Not checked, because the previous answer was too recent
+    Wait(Duration),
+    /// Not checked, because the part is already solved or not unlocked yet
+    WrongLevel,
+}
+
+impl Verdict {
+    /// Time until the site takes another answer
+    pub fn wait(&self) -> Option<Duration> {
+        match *self {
+            Verdict::Incorrect { wait, .. } => wait,
+            Verdict::Wait(wait) => Some(wait),
+            _ => None,
+        }
+    }
+
+    /// Name of the verdict in the history file
+    fn name(&self) -> &'static str {
+        match self {
+            Verdict::Correct => "correct",
+            Verdict::Incorrect {
+                hint: Some(Hint::TooHigh),
+                ..
+            } => "too-high",
+            Verdict::Incorrect {
+                hint: Some(Hint::TooLow),
+                ..
+            } => "too-low",
+            Verdict::Incorrect { hint: None, .. } => "incorrect",
+            Verdict::Wait(_) => "wait",
+            Verdict::WrongLevel => "wrong-level",
+        }
+    }
+
+    fn from_name(name: &str, wait: Option<Duration>) -> Option<Verdict> {
+        let incorrect = |hint| Verdict::Incorrect { hint, wait };
+        Some(match name {
+            "correct" => Verdict::Correct,
+            "too-high" => incorrect(Some(Hint::TooHigh)),
+            "too-low" => incorrect(Some(Hint::TooLow)),
+            "incorrect" => incorrect(None),
+            "wait" => Verdict::Wait(wait?),
+            "wrong-level" => Verdict::WrongLevel,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Verdict::Correct => write!(f, "correct")?,
+            Verdict::Incorrect { hint, .. } => {
+                write!(f, "incorrect")?;
+                match hint {
+                    Some(Hint::TooHigh) => write!(f, ", too high")?,
+                    Some(Hint::TooLow) => write!(f, ", too low")?,
+                    None => {}
+                }
+            }
+            Verdict::Wait(_) => write!(f, "not checked, answered too recently")?,
+            Verdict::WrongLevel => write!(f, "not checked, the part is solved or locked")?,
+        }
+        match self.wait() {
+            Some(wait) => write!(f, "; wait {}s before answering again", wait.as_secs()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Text of the page's `<article>`, where the site explains its verdict, without tags
+fn article_text(html: &str) -> String {
+    let article = match (html.find("<article"), html.find("</article>")) {
+        (Some(start), Some(end)) if start < end => &html[start..end],
+        _ => html,
+    };
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in article.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Duration written like `1m 30s` or `45s`
+fn parse_wait(s: &str) -> Option<Duration> {
+    s.split_whitespace()
+        .try_fold(Duration::ZERO, |total, part| {
+            let (n, unit) = part.split_at(part.find(|c: char| !c.is_ascii_digit())?);
+            let n = n.parse::<u64>().ok()?;
+            let secs = match unit {
+                "h" => n * 3600,
+                "m" => n * 60,
+                "s" => n,
+                _ => return None,
+            };
+            Some(total + Duration::from_secs(secs))
+        })
+}
+
+/// Parses the page the site responds to an answer with
+pub fn parse_response(html: &str) -> Result<Verdict> {
+    let text = article_text(html);
+    if text.contains("That's the right answer") {
+        return Ok(Verdict::Correct);
+    }
+    if text.contains("That's not the right answer") {
+        let hint = if text.contains("too high") {
+            Some(Hint::TooHigh)
+        } else if text.contains("too low") {
+            Some(Hint::TooLow)
+        } else {
+            None
+        };
+        // "Please wait one minute before trying again", or "wait 5 minutes"
+        let wait = text
+            .split_once("wait ")
+            .and_then(|(_, rest)| rest.split_once(" minute"))
+            .and_then(|(n, _)| match n {
+                "one" => Some(1),
+                n => n.parse().ok(),
+            })
+            .map(|minutes| Duration::from_secs(minutes * 60));
+        return Ok(Verdict::Incorrect { hint, wait });
+    }
+    if text.contains("You gave an answer too recently") {
+        // "You have 1m 30s left to wait."
+        let wait = text
+            .split_once("You have ")
+            .and_then(|(_, rest)| rest.split_once(" left to wait"))
+            .and_then(|(wait, _)| parse_wait(wait))
+            .ok_or_else(|| eyre!("could not find how long to wait in: {text}"))?;
+        return Ok(Verdict::Wait(wait));
+    }
+    if text.contains("You don't seem to be solving the right level") {
+        return Ok(Verdict::WrongLevel);
+    }
+
+    let start = text.chars().take(200).collect::<String>();
+    Err(eyre!("could not understand the response: {start}"))
+}
+
+/// An answer that was sent, and what came of it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attempt {
+    /// Seconds since the Unix epoch
+    pub time: u64,
+    pub year: u16,
+    pub day: u8,
+    pub part: Part,
+    pub answer: String,
+    pub verdict: Verdict,
+}
+
+impl Attempt {
+    fn is_for(&self, year: u16, day: u8, part: Part) -> bool {
+        (self.year, self.day, self.part) == (year, day, part)
+    }
+}
+
+/// Every attempt made, kept in a file of tab separated lines of the form
+/// `<time> <year> <day> <part> <verdict> <wait seconds> <answer>`, where the wait is `-` if
+/// there is none
+#[derive(Debug)]
+pub struct History {
+    path: PathBuf,
+    attempts: Vec<Attempt>,
+}
+
+fn parse_field<T: FromStr>(line: &str, field: &str, expected: &str) -> Result<T, ParseError> {
+    field
+        .parse()
+        .map_err(|_| ParseError::at(line, field, expected))
+}
+
+fn parse_attempt(line: &str) -> Result<Attempt, ParseError> {
+    let mut fields = line.splitn(7, '\t');
+    let mut next = |expected| {
+        fields
+            .next()
+            .ok_or_else(|| ParseError::at(line, &line[line.len()..], expected))
+    };
+
+    let time = parse_field(line, next("time")?, "time")?;
+    let year = parse_field(line, next("year")?, "year")?;
+    let day = parse_field(line, next("day")?, "day")?;
+    let part = parse_field(line, next("part")?, "part")?;
+    let verdict = next("verdict")?;
+    let wait = match next("wait seconds")? {
+        "-" => None,
+        wait => Some(Duration::from_secs(parse_field(
+            line,
+            wait,
+            "wait seconds",
+        )?)),
+    };
+    let verdict = Verdict::from_name(verdict, wait)
+        .ok_or_else(|| ParseError::at(line, verdict, "verdict"))?;
+    let answer = next("answer")?;
+    Ok(Attempt {
+        time,
+        year,
+        day,
+        part,
+        answer: answer.to_string(),
+        verdict,
+    })
+}
+
+impl History {
+    /// Reads the history file, which need not exist yet
+    pub fn load(path: impl Into<PathBuf>) -> Result<History> {
+        let path = path.into();
+        let attempts = if path.exists() {
+            let input = Input::from_path(&path)?;
+            input
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !line.is_empty())
+                .map(|(idx, line)| parse_attempt(line).map_err(|e| e.offset_lines(idx)))
+                .collect::<Result<Vec<_>, _>>()
+                .wrap_err_with(|| format!("could not parse {}", path.display()))?
+        } else {
+            vec![]
+        };
+        Ok(History { path, attempts })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn attempts(&self) -> &[Attempt] {
+        &self.attempts
+    }
+
+    /// Appends the attempt to the history file
+    pub fn record(&mut self, attempt: Attempt) -> Result<()> {
+        let append = || -> Result<()> {
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            let wait = attempt
+                .verdict
+                .wait()
+                .map_or("-".to_string(), |w| w.as_secs().to_string());
+            writeln!(
+                file,
+                "{}\t{}\t{}\t{}\t{}\t{wait}\t{}",
+                attempt.time,
+                attempt.year,
+                attempt.day,
+                attempt.part,
+                attempt.verdict.name(),
+                attempt.answer
+            )?;
+            Ok(())
+        };
+        append().wrap_err_with(|| format!("could not write to {}", self.path.display()))?;
+        self.attempts.push(attempt);
+        Ok(())
+    }
+
+    /// Seconds since the Unix epoch when the site takes answers for the year again
+    pub fn ready_at(&self, year: u16) -> Option<u64> {
+        self.attempts
+            .iter()
+            .filter(|a| a.year == year)
+            .filter_map(|a| Some(a.time + a.verdict.wait()?.as_secs()))
+            .max()
+    }
+
+    /// Checks the answer against earlier attempts at `now`, refusing answers that cannot be
+    /// right or cannot be checked yet, and returning warnings about answers that look wrong
+    pub fn check(
+        &self,
+        year: u16,
+        day: u8,
+        part: Part,
+        answer: &str,
+        now: u64,
+    ) -> Result<Vec<String>> {
+        if answer.is_empty() || answer.contains(['\t', '\n']) {
+            return Err(eyre!("{answer:?} is not an answer that can be sent"));
+        }
+
+        let attempts = self
+            .attempts
+            .iter()
+            .filter(|a| a.is_for(year, day, part))
+            .collect::<Vec<_>>();
+        if let Some(solved) = attempts.iter().find(|a| a.verdict == Verdict::Correct) {
+            return Err(eyre!(
+                "day {day} part {part} was already solved with {}",
+                solved.answer
+            ));
+        }
+        if let Some(wrong) = attempts
+            .iter()
+            .find(|a| a.answer == answer && matches!(a.verdict, Verdict::Incorrect { .. }))
+        {
+            return Err(eyre!(
+                "{answer} was already sent for day {day} part {part} and was {}",
+                wrong.verdict
+            ));
+        }
+        if let Some(ready) = self.ready_at(year).filter(|&ready| ready > now) {
+            return Err(eyre!("the site takes another answer in {}s", ready - now));
+        }
+
+        let mut warnings = vec![];
+        if let Ok(value) = answer.parse::<i64>() {
+            let bound = |hint| {
+                attempts
+                    .iter()
+                    .filter(move |a| matches!(a.verdict, Verdict::Incorrect { hint: Some(h), .. } if h == hint))
+                    .filter_map(|a| a.answer.parse::<i64>().ok())
+            };
+            if let Some(high) = bound(Hint::TooHigh).min().filter(|&high| value >= high) {
+                warnings.push(format!("{value} is not below {high}, which was too high"));
+            }
+            if let Some(low) = bound(Hint::TooLow).max().filter(|&low| value <= low) {
+                warnings.push(format!("{value} is not above {low}, which was too low"));
+            }
+        }
+        Ok(warnings)
+    }
+}
+
+/// Sends answers to the site
+pub struct Submitter {
+    base_url: String,
+    session: String,
+    client: Box<dyn Fetch>,
+    /// Whether to send answers that the history warns about
+    force: bool,
+}
+
+impl Submitter {
+    pub fn new(client: Box<dyn Fetch>, session: &str) -> Submitter {
+        Submitter {
+            base_url: BASE_URL.to_string(),
+            session: session.to_string(),
+            client,
+            force: false,
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: &str) -> Submitter {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Sends answers that the history warns about instead of refusing them
+    pub fn with_force(mut self, force: bool) -> Submitter {
+        self.force = force;
+        self
+    }
+
+    /// Sends the answer at `now` unless the history says not to, and records the attempt. An
+    /// answer the history warns about is refused unless forced, so it costs no attempt. Returns
+    /// the site's verdict and the warnings that were forced past.
+    pub fn submit(
+        &self,
+        history: &mut History,
+        year: u16,
+        day: u8,
+        part: Part,
+        answer: &str,
+        now: u64,
+    ) -> Result<(Verdict, Vec<String>)> {
+        let warnings = history.check(year, day, part, answer, now)?;
+        if !warnings.is_empty() && !self.force {
+            return Err(eyre!(
+                "not sending {answer} unless forced: {}",
+                warnings.join("; ")
+            ));
+        }
+        let url = format!("{}/{year}/day/{day}/answer", self.base_url);
+        let level = part.to_string();
+        let page = self.client.post(
+            &url,
+            &self.session,
+            &[("level", &level), ("answer", answer)],
+        )?;
+        let verdict = parse_response(&page)?;
+        history.record(Attempt {
+            time: now,
+            year,
+            day,
+            part,
+            answer: answer.to_string(),
+            verdict,
+        })?;
+        Ok((verdict, warnings))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cache::Http,
+        test_support::{serve_once, temp_dir},
+    };
+
+    fn page(article: &str) -> String {
+        format!(
+            "<!DOCTYPE html>\n<html><main>\n<article><p>{article}</p></article>\n</main></html>"
+        )
+    }
+
+    const TOO_HIGH: &str = "That's not the right answer; your answer is too high.  If you're \
+        stuck, make sure you're using the full input data. Please wait one minute before trying \
+        again. <a href=\"/2022/day/1\">[Return to Day 1]</a>";
+
+    #[test]
+    fn parse_response_test() {
+        assert_eq!(
+            parse_response(&page(
+                "That's the right answer!  You are <em>one gold star</em> closer."
+            ))
+            .unwrap(),
+            Verdict::Correct
+        );
+        assert_eq!(
+            parse_response(&page(TOO_HIGH)).unwrap(),
+            Verdict::Incorrect {
+                hint: Some(Hint::TooHigh),
+                wait: Some(Duration::from_secs(60))
+            }
+        );
+        assert_eq!(
+            parse_response(&page("That's not the right answer; your answer is too low.  Please wait 5 minutes before trying again.")).unwrap(),
+            Verdict::Incorrect {
+                hint: Some(Hint::TooLow),
+                wait: Some(Duration::from_secs(300))
+            }
+        );
+        assert_eq!(
+            parse_response(&page("You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 5s left to wait.")).unwrap(),
+            Verdict::Wait(Duration::from_secs(65))
+        );
+        assert_eq!(
+            parse_response(&page(
+                "You don't seem to be solving the right level.  Did you already complete it?"
+            ))
+            .unwrap(),
+            Verdict::WrongLevel
+        );
+        assert!(parse_response(&page("Something else")).is_err());
+    }
+
+    #[test]
+    fn parse_wait_test() {
+        assert_eq!(parse_wait("45s"), Some(Duration::from_secs(45)));
+        assert_eq!(parse_wait("1h 2m 3s"), Some(Duration::from_secs(3723)));
+        assert_eq!(parse_wait("soon"), None);
+    }
+
+    fn attempt(time: u64, part: Part, answer: &str, verdict: Verdict) -> Attempt {
+        Attempt {
+            time,
+            year: 2022,
+            day: 1,
+            part,
+            answer: answer.to_string(),
+            verdict,
+        }
+    }
+
+    #[test]
+    fn history_test() {
+        let dir = temp_dir("history");
+        let path = dir.join("submissions");
+        let mut history = History::load(&path).unwrap();
+        assert!(history.attempts().is_empty());
+
+        let too_low = Verdict::Incorrect {
+            hint: Some(Hint::TooLow),
+            wait: Some(Duration::from_secs(60)),
+        };
+        let too_high = Verdict::Incorrect {
+            hint: Some(Hint::TooHigh),
+            wait: None,
+        };
+        history
+            .record(attempt(1000, Part::One, "10", too_low))
+            .unwrap();
+        history
+            .record(attempt(1100, Part::One, "50", too_high))
+            .unwrap();
+        history
+            .record(attempt(1200, Part::Two, "abc", Verdict::Correct))
+            .unwrap();
+
+        let reloaded = History::load(&path).unwrap();
+        assert_eq!(reloaded.attempts(), history.attempts());
+        assert_eq!(reloaded.ready_at(2022), Some(1060));
+
+        // Within the minute after the first wrong answer
+        assert!(history.check(2022, 1, Part::One, "20", 1030).is_err());
+        assert_eq!(
+            history.check(2022, 1, Part::One, "20", 2000).unwrap(),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            history.check(2022, 1, Part::One, "60", 2000).unwrap(),
+            vec!["60 is not below 50, which was too high"]
+        );
+        assert_eq!(
+            history.check(2022, 1, Part::One, "5", 2000).unwrap(),
+            vec!["5 is not above 10, which was too low"]
+        );
+        let error = history.check(2022, 1, Part::One, "10", 2000).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "10 was already sent for day 1 part 1 and was incorrect, too low; wait 60s before answering again"
+        );
+        assert!(history.check(2022, 1, Part::Two, "def", 2000).is_err());
+        assert!(history.check(2022, 2, Part::One, "10", 2000).is_ok());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn history_parse_error_test() {
+        let dir = temp_dir("history-error");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("submissions");
+        fs::write(
+            &path,
+            "1000\t2022\t1\t1\tcorrect\t-\t42\n1000\t2022\t1\t3\tcorrect\t-\t42\n",
+        )
+        .unwrap();
+        let error = History::load(&path).unwrap_err();
+        assert_eq!(
+            error.root_cause().to_string(),
+            ParseError::new(2, 13, "part", "3").to_string()
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn submit_test() {
+        let dir = temp_dir("submit");
+        let mut history = History::load(dir.join("submissions")).unwrap();
+        let (url, server) = serve_once(200, &page(TOO_HIGH));
+        let submitter = Submitter::new(Box::new(Http::new()), "abc").with_base_url(&url);
+
+        let (verdict, warnings) = submitter
+            .submit(&mut history, 2022, 1, Part::Two, "1234", 5000)
+            .unwrap();
+        assert_eq!(verdict.wait(), Some(Duration::from_secs(60)));
+        assert!(warnings.is_empty());
+
+        let request = server.join().unwrap();
+        assert_eq!(
+            (request.method.as_str(), request.url.as_str()),
+            ("POST", "/2022/day/1/answer")
+        );
+        assert_eq!(request.cookie, "session=abc");
+        assert_eq!(request.body, "level=2&answer=1234");
+        assert_eq!(
+            history.attempts()[0],
+            attempt(5000, Part::Two, "1234", verdict)
+        );
+
+        // Refused without reaching the server, which has stopped
+        assert!(submitter
+            .submit(&mut history, 2022, 1, Part::Two, "1234", 9000)
+            .is_err());
+        assert!(submitter
+            .submit(&mut history, 2022, 1, Part::Two, "1000", 5010)
+            .is_err());
+        assert_eq!(history.attempts().len(), 1);
+
+        // Once the wait is over, an answer the site would call too high is still refused
+        let error = submitter
+            .submit(&mut history, 2022, 1, Part::Two, "2000", 9000)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "not sending 2000 unless forced: 2000 is not below 1234, which was too high"
+        );
+        assert_eq!(history.attempts().len(), 1);
+
+        let (url, server) = serve_once(200, &page(TOO_HIGH));
+        let submitter = Submitter::new(Box::new(Http::new()), "abc")
+            .with_base_url(&url)
+            .with_force(true);
+        let (_, warnings) = submitter
+            .submit(&mut history, 2022, 1, Part::Two, "2000", 9000)
+            .unwrap();
+        assert_eq!(warnings, ["2000 is not below 1234, which was too high"]);
+        assert_eq!(server.join().unwrap().body, "level=2&answer=2000");
+        assert_eq!(history.attempts().len(), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
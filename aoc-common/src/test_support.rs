@@ -0,0 +1,51 @@
+//! Helpers for tests that touch the file system or talk to the site
+
+use std::{env, fs, path::PathBuf, process, thread};
+
+use tiny_http::{Response, Server};
+
+/// An empty directory for one test, unique to the test process
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("aoc-{}-{name}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+/// What a stand-in server was sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub url: String,
+    pub cookie: String,
+    pub body: String,
+}
+
+/// Serves one request with the status and body on a local port, returning the server's URL and
+/// a handle that gives the request it was sent
+pub fn serve_once(status: u16, body: &str) -> (String, thread::JoinHandle<Request>) {
+    let body = body.to_string();
+    let server = Server::http("127.0.0.1:0").unwrap();
+    let port = server.server_addr().to_ip().unwrap().port();
+    let handle = thread::spawn(move || {
+        let mut request = server.recv().unwrap();
+        let cookie = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("Cookie"))
+            .map(|h| h.value.to_string())
+            .unwrap_or_default();
+        let mut sent = String::new();
+        request.as_reader().read_to_string(&mut sent).unwrap();
+        let received = Request {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            cookie,
+            body: sent,
+        };
+        request
+            .respond(Response::from_string(body).with_status_code(status))
+            .unwrap();
+        received
+    });
+    (format!("http://127.0.0.1:{port}"), handle)
+}
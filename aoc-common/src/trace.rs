@@ -0,0 +1,253 @@
+//! Diagnostics that explain how a day reaches its answer. Nothing is written unless tracing is
+//! enabled for the day, either on stderr with [`enable`] or into a string with [`capture`].
+
+use std::{
+    cell::RefCell,
+    fmt,
+    io::Write,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        RwLock,
+    },
+};
+
+use eyre::{eyre, Result};
+
+/// How much detail a message gives, from least to most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// A few lines per part
+    Info,
+    /// The steps taken for each piece of input
+    Debug,
+    /// Every step
+    Trace,
+}
+
+impl Level {
+    /// Level for a runner's verbosity, where 0 is silent
+    pub fn from_verbosity(verbosity: u8) -> Option<Level> {
+        match verbosity {
+            0 => None,
+            1 => Some(Level::Info),
+            2 => Some(Level::Debug),
+            _ => Some(Level::Trace),
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Level::Info => write!(f, "info"),
+            Level::Debug => write!(f, "debug"),
+            Level::Trace => write!(f, "trace"),
+        }
+    }
+}
+
+impl FromStr for Level {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(eyre!(
+                "unknown trace level {s:?}, expected info, debug or trace"
+            )),
+        }
+    }
+}
+
+/// Which days trace, and in how much detail. Parsed from comma separated directives, each a
+/// level for every day, like `debug`, or a day with an optional level, like `day13=trace` or
+/// `day7`, which traces everything for that day.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    /// Most detailed level for a day, or for every day if the day is `None`
+    directives: Vec<(Option<u8>, Level)>,
+}
+
+impl Filter {
+    /// Filter that traces one day up to the level
+    pub fn day(day: u8, level: Level) -> Filter {
+        Filter {
+            directives: vec![(Some(day), level)],
+        }
+    }
+
+    /// Most detailed level the day traces at, or `None` if it is silent. A directive for the day
+    /// takes precedence over one for every day.
+    pub fn level(&self, day: Option<u8>) -> Option<Level> {
+        let for_day = self
+            .directives
+            .iter()
+            .rev()
+            .find(|(d, _)| d.is_some() && *d == day);
+        let for_all = self.directives.iter().rev().find(|(d, _)| d.is_none());
+        for_day.or(for_all).map(|(_, level)| *level)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.directives.is_empty()
+    }
+}
+
+impl FromStr for Filter {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parse_day = |day: &str| {
+            day.strip_prefix("day")
+                .and_then(|n| n.parse::<u8>().ok())
+                .ok_or_else(|| eyre!("expected a day like day7 or a level, got {day:?}"))
+        };
+
+        let directives = s
+            .split(',')
+            .map(|directive| match directive.split_once('=') {
+                Some((day, level)) => Ok((Some(parse_day(day)?), level.parse()?)),
+                None => match directive.parse() {
+                    Ok(level) => Ok((None, level)),
+                    Err(_) => Ok((Some(parse_day(directive)?), Level::Trace)),
+                },
+            })
+            .collect::<Result<_>>()?;
+        Ok(Filter { directives })
+    }
+}
+
+/// Filter for messages written to stderr
+static FILTER: RwLock<Filter> = RwLock::new(Filter {
+    directives: Vec::new(),
+});
+
+/// Whether `FILTER` has any directives, so silent days skip taking the lock
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Level and text of the innermost `capture` running on this thread
+    static CAPTURE: RefCell<Option<(Level, String)>> = const { RefCell::new(None) };
+}
+
+/// Writes messages that pass the filter to stderr, replacing the previous filter
+pub fn enable(filter: Filter) {
+    ENABLED.store(!filter.is_empty(), Ordering::Relaxed);
+    *FILTER.write().unwrap() = filter;
+}
+
+/// Runs `f` and returns the messages it wrote up to the level, one per line, in place of
+/// writing them to stderr. Only messages written on the calling thread are captured.
+pub fn capture<R>(level: Level, f: impl FnOnce() -> R) -> (R, String) {
+    let outer = CAPTURE.with(|c| c.replace(Some((level, String::new()))));
+    let result = f();
+    let (_, text) = CAPTURE.with(|c| c.replace(outer)).unwrap();
+    (result, text)
+}
+
+/// Day of a module path like `adventofcode_2022::day13`
+fn day_of(module: &str) -> Option<u8> {
+    module
+        .rsplit("::")
+        .find_map(|m| m.strip_prefix("day")?.parse().ok())
+}
+
+/// Whether a message at the level from the module would be written anywhere. Used by
+/// [`trace!`](crate::trace!) to skip formatting messages nobody sees.
+pub fn enabled(module: &str, level: Level) -> bool {
+    let captured = CAPTURE.with(|c| c.borrow().as_ref().is_some_and(|(l, _)| level <= *l));
+    captured
+        || ENABLED.load(Ordering::Relaxed)
+            && FILTER
+                .read()
+                .unwrap()
+                .level(day_of(module))
+                .is_some_and(|l| level <= l)
+}
+
+/// Writes a message to the innermost capture if there is one, or else to stderr labelled
+/// with its day and level
+pub fn write(module: &str, level: Level, message: fmt::Arguments) {
+    let captured = CAPTURE.with(|c| match c.borrow_mut().as_mut() {
+        Some((l, text)) => {
+            if level <= *l {
+                text.push_str(&message.to_string());
+                text.push('\n');
+            }
+            true
+        }
+        None => false,
+    });
+
+    if !captured {
+        let label = day_of(module).map_or("aoc".to_string(), |day| format!("day{day}"));
+        // Diagnostics are best effort, so a closed stderr is not worth failing a solution for
+        let _ = writeln!(std::io::stderr().lock(), "[{label} {level}] {message}");
+    }
+}
+
+/// Writes a diagnostic message if tracing is enabled for the calling day at the level, which is
+/// one of `Info`, `Debug` or `Trace`. The rest of the arguments are as for `format!`.
+#[macro_export]
+macro_rules! trace {
+    ($level:ident, $($arg:tt)+) => {
+        if $crate::trace::enabled(module_path!(), $crate::trace::Level::$level) {
+            $crate::trace::write(
+                module_path!(),
+                $crate::trace::Level::$level,
+                format_args!($($arg)+),
+            );
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_test() {
+        let filter: Filter = "info,day13=trace,day7".parse().unwrap();
+        assert_eq!(filter.level(Some(13)), Some(Level::Trace));
+        assert_eq!(filter.level(Some(7)), Some(Level::Trace));
+        assert_eq!(filter.level(Some(9)), Some(Level::Info));
+        assert_eq!(filter.level(None), Some(Level::Info));
+
+        let filter: Filter = "day9=debug".parse().unwrap();
+        assert_eq!(filter, Filter::day(9, Level::Debug));
+        assert_eq!(filter.level(Some(7)), None);
+
+        assert!("day9=loud".parse::<Filter>().is_err());
+        assert!("monday".parse::<Filter>().is_err());
+    }
+
+    #[test]
+    fn capture_test() {
+        let ((), text) = capture(Level::Debug, || {
+            crate::trace!(Info, "one");
+            crate::trace!(Debug, "two {}", 2);
+            crate::trace!(Trace, "three");
+        });
+        assert_eq!(text, "one\ntwo 2\n");
+    }
+
+    #[test]
+    fn nested_capture_test() {
+        let (inner, outer) = capture(Level::Trace, || {
+            crate::trace!(Info, "outer");
+            capture(Level::Info, || crate::trace!(Info, "inner")).1
+        });
+        assert_eq!(inner, "inner\n");
+        assert_eq!(outer, "outer\n");
+    }
+
+    #[test]
+    fn day_of_test() {
+        assert_eq!(day_of("adventofcode_2022::day13"), Some(13));
+        assert_eq!(day_of("adventofcode_2022::day13::tests"), Some(13));
+        assert_eq!(day_of("adventofcode_2022::grid"), None);
+    }
+}